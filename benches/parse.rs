@@ -0,0 +1,66 @@
+//! Parsing benchmarks
+//!
+//! Byte string parameters are decoded in bulk with `deserialize_bytes`.  The `seq` benchmarks
+//! decode the same amount of key material element by element for comparison.
+
+use cosey::{define_cose_key, Alg, Kty, P256PublicKey, PublicKey};
+use criterion::{criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
+use serde::Deserialize;
+
+define_cose_key! {
+    /// A key with the size of an ML-DSA-44 public key
+    struct LargeKey {
+        kty: Kty::Other(7),
+        alg: Alg::Other(-48),
+        crv: None,
+        pub_key: [u8; 1312] = -1,
+    }
+}
+
+fn p256(c: &mut Criterion) {
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let data = serde_cbor::to_vec(&key).unwrap();
+
+    let mut group = c.benchmark_group("p256");
+    group.throughput(Throughput::Bytes(data.len() as u64));
+    group.bench_function("from_slice", |b| {
+        b.iter(|| PublicKey::from_slice(&data).unwrap())
+    });
+    group.bench_function("serde_cbor", |b| {
+        b.iter(|| serde_cbor::from_slice::<P256PublicKey>(&data).unwrap())
+    });
+    group.bench_function("cbor_smol", |b| {
+        b.iter(|| cbor_smol::cbor_deserialize::<P256PublicKey>(&data).unwrap())
+    });
+    group.finish();
+}
+
+fn large(c: &mut Criterion) {
+    let key = LargeKey {
+        pub_key: [0x03; 1312],
+    };
+    let data = serde_cbor::to_vec(&key).unwrap();
+    let seq = serde_cbor::to_vec(&key.pub_key.to_vec()).unwrap();
+
+    let mut group = c.benchmark_group("large");
+    group.throughput(Throughput::Bytes(1312));
+    group.bench_function(BenchmarkId::new("bytes", "serde_cbor"), |b| {
+        b.iter(|| serde_cbor::from_slice::<LargeKey>(&data).unwrap())
+    });
+    group.bench_function(BenchmarkId::new("bytes", "in_place"), |b| {
+        let mut place = LargeKey {
+            pub_key: [0x00; 1312],
+        };
+        b.iter(|| {
+            let mut deserializer = serde_cbor::Deserializer::from_slice(&data);
+            LargeKey::deserialize_in_place(&mut deserializer, &mut place).unwrap()
+        })
+    });
+    group.bench_function(BenchmarkId::new("seq", "serde_cbor"), |b| {
+        b.iter(|| serde_cbor::from_slice::<Vec<u8>>(&seq).unwrap())
+    });
+    group.finish();
+}
+
+criterion_group!(benches, p256, large);
+criterion_main!(benches);
@@ -0,0 +1,359 @@
+//! Generates the `iana` module from the registry extracts in the `iana` directory
+//!
+//! Each file is a CSV extract of an IANA COSE registry.  The first three columns are value, name
+//! and variant; the optional columns after them are looked up by their header:
+//!
+//! - `Key Types` (algorithms) or `Key Type` (curves): the compatible key types separated by
+//!   spaces
+//! - `Use` (algorithms): the `KeyUse` variant, or empty for `Other`
+//! - `Hash` (algorithms): the variant of the hash algorithm
+//! - `Key Size` (algorithms) and `Coordinate Size` (curves): a size in bytes
+//!
+//! Trailing empty columns may be omitted.  Lines starting with `#` are added to the
+//! documentation of the following entry.
+
+use std::{collections::HashMap, env, fmt::Write as _, fs, path::Path};
+
+struct Entry {
+    value: i32,
+    name: String,
+    variant: String,
+    // the non-empty optional columns by header
+    columns: HashMap<String, String>,
+    doc: Vec<String>,
+}
+
+impl Entry {
+    fn column(&self, header: &str) -> Option<&str> {
+        self.columns.get(header).map(String::as_str)
+    }
+
+    fn key_types(&self) -> Vec<&str> {
+        self.column("Key Types")
+            .or(self.column("Key Type"))
+            .unwrap_or_default()
+            .split_whitespace()
+            .collect()
+    }
+}
+
+const KEY_USES: &[&str] = &["Signature", "KeyAgreement", "Mac"];
+
+fn read(registry: &str) -> Vec<Entry> {
+    let path = format!("iana/{registry}.csv");
+    println!("cargo:rerun-if-changed={path}");
+    let data = fs::read_to_string(&path).unwrap_or_else(|err| panic!("{path}: {err}"));
+
+    let mut lines = data.lines();
+    let header: Vec<_> = lines.next().unwrap_or_default().split(',').collect();
+    let mut entries = Vec::new();
+    let mut doc = Vec::new();
+    for line in lines {
+        if let Some(comment) = line.strip_prefix('#') {
+            doc.push(comment.trim().to_owned());
+            continue;
+        }
+        let columns: Vec<_> = line.split(',').collect();
+        assert!(
+            columns.len() >= 3 && columns.len() <= header.len(),
+            "{path}: invalid line {line:?}"
+        );
+        let entry = Entry {
+            value: columns[0]
+                .parse()
+                .unwrap_or_else(|err| panic!("{path}: invalid value in {line:?}: {err}")),
+            name: columns[1].to_owned(),
+            variant: columns[2].to_owned(),
+            columns: header
+                .iter()
+                .zip(&columns)
+                .skip(3)
+                .filter(|(_, value)| !value.is_empty())
+                .map(|(header, value)| ((*header).to_owned(), (*value).to_owned()))
+                .collect(),
+            doc: core::mem::take(&mut doc),
+        };
+        if let Some(key_use) = entry.column("Use") {
+            assert!(
+                KEY_USES.contains(&key_use),
+                "{path}: invalid key use in {line:?}"
+            );
+        }
+        entries.push(entry);
+    }
+    entries
+}
+
+// Converts a variant name to the name of a constant, e.g. `EcdhEsHkdf256` to `ECDH_ES_HKDF256`.
+fn constant(variant: &str) -> String {
+    let mut constant = String::new();
+    let mut previous: Option<char> = None;
+    for c in variant.chars() {
+        if c.is_ascii_uppercase()
+            && previous.is_some_and(|p| p.is_ascii_lowercase() || p.is_ascii_digit())
+        {
+            constant.push('_');
+        }
+        constant.push(c.to_ascii_uppercase());
+        previous = Some(c);
+    }
+    constant
+}
+
+fn kty_variant<'a>(ktys: &'a [Entry], name: &str) -> &'a str {
+    ktys.iter()
+        .find(|kty| kty.name == name)
+        .map(|kty| kty.variant.as_str())
+        .unwrap_or_else(|| panic!("unknown key type {name}"))
+}
+
+// Writes a `const fn` that maps the entries with a value in the given column to the result of
+// `arm` and all other values to `default`.
+fn method(
+    out: &mut String,
+    doc: &str,
+    signature: &str,
+    entries: &[Entry],
+    column: &str,
+    arm: impl Fn(&str) -> String,
+    default: &str,
+) {
+    for line in doc.lines() {
+        writeln!(
+            out,
+            "    ///{}{line}",
+            if line.is_empty() { "" } else { " " }
+        )
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "    pub const fn {signature} {{\n        match self {{"
+    )
+    .unwrap();
+    for entry in entries {
+        if let Some(value) = entry.column(column) {
+            writeln!(
+                out,
+                "            Self::{} => {},",
+                entry.variant,
+                arm(value)
+            )
+            .unwrap();
+        }
+    }
+    writeln!(out, "            _ => {default},\n        }}\n    }}\n").unwrap();
+}
+
+fn registry(out: &mut String, doc: &str, name: &str, entries: &[Entry]) {
+    writeln!(out, "registry! {{").unwrap();
+    for line in doc.lines() {
+        writeln!(
+            out,
+            "    ///{}{line}",
+            if line.is_empty() { "" } else { " " }
+        )
+        .unwrap();
+    }
+    writeln!(out, "    pub enum {name} {{").unwrap();
+    for entry in entries {
+        writeln!(out, "        /// `{}`", entry.name).unwrap();
+        if !entry.doc.is_empty() {
+            writeln!(out, "        ///").unwrap();
+        }
+        for line in &entry.doc {
+            writeln!(out, "        /// {line}").unwrap();
+        }
+        writeln!(
+            out,
+            "        {} = {} => {:?},",
+            entry.variant, entry.value, entry.name
+        )
+        .unwrap();
+    }
+    writeln!(out, "    }}\n}}\n").unwrap();
+}
+
+// Writes the values as constants.  Each alias is a constant name, its documentation and the
+// variant whose value it has.
+fn constants(
+    out: &mut String,
+    module: &str,
+    registry: &str,
+    entries: &[Entry],
+    aliases: &[(&str, &str, &str)],
+) {
+    writeln!(out, "/// Values of the {registry} registry").unwrap();
+    writeln!(out, "pub mod {module} {{").unwrap();
+    for entry in entries {
+        writeln!(out, "    /// `{}`", entry.name).unwrap();
+        writeln!(
+            out,
+            "    pub const {}: i32 = {};",
+            constant(&entry.variant),
+            entry.value
+        )
+        .unwrap();
+    }
+    for (name, doc, variant) in aliases {
+        writeln!(out, "    /// {doc}").unwrap();
+        writeln!(out, "    pub const {name}: i32 = {};", constant(variant)).unwrap();
+    }
+    writeln!(out, "}}\n").unwrap();
+}
+
+fn main() {
+    println!("cargo:rerun-if-changed=build.rs");
+
+    let ktys = read("kty");
+    let algs = read("alg");
+    let crvs = read("crv");
+    let key_ops = read("key_ops");
+
+    let mut out = String::new();
+    registry(
+        &mut out,
+        "COSE key type (`kty`)\n\nSee the IANA \"COSE Key Types\" registry.",
+        "Kty",
+        &ktys,
+    );
+    registry(
+        &mut out,
+        "COSE algorithm (`alg`)\n\nSee the IANA \"COSE Algorithms\" registry.  The key types of this \
+         crate only support\n[`Es256`][Self::Es256], [`EdDsa`][Self::EdDsa], \
+         [`Totp`][Self::Totp] and\n[`EcdhEsHkdf256`][Self::EcdhEsHkdf256].",
+        "Alg",
+        &algs,
+    );
+    registry(
+        &mut out,
+        "COSE elliptic curve (`crv`)\n\nSee the IANA \"COSE Elliptic Curves\" registry.",
+        "Crv",
+        &crvs,
+    );
+    registry(
+        &mut out,
+        "COSE key operation (`key_ops`)\n\nSee the \"Key Operation Values\" table of RFC 9052, \
+         section 7.1.",
+        "KeyOp",
+        &key_ops,
+    );
+
+    let kty = |name: &str| format!("Kty::{}", kty_variant(&ktys, name));
+    let alg = |name: &str| {
+        assert!(
+            algs.iter().any(|alg| alg.variant == name),
+            "unknown algorithm {name}"
+        );
+        format!("Some(Self::{name})")
+    };
+    let size = |size: &str| {
+        let size: usize = size
+            .parse()
+            .unwrap_or_else(|err| panic!("invalid size {size}: {err}"));
+        format!("Some({size})")
+    };
+
+    writeln!(out, "impl Alg {{").unwrap();
+    method(
+        &mut out,
+        "Returns the key types that can be used with the algorithm.\n\nThe result is empty for \
+         algorithms that do not use a key, e.g. hash functions, and for\nvalues that are not listed.",
+        "key_types(self) -> &'static [Kty]",
+        &algs,
+        "Key Types",
+        |key_types| {
+            let key_types: Vec<_> = key_types.split_whitespace().map(kty).collect();
+            format!("&[{}]", key_types.join(", "))
+        },
+        "&[]",
+    );
+    method(
+        &mut out,
+        "Returns what keys for the algorithm are used for.",
+        "key_use(self) -> KeyUse",
+        &algs,
+        "Use",
+        |key_use| format!("KeyUse::{key_use}"),
+        "KeyUse::Other",
+    );
+    method(
+        &mut out,
+        "Returns the hash function that is used by the algorithm, e.g. for signatures, MACs or \
+         key\nderivation.\n\nReturns `None` if the algorithm does not use a hash function, if \
+         the hash function is\nimplied by the key, as for EdDSA and HSS-LMS, or if the value is \
+         not listed.",
+        "hash(self) -> Option<Alg>",
+        &algs,
+        "Hash",
+        alg,
+        "None",
+    );
+    method(
+        &mut out,
+        "Returns the size of symmetric keys for the algorithm in bytes.\n\nReturns `None` for \
+         asymmetric algorithms, for algorithms with variable key sizes, e.g.\nHMAC, and for \
+         values that are not listed.",
+        "key_size(self) -> Option<usize>",
+        &algs,
+        "Key Size",
+        size,
+        "None",
+    );
+    writeln!(out, "}}\n").unwrap();
+
+    for crv in &crvs {
+        assert!(
+            crv.key_types().len() == 1,
+            "curve {} must have exactly one key type",
+            crv.name
+        );
+    }
+    writeln!(out, "impl Crv {{").unwrap();
+    method(
+        &mut out,
+        "Returns the key type of keys on the curve, or `None` for values that are not listed.",
+        "key_type(self) -> Option<Kty>",
+        &crvs,
+        "Key Type",
+        |name| format!("Some({})", kty(name)),
+        "None",
+    );
+    method(
+        &mut out,
+        "Returns the size of a coordinate in bytes, or `None` for values that are not listed.\n\n\
+         For OKP curves, this is the size of the public key `x`.  For EC2 curves, the\n\
+         coordinates `x` and `y` have this size, so an uncompressed public key has twice\nthis \
+         size.",
+        "coordinate_size(self) -> Option<usize>",
+        &crvs,
+        "Coordinate Size",
+        size,
+        "None",
+    );
+    writeln!(out, "}}\n").unwrap();
+
+    constants(&mut out, "kty", "COSE Key Types", &ktys, &[]);
+    constants(
+        &mut out,
+        "alg",
+        "COSE Algorithms",
+        &algs,
+        &[(
+            "TOTP",
+            "TOTP, see [`Alg::Totp`][crate::Alg::Totp]",
+            "Esp256",
+        )],
+    );
+    constants(&mut out, "crv", "COSE Elliptic Curves", &crvs, &[]);
+    constants(
+        &mut out,
+        "key_op",
+        "COSE Key Operation Values",
+        &key_ops,
+        &[],
+    );
+
+    let path = Path::new(&env::var("OUT_DIR").unwrap()).join("iana.rs");
+    fs::write(path, out).unwrap();
+}
@@ -0,0 +1,179 @@
+//! Implementations of [`Arbitrary`] for fuzzing
+//!
+//! The generated keys have the structure of valid keys, but their key material is arbitrary, so
+//! e.g. P-256 coordinates are usually not on the curve.  [`RawCoseKey`] is generated with
+//! arbitrary fields to reach the validation in [`PublicKey::from_raw`].
+
+use ::arbitrary::{Arbitrary, Error, Result, Unstructured};
+
+#[cfg(feature = "ecdh")]
+use crate::EcdhEsHkdf256PublicKey;
+#[cfg(all(feature = "ecdh", feature = "serde"))]
+use crate::EcdhEsHkdf256PublicKeyRef;
+#[cfg(feature = "ed25519")]
+use crate::Ed25519PublicKey;
+#[cfg(all(feature = "ed25519", feature = "serde"))]
+use crate::Ed25519PublicKeyRef;
+#[cfg(feature = "p256")]
+use crate::P256PublicKey;
+#[cfg(all(feature = "p256", feature = "serde"))]
+use crate::P256PublicKeyRef;
+#[cfg(feature = "totp")]
+use crate::TotpPublicKey;
+#[cfg(feature = "x25519")]
+use crate::X25519PublicKey;
+use crate::{Bytes, KeyOpEntry, KeyOps, PublicKey, RawCoseKey, MAX_KEY_OPS, MAX_KEY_OP_LEN};
+
+#[cfg(feature = "p256")]
+impl<'a> Arbitrary<'a> for P256PublicKey {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            x: u.arbitrary()?,
+            y: u.arbitrary()?,
+        })
+    }
+}
+
+#[cfg(feature = "ecdh")]
+impl<'a> Arbitrary<'a> for EcdhEsHkdf256PublicKey {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            x: u.arbitrary()?,
+            y: u.arbitrary()?,
+        })
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl<'a> Arbitrary<'a> for Ed25519PublicKey {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self { x: u.arbitrary()? })
+    }
+}
+
+#[cfg(feature = "totp")]
+impl<'a> Arbitrary<'a> for TotpPublicKey {
+    fn arbitrary(_u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {})
+    }
+}
+
+#[cfg(feature = "x25519")]
+impl<'a> Arbitrary<'a> for X25519PublicKey {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            pub_key: u.arbitrary()?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for PublicKey {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        type Variant<'a> = fn(&mut Unstructured<'a>) -> Result<PublicKey>;
+        let variants: &[Variant<'a>] = &[
+            #[cfg(feature = "p256")]
+            |u| u.arbitrary().map(Self::P256Key),
+            #[cfg(feature = "ecdh")]
+            |u| u.arbitrary().map(Self::EcdhEsHkdf256Key),
+            #[cfg(feature = "ed25519")]
+            |u| u.arbitrary().map(Self::Ed25519Key),
+            #[cfg(feature = "totp")]
+            |u| u.arbitrary().map(Self::TotpKey),
+            #[cfg(feature = "x25519")]
+            |u| u.arbitrary().map(Self::X25519Key),
+        ];
+        u.choose(variants)?(u)
+    }
+}
+
+impl<'a> Arbitrary<'a> for RawCoseKey {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            kty: u.arbitrary()?,
+            alg: u.arbitrary()?,
+            key_ops: arbitrary_key_ops(u)?,
+            crv: u.arbitrary()?,
+            x: arbitrary_bytes(u)?,
+            y: arbitrary_bytes(u)?,
+        })
+    }
+}
+
+impl<'a> Arbitrary<'a> for KeyOpEntry {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        if u.arbitrary()? {
+            return u.arbitrary().map(Self::Int);
+        }
+        let mut text = heapless::String::new();
+        for _ in 0..u.int_in_range(0..=MAX_KEY_OP_LEN)? {
+            // stop at the first character that does not fit
+            if text.push(u.arbitrary()?).is_err() {
+                break;
+            }
+        }
+        Ok(Self::Text(text))
+    }
+}
+
+#[cfg(all(feature = "p256", feature = "serde"))]
+impl<'a> Arbitrary<'a> for P256PublicKeyRef<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            x: arbitrary_array(u)?,
+            y: arbitrary_array(u)?,
+        })
+    }
+}
+
+#[cfg(all(feature = "ecdh", feature = "serde"))]
+impl<'a> Arbitrary<'a> for EcdhEsHkdf256PublicKeyRef<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            x: arbitrary_array(u)?,
+            y: arbitrary_array(u)?,
+        })
+    }
+}
+
+#[cfg(all(feature = "ed25519", feature = "serde"))]
+impl<'a> Arbitrary<'a> for Ed25519PublicKeyRef<'a> {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Ok(Self {
+            x: arbitrary_array(u)?,
+        })
+    }
+}
+
+// A non-empty `key_ops` field, or `None`
+fn arbitrary_key_ops(u: &mut Unstructured<'_>) -> Result<Option<KeyOps>> {
+    if !u.arbitrary()? {
+        return Ok(None);
+    }
+    let mut key_ops = KeyOps::new();
+    for _ in 0..u.int_in_range(1..=MAX_KEY_OPS)? {
+        key_ops
+            .push(u.arbitrary()?)
+            .map_err(|_| Error::IncorrectFormat)?;
+    }
+    Ok(Some(key_ops))
+}
+
+// A byte string of at most `N` bytes, or `None`
+fn arbitrary_bytes<const N: usize>(u: &mut Unstructured<'_>) -> Result<Option<Bytes<N>>> {
+    if !u.arbitrary()? {
+        return Ok(None);
+    }
+    let len = u.int_in_range(0..=N)?;
+    Bytes::from_slice(u.bytes(len)?)
+        .map(Some)
+        .map_err(|_| Error::IncorrectFormat)
+}
+
+// An array borrowed from the input
+#[cfg(all(
+    any(feature = "ecdh", feature = "ed25519", feature = "p256"),
+    feature = "serde"
+))]
+fn arbitrary_array<'a, const N: usize>(u: &mut Unstructured<'a>) -> Result<&'a [u8; N]> {
+    u.bytes(N)?.try_into().map_err(|_| Error::NotEnoughData)
+}
@@ -0,0 +1,109 @@
+//! Unpadded base64url (RFC 4648, section 5) for the human-readable serde format
+//!
+//! Neither direction allocates: encoding writes the text in chunks through a small stack buffer
+//! and decoding writes into a buffer provided by the caller.
+
+use core::fmt::{self, Formatter};
+
+use crate::Error;
+
+const ALPHABET: &[u8; 64] = b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789-_";
+
+// The number of bytes that are encoded per `write_str` call.  It must be a multiple of three so
+// that only the last chunk is padded.
+const CHUNK_LEN: usize = 48;
+
+/// Formats a byte string as unpadded base64url
+pub(crate) struct Base64Url<'a>(pub(crate) &'a [u8]);
+
+impl fmt::Display for Base64Url<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        for chunk in self.0.chunks(CHUNK_LEN) {
+            let mut buffer = [0; CHUNK_LEN / 3 * 4];
+            let mut len = 0;
+            for (group, output) in chunk.chunks(3).zip(buffer.chunks_mut(4)) {
+                let mut bytes = [0; 4];
+                for (byte, value) in bytes.iter_mut().skip(1).zip(group) {
+                    *byte = *value;
+                }
+                let bits = u32::from_be_bytes(bytes);
+                // n bytes are encoded with n + 1 characters
+                let chars = group.len() + 1;
+                for (output, shift) in output.iter_mut().zip([18, 12, 6, 0]).take(chars) {
+                    let index = (bits >> shift) & 0x3f;
+                    *output = ALPHABET.get(index as usize).copied().unwrap_or(b'A');
+                }
+                len += chars;
+            }
+            // the alphabet is ASCII
+            let text = buffer
+                .get(..len)
+                .and_then(|text| core::str::from_utf8(text).ok())
+                .ok_or(fmt::Error)?;
+            f.write_str(text)?;
+        }
+        Ok(())
+    }
+}
+
+fn value(char: u8) -> Option<u8> {
+    match char {
+        b'A'..=b'Z' => Some(char - b'A'),
+        b'a'..=b'z' => Some(char - b'a' + 26),
+        b'0'..=b'9' => Some(char - b'0' + 52),
+        b'-' => Some(62),
+        b'_' => Some(63),
+        _ => None,
+    }
+}
+
+/// Returns the length of the data encoded by `text`.
+///
+/// Fails with [`Error::InvalidBase64Url`] if `text` is not the canonical unpadded base64url
+/// encoding of a byte string, i.e. if it has padding, characters outside of the alphabet, an
+/// impossible length or unused bits that are not zero.
+pub(crate) fn decoded_len(label: i8, text: &str) -> Result<usize, Error> {
+    let invalid = Error::InvalidBase64Url { label };
+    let (len, unused_bits) = match text.len() % 4 {
+        0 => (0, 0),
+        2 => (1, 0x0f),
+        3 => (2, 0x03),
+        _ => return Err(invalid),
+    };
+    if text.bytes().any(|char| value(char).is_none()) {
+        return Err(invalid);
+    }
+    let last = text.bytes().last().and_then(value).unwrap_or(0);
+    if last & unused_bits != 0 {
+        return Err(invalid);
+    }
+    Ok(text.len() / 4 * 3 + len)
+}
+
+/// Decodes `text` into `output`, which must have the length of the encoded data.
+///
+/// `text` is validated before anything is written, so `output` is unchanged on error.  Data
+/// with another length is rejected with [`Error::InvalidLength`].
+pub(crate) fn decode(label: i8, text: &str, output: &mut [u8]) -> Result<(), Error> {
+    let len = decoded_len(label, text)?;
+    if len != output.len() {
+        return Err(Error::InvalidLength {
+            label,
+            expected: output.len(),
+            actual: len,
+        });
+    }
+    for (group, output) in text.as_bytes().chunks(4).zip(output.chunks_mut(3)) {
+        let mut bits = 0u32;
+        for (char, shift) in group.iter().zip([18, 12, 6, 0]) {
+            bits |= u32::from(value(*char).unwrap_or(0)) << shift;
+        }
+        for (output, byte) in output
+            .iter_mut()
+            .zip(bits.to_be_bytes().into_iter().skip(1))
+        {
+            *output = byte;
+        }
+    }
+    Ok(())
+}
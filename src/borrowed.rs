@@ -0,0 +1,99 @@
+//! Key types that borrow their parameters from the input
+//!
+//! The types are defined with [`define_cose_key`](crate::define_cose_key), so they accept the
+//! same [`ParseOptions`](crate::ParseOptions), except for
+//! [`pad_short_coordinates`](crate::ParseOptions::pad_short_coordinates) as short coordinates
+//! cannot be padded without copying them.  Deserialization fails with
+//! [`Error::NotBorrowed`](crate::Error::NotBorrowed) if the deserializer does not borrow byte
+//! strings from its input, e.g. if it reads from a stream.
+
+use crate::define_cose_key;
+#[cfg(feature = "ecdh")]
+use crate::EcdhEsHkdf256PublicKey;
+#[cfg(feature = "ed25519")]
+use crate::Ed25519PublicKey;
+#[cfg(feature = "p256")]
+use crate::P256PublicKey;
+
+#[cfg(feature = "p256")]
+define_cose_key! {
+    /// A borrowed [`P256PublicKey`]
+    pub struct P256PublicKeyRef<'a> {
+        kty: crate::Kty::Ec2,
+        alg: crate::Alg::Es256,
+        crv: Some(crate::Crv::P256),
+        pub x: &'a [u8; 32] = -2,
+        pub y: &'a [u8; 32] = -3,
+    }
+}
+
+#[cfg(feature = "ecdh")]
+define_cose_key! {
+    /// A borrowed [`EcdhEsHkdf256PublicKey`]
+    pub struct EcdhEsHkdf256PublicKeyRef<'a> {
+        kty: crate::Kty::Ec2,
+        alg: crate::Alg::EcdhEsHkdf256,
+        crv: Some(crate::Crv::P256),
+        pub x: &'a [u8; 32] = -2,
+        pub y: &'a [u8; 32] = -3,
+    }
+}
+
+#[cfg(feature = "ed25519")]
+define_cose_key! {
+    /// A borrowed [`Ed25519PublicKey`]
+    pub struct Ed25519PublicKeyRef<'a> {
+        kty: crate::Kty::Okp,
+        alg: crate::Alg::EdDsa,
+        crv: Some(crate::Crv::Ed25519),
+        pub x: &'a [u8; 32] = -2,
+    }
+}
+
+#[cfg(feature = "p256")]
+impl<'a> From<&'a P256PublicKey> for P256PublicKeyRef<'a> {
+    fn from(key: &'a P256PublicKey) -> Self {
+        Self {
+            x: &key.x,
+            y: &key.y,
+        }
+    }
+}
+
+#[cfg(feature = "p256")]
+impl From<P256PublicKeyRef<'_>> for P256PublicKey {
+    fn from(key: P256PublicKeyRef<'_>) -> Self {
+        Self::from_coordinates(*key.x, *key.y)
+    }
+}
+
+#[cfg(feature = "ecdh")]
+impl<'a> From<&'a EcdhEsHkdf256PublicKey> for EcdhEsHkdf256PublicKeyRef<'a> {
+    fn from(key: &'a EcdhEsHkdf256PublicKey) -> Self {
+        Self {
+            x: &key.x,
+            y: &key.y,
+        }
+    }
+}
+
+#[cfg(feature = "ecdh")]
+impl From<EcdhEsHkdf256PublicKeyRef<'_>> for EcdhEsHkdf256PublicKey {
+    fn from(key: EcdhEsHkdf256PublicKeyRef<'_>) -> Self {
+        Self::from_coordinates(*key.x, *key.y)
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl<'a> From<&'a Ed25519PublicKey> for Ed25519PublicKeyRef<'a> {
+    fn from(key: &'a Ed25519PublicKey) -> Self {
+        Self { x: &key.x }
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl From<Ed25519PublicKeyRef<'_>> for Ed25519PublicKey {
+    fn from(key: Ed25519PublicKeyRef<'_>) -> Self {
+        Self::from(*key.x)
+    }
+}
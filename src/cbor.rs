@@ -0,0 +1,564 @@
+//! Parsing helpers based on a minimal CBOR decoder
+//!
+//! The decoder implements `serde::Deserializer` for the data model of CBOR (RFC 8949) without
+//! indefinite-length byte and text strings.  It is used instead of a general-purpose CBOR crate
+//! so that the encoding rules can be relaxed with [`ParseOptions`] and errors can be reported
+//! as [`Error`].  The matching encoder is in the `ser` module.  Without the `serde` feature,
+//! only the heads of data items are decoded here, for the [`StreamDecoder`][crate::StreamDecoder].
+
+#![deny(clippy::arithmetic_side_effects)]
+
+#[cfg(feature = "serde")]
+use core::fmt::{self, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::de::{DeserializeSeed, IgnoredAny, MapAccess, SeqAccess, Visitor};
+#[cfg(feature = "serde")]
+use serde::Deserialize as _;
+
+#[cfg(feature = "serde")]
+use crate::{Alg, Bytes, Kty, Label, PublicKey, RawCoseKey};
+use crate::{Error, ParseOptions, TagPolicy};
+
+#[cfg(feature = "serde")]
+pub(crate) mod ser;
+
+// Limits the recursion when skipping nested unknown values.
+pub(crate) const MAX_DEPTH: usize = 16;
+
+pub(crate) const BREAK: u8 = 0xff;
+
+#[cfg(feature = "serde")]
+impl RawCoseKey {
+    /// Parses the COSE_Key at the start of `data` and returns it together with the number of
+    /// bytes it occupies.
+    pub fn take_from_slice(data: &[u8], options: ParseOptions) -> Result<(Self, usize), Error> {
+        let mut deserializer = Deserializer::new(data, options);
+        let raw = Self::try_deserialize_with(&mut deserializer, options).map_err(|err| err.0)??;
+        Ok((raw, deserializer.offset(data)))
+    }
+
+    /// Parses a COSE_Key that occupies all of `data`.
+    ///
+    /// If there are bytes after the key, [`Error::TrailingData`] is returned.
+    pub fn from_slice_exact(data: &[u8], options: ParseOptions) -> Result<Self, Error> {
+        exact(Self::take_from_slice(data, options)?, data)
+    }
+
+    /// Serializes the key in the canonical CTAP2 form.
+    ///
+    /// Returns [`Error::BufferTooSmall`] if the key does not fit into `N` bytes.
+    pub fn to_bytes<const N: usize>(&self) -> Result<Bytes<N>, Error> {
+        ser::to_bytes(self)
+    }
+
+    /// Serializes the key in the canonical CTAP2 form into a `heapless::Vec`.
+    ///
+    /// Returns [`Error::BufferTooSmall`] if the key does not fit into `N` bytes.
+    pub fn to_heapless_vec<const N: usize>(&self) -> Result<heapless::Vec<u8, N>, Error> {
+        self.to_bytes().map(Bytes::into_vec)
+    }
+
+    /// Appends the key in the canonical CTAP2 form to `output` and returns the length of the
+    /// encoding.
+    ///
+    /// Returns [`Error::BufferTooSmall`] and leaves `output` unchanged if the key does not fit.
+    pub fn append_to<const N: usize>(
+        &self,
+        output: &mut heapless::Vec<u8, N>,
+    ) -> Result<usize, Error> {
+        ser::append_to(self, output)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl PublicKey {
+    /// Parses the COSE_Key at the start of `data` and returns it together with the number of
+    /// bytes it occupies.
+    pub fn take_from_slice(data: &[u8], options: ParseOptions) -> Result<(Self, usize), Error> {
+        let (raw, len) = RawCoseKey::take_from_slice(data, options)?;
+        Ok((Self::from_raw(raw, options)?, len))
+    }
+
+    /// Parses a COSE_Key that occupies all of `data`.
+    ///
+    /// If there are bytes after the key, [`Error::TrailingData`] is returned.
+    pub fn from_slice_exact(data: &[u8], options: ParseOptions) -> Result<Self, Error> {
+        exact(Self::take_from_slice(data, options)?, data)
+    }
+
+    /// Parses a COSE_Key that occupies all of `data` using the default options.
+    pub fn from_slice(data: &[u8]) -> Result<Self, Error> {
+        Self::from_slice_exact(data, ParseOptions::default())
+    }
+
+    /// Serializes the key in the canonical CTAP2 form.
+    ///
+    /// Returns [`Error::BufferTooSmall`] if the key does not fit into `N` bytes.
+    pub fn to_bytes<const N: usize>(&self) -> Result<Bytes<N>, Error> {
+        ser::to_bytes(self)
+    }
+
+    /// Serializes the key in the canonical CTAP2 form into a `heapless::Vec`.
+    ///
+    /// Returns [`Error::BufferTooSmall`] if the key does not fit into `N` bytes.
+    pub fn to_heapless_vec<const N: usize>(&self) -> Result<heapless::Vec<u8, N>, Error> {
+        self.to_bytes().map(Bytes::into_vec)
+    }
+
+    /// Appends the key in the canonical CTAP2 form to `output` and returns the length of the
+    /// encoding.
+    ///
+    /// Returns [`Error::BufferTooSmall`] and leaves `output` unchanged if the key does not fit.
+    pub fn append_to<const N: usize>(
+        &self,
+        output: &mut heapless::Vec<u8, N>,
+    ) -> Result<usize, Error> {
+        ser::append_to(self, output)
+    }
+}
+
+#[cfg(feature = "serde")]
+pub(crate) fn exact<T>((key, consumed): (T, usize), data: &[u8]) -> Result<T, Error> {
+    if consumed == data.len() {
+        Ok(key)
+    } else {
+        Err(Error::TrailingData { consumed })
+    }
+}
+
+/// Returns the key type of the COSE_Key at the start of `data` without decoding the other fields.
+///
+/// The key must be encoded as required by [`ParseOptions::new`], except that only `kty` is
+/// checked.
+#[cfg(feature = "serde")]
+pub fn peek_kty(data: &[u8]) -> Result<Kty, Error> {
+    peek(data).map(|(kty, _)| kty)
+}
+
+/// Returns the algorithm of the COSE_Key at the start of `data` without decoding the other
+/// fields.
+///
+/// The key must be encoded as required by [`ParseOptions::new`], except that only `kty` and `alg`
+/// are checked.
+#[cfg(feature = "serde")]
+pub fn peek_alg(data: &[u8]) -> Result<Option<Alg>, Error> {
+    peek(data).map(|(_, alg)| alg)
+}
+
+// Reads the fields up to alg, which are the first known fields in canonical order.
+#[cfg(feature = "serde")]
+fn peek(data: &[u8]) -> Result<(Kty, Option<Alg>), Error> {
+    let mut deserializer = Deserializer::new(data, ParseOptions::new());
+    let mut access = match deserializer.head()? {
+        (5, _, argument) => Access::new(&mut deserializer, argument),
+        _ => return Err(Error::InvalidKey),
+    };
+
+    let kty = match access.next_known_label()? {
+        Some(label @ Label::Kty) => {
+            let value = access.de.int()?;
+            Kty::try_from(value).map_err(|_| Error::UnsupportedValue {
+                label: label as i8,
+                value,
+            })?
+        }
+        Some(_) => return Err(Error::NonCanonicalOrder),
+        None => return Err(Error::MissingField("kty")),
+    };
+    let alg = match access.next_known_label()? {
+        Some(label @ Label::Alg) => {
+            let value = access.de.int()?;
+            let alg = Alg::try_from(value).map_err(|_| Error::UnsupportedValue {
+                label: label as i8,
+                value,
+            })?;
+            Some(alg)
+        }
+        _ => None,
+    };
+    Ok((kty, alg))
+}
+
+#[cfg(feature = "serde")]
+#[derive(Debug)]
+pub(crate) struct CborError(pub(crate) Error);
+
+#[cfg(feature = "serde")]
+impl From<Error> for CborError {
+    fn from(error: Error) -> Self {
+        Self(error)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl fmt::Display for CborError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        fmt::Display::fmt(&self.0, f)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::StdError for CborError {}
+
+#[cfg(feature = "serde")]
+impl serde::ser::Error for CborError {
+    // Errors raised by `Serialize` implementations mean that a value can't be represented.
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        Self(Error::InvalidKey)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl serde::de::Error for CborError {
+    // Errors raised by `Deserialize` implementations mean that a value has the wrong type or is
+    // out of range.
+    fn custom<T: fmt::Display>(_msg: T) -> Self {
+        Self(Error::InvalidKey)
+    }
+}
+
+// The argument of a data item head, see RFC 8949, section 3
+#[derive(Clone, Copy)]
+pub(crate) enum Argument {
+    Value(u64),
+    Indefinite,
+}
+
+pub(crate) struct Deserializer<'de> {
+    pub(crate) input: &'de [u8],
+    allow_non_minimal: bool,
+    #[cfg(feature = "serde")]
+    tags: TagPolicy,
+    #[cfg(feature = "serde")]
+    max_string_len: Option<usize>,
+    #[cfg(feature = "serde")]
+    depth: usize,
+}
+
+impl<'de> Deserializer<'de> {
+    pub(crate) fn new(input: &'de [u8], options: ParseOptions) -> Self {
+        Self {
+            input,
+            allow_non_minimal: options.allow_non_minimal,
+            #[cfg(feature = "serde")]
+            tags: options.tags,
+            #[cfg(feature = "serde")]
+            max_string_len: options.max_string_len,
+            #[cfg(feature = "serde")]
+            depth: 0,
+        }
+    }
+
+    // The number of bytes of `data` that have been consumed, if the deserializer was created for
+    // `data`.
+    #[cfg(feature = "serde")]
+    pub(crate) fn offset(&self, data: &[u8]) -> usize {
+        data.len().saturating_sub(self.input.len())
+    }
+
+    #[cfg(feature = "serde")]
+    fn peek(&self) -> Result<u8, Error> {
+        self.input.first().copied().ok_or(Error::Truncated)
+    }
+
+    fn take(&mut self, n: usize) -> Result<&'de [u8], Error> {
+        if n > self.input.len() {
+            return Err(Error::Truncated);
+        }
+        let (bytes, rest) = self.input.split_at(n);
+        self.input = rest;
+        Ok(bytes)
+    }
+
+    fn take_array<const N: usize>(&mut self) -> Result<[u8; N], Error> {
+        let mut array = [0; N];
+        array.copy_from_slice(self.take(N)?);
+        Ok(array)
+    }
+
+    #[cfg(feature = "serde")]
+    fn take_len(&mut self, len: u64) -> Result<&'de [u8], Error> {
+        check_string_len(self.max_string_len, len)?;
+        // a length that does not fit into usize can never be satisfied by the input
+        let len = usize::try_from(len).map_err(|_| Error::Truncated)?;
+        self.take(len)
+    }
+
+    // Reads the head of a data item, returning the major type, the additional information and
+    // the argument.  For major type 7, the argument is the raw value of the simple value or float.
+    pub(crate) fn head(&mut self) -> Result<(u8, u8, Argument), Error> {
+        let [initial] = self.take_array()?;
+        let (major, info) = (initial >> 5, initial & 0x1f);
+        let (value, min) = match info {
+            0..=23 => return Ok((major, info, Argument::Value(info.into()))),
+            24 => (u8::from_be_bytes(self.take_array()?).into(), 24),
+            25 => (u16::from_be_bytes(self.take_array()?).into(), 0x100),
+            26 => (u32::from_be_bytes(self.take_array()?).into(), 0x1_0000),
+            27 => (u64::from_be_bytes(self.take_array()?), 0x1_0000_0000),
+            31 => return Ok((major, info, Argument::Indefinite)),
+            _ => return Err(Error::InvalidKey),
+        };
+        // floats have a fixed size that is given by the additional information
+        let is_float = major == 7 && info != 24;
+        if value < min && !is_float && !self.allow_non_minimal {
+            return Err(Error::NonMinimal);
+        }
+        Ok((major, info, Argument::Value(value)))
+    }
+
+    #[cfg(feature = "serde")]
+    fn int(&mut self) -> Result<i64, Error> {
+        let (major, _, argument) = self.head()?;
+        int(major, argument)
+    }
+
+    #[cfg(feature = "serde")]
+    fn nested<T>(
+        &mut self,
+        f: impl FnOnce(&mut Self) -> Result<T, CborError>,
+    ) -> Result<T, CborError> {
+        let depth = self.depth;
+        self.depth = depth
+            .checked_add(1)
+            .filter(|depth| *depth <= MAX_DEPTH)
+            .ok_or(Error::InvalidKey)?;
+        let result = f(self);
+        self.depth = depth;
+        result
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = CborError;
+
+    fn deserialize_any<V>(self, visitor: V) -> Result<V::Value, CborError>
+    where
+        V: Visitor<'de>,
+    {
+        let (mut major, mut info, mut argument) = self.head()?;
+        while let (6, Argument::Value(tag)) = (major, argument) {
+            check_tag(self.tags, self.depth, tag)?;
+            (major, info, argument) = self.head()?;
+        }
+
+        match (major, argument) {
+            (0, Argument::Value(value)) => visitor.visit_u64(value),
+            (1, Argument::Value(value)) => match i64::try_from(value) {
+                // -1 - value
+                Ok(value) => visitor.visit_i64(!value),
+                Err(_) => visitor.visit_i128(!i128::from(value)),
+            },
+            (2, Argument::Value(len)) => visitor.visit_borrowed_bytes(self.take_len(len)?),
+            (3, Argument::Value(len)) => {
+                let text =
+                    core::str::from_utf8(self.take_len(len)?).map_err(|_| Error::InvalidKey)?;
+                visitor.visit_borrowed_str(text)
+            }
+            (4, argument) => self.nested(|de| {
+                let mut access = Access::new(de, argument);
+                let value = visitor.visit_seq(&mut access)?;
+                access.end().map(|()| value)
+            }),
+            (5, argument) => self.nested(|de| {
+                let mut access = Access::new(de, argument);
+                let value = visitor.visit_map(&mut access)?;
+                access.end().map(|()| value)
+            }),
+            (7, Argument::Value(value)) => match (info, value) {
+                (20, _) => visitor.visit_bool(false),
+                (21, _) => visitor.visit_bool(true),
+                // null and undefined
+                (22 | 23, _) => visitor.visit_unit(),
+                (25, value) => visitor.visit_f64(f16_to_f64(value as u16)),
+                (26, value) => visitor.visit_f32(f32::from_bits(value as u32)),
+                (27, value) => visitor.visit_f64(f64::from_bits(value)),
+                _ => Err(Error::InvalidKey.into()),
+            },
+            // indefinite-length strings, breaks and reserved values
+            _ => Err(Error::InvalidKey.into()),
+        }
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+// Access to the elements of an array or the entries of a map
+#[cfg(feature = "serde")]
+struct Access<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+    // `None` for indefinite-length items that have not reached the break
+    remaining: Option<u64>,
+}
+
+#[cfg(feature = "serde")]
+impl<'a, 'de> Access<'a, 'de> {
+    fn new(de: &'a mut Deserializer<'de>, argument: Argument) -> Self {
+        let remaining = match argument {
+            Argument::Value(len) => Some(len),
+            Argument::Indefinite => None,
+        };
+        Self { de, remaining }
+    }
+
+    fn has_next(&mut self) -> Result<bool, Error> {
+        match self.remaining {
+            Some(0) => Ok(false),
+            Some(n) => {
+                self.remaining = n.checked_sub(1);
+                Ok(true)
+            }
+            None if self.de.peek()? == BREAK => {
+                self.de.take(1)?;
+                self.remaining = Some(0);
+                Ok(false)
+            }
+            None => Ok(true),
+        }
+    }
+
+    // Skips entries with unknown labels and returns the next known label.
+    fn next_known_label(&mut self) -> Result<Option<Label>, Error> {
+        while self.has_next()? {
+            if let Ok(label) = Label::try_from(self.de.int()?) {
+                return Ok(Some(label));
+            }
+            IgnoredAny::deserialize(&mut *self.de).map_err(|err| err.0)?;
+        }
+        Ok(None)
+    }
+
+    // Checks that the visitor consumed all elements so that the input stays in sync.
+    fn end(&self) -> Result<(), CborError> {
+        match self.remaining {
+            Some(0) => Ok(()),
+            _ => Err(Error::InvalidKey.into()),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> SeqAccess<'de> for Access<'_, 'de> {
+    type Error = CborError;
+
+    fn next_element_seed<T>(&mut self, seed: T) -> Result<Option<T::Value>, CborError>
+    where
+        T: DeserializeSeed<'de>,
+    {
+        if !self.has_next()? {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.remaining.and_then(|n| usize::try_from(n).ok())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> MapAccess<'de> for Access<'_, 'de> {
+    type Error = CborError;
+
+    fn next_key_seed<K>(&mut self, seed: K) -> Result<Option<K::Value>, CborError>
+    where
+        K: DeserializeSeed<'de>,
+    {
+        if !self.has_next()? {
+            return Ok(None);
+        }
+        seed.deserialize(&mut *self.de).map(Some)
+    }
+
+    fn next_value_seed<V>(&mut self, seed: V) -> Result<V::Value, CborError>
+    where
+        V: DeserializeSeed<'de>,
+    {
+        seed.deserialize(&mut *self.de)
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        self.remaining.and_then(|n| usize::try_from(n).ok())
+    }
+}
+
+// Returns the number of bytes of the head starting with `initial`.
+pub(crate) fn head_len(initial: u8) -> usize {
+    match initial & 0x1f {
+        24 => 2,
+        25 => 3,
+        26 => 5,
+        27 => 9,
+        _ => 1,
+    }
+}
+
+// Returns the value of an integer with the given head.
+pub(crate) fn int(major: u8, argument: Argument) -> Result<i64, Error> {
+    let Argument::Value(value) = argument else {
+        return Err(Error::InvalidKey);
+    };
+    let value = i64::try_from(value).map_err(|_| Error::InvalidKey)?;
+    match major {
+        0 => Ok(value),
+        // -1 - value
+        1 => Ok(!value),
+        _ => Err(Error::InvalidKey),
+    }
+}
+
+// Checks the length of a byte or text string against the limit from the options.
+pub(crate) fn check_string_len(max: Option<usize>, len: u64) -> Result<(), Error> {
+    match max {
+        Some(max) if len > max as u64 => Err(Error::StringTooLong { max, actual: len }),
+        _ => Ok(()),
+    }
+}
+
+// Checks a tag on a data item at the given depth.  The key itself is at depth 0, the keys and
+// values of its fields at depth 1.
+pub(crate) fn check_tag(tags: TagPolicy, depth: usize, tag: u64) -> Result<(), Error> {
+    let is_accepted = match tags {
+        TagPolicy::Reject => false,
+        TagPolicy::Accept => true,
+        TagPolicy::Only(expected) => depth > 0 || tag == expected,
+    };
+    if is_accepted {
+        Ok(())
+    } else {
+        Err(Error::UnexpectedTag(tag))
+    }
+}
+
+// Converts a half-precision float, see RFC 8949, appendix D.
+#[cfg(feature = "serde")]
+fn f16_to_f64(half: u16) -> f64 {
+    let exponent = i32::from((half >> 10) & 0x1f);
+    let mantissa = f64::from(half & 0x3ff);
+    let value = match exponent {
+        0 => mantissa * pow2(-24),
+        31 if mantissa == 0.0 => f64::INFINITY,
+        31 => f64::NAN,
+        _ => (mantissa + 1024.0) * pow2(exponent.saturating_sub(25)),
+    };
+    if half & 0x8000 == 0 {
+        value
+    } else {
+        -value
+    }
+}
+
+// 2^exponent for exponents in the normal range of f64
+#[cfg(feature = "serde")]
+fn pow2(exponent: i32) -> f64 {
+    f64::from_bits((1023_i32.saturating_add(exponent) as u64) << 52)
+}
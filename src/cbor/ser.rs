@@ -0,0 +1,293 @@
+//! A minimal CBOR encoder
+//!
+//! The encoder implements `serde::Serializer` for the types used by COSE_Keys: integers, byte and
+//! text strings, booleans, null, and arrays and maps with a known length.  All items are encoded
+//! in the shortest form, so keys are serialized in the canonical CTAP2 form.
+
+use heapless::Vec;
+use serde::ser::{self, Impossible, Serialize};
+
+use super::CborError;
+use crate::{Bytes, Error};
+
+pub(crate) fn to_bytes<T: Serialize + ?Sized, const N: usize>(
+    value: &T,
+) -> Result<Bytes<N>, Error> {
+    let mut output = Vec::new();
+    append_to(value, &mut output)?;
+    Ok(output.into())
+}
+
+// Appends the encoding to `output`, leaving it unchanged if the encoding does not fit.
+pub(crate) fn append_to<T: Serialize + ?Sized, const N: usize>(
+    value: &T,
+    output: &mut Vec<u8, N>,
+) -> Result<usize, Error> {
+    let len = output.len();
+    let mut serializer = Serializer { output };
+    match value.serialize(&mut serializer) {
+        Ok(()) => Ok(serializer.output.len().saturating_sub(len)),
+        Err(err) => {
+            serializer.output.truncate(len);
+            Err(err.0)
+        }
+    }
+}
+
+struct Serializer<'a, const N: usize> {
+    output: &'a mut Vec<u8, N>,
+}
+
+impl<const N: usize> Serializer<'_, N> {
+    fn write(&mut self, data: &[u8]) -> Result<(), CborError> {
+        self.output
+            .extend_from_slice(data)
+            .map_err(|()| Error::BufferTooSmall.into())
+    }
+
+    fn head(&mut self, major: u8, value: u64) -> Result<(), CborError> {
+        let major = major << 5;
+        let bytes = value.to_be_bytes();
+        match value {
+            0..=23 => self.write(&[major | bytes[7]]),
+            24..=0xff => self.write(&[major | 24, bytes[7]]),
+            0x100..=0xffff => {
+                self.write(&[major | 25])?;
+                self.write(&bytes[6..])
+            }
+            0x1_0000..=0xffff_ffff => {
+                self.write(&[major | 26])?;
+                self.write(&bytes[4..])
+            }
+            _ => {
+                self.write(&[major | 27])?;
+                self.write(&bytes)
+            }
+        }
+    }
+
+    fn len(&mut self, major: u8, len: Option<usize>) -> Result<(), CborError> {
+        // indefinite lengths are not canonical
+        let len = len.ok_or(Error::InvalidKey)?;
+        self.head(major, len as u64)
+    }
+}
+
+impl<const N: usize> ser::Serializer for &mut Serializer<'_, N> {
+    type Ok = ();
+    type Error = CborError;
+
+    type SerializeSeq = Self;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Impossible<(), CborError>;
+    type SerializeTupleVariant = Impossible<(), CborError>;
+    type SerializeMap = Self;
+    type SerializeStruct = Impossible<(), CborError>;
+    type SerializeStructVariant = Impossible<(), CborError>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), CborError> {
+        self.write(&[if v { 0xf5 } else { 0xf4 }])
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), CborError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), CborError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), CborError> {
+        self.serialize_i64(v.into())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), CborError> {
+        match u64::try_from(v) {
+            Ok(v) => self.head(0, v),
+            // -1 - v without overflow
+            Err(_) => self.head(1, !v as u64),
+        }
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), CborError> {
+        self.head(0, v.into())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), CborError> {
+        self.head(0, v.into())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), CborError> {
+        self.head(0, v.into())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), CborError> {
+        self.head(0, v)
+    }
+
+    fn serialize_f32(self, _v: f32) -> Result<(), CborError> {
+        Err(Error::InvalidKey.into())
+    }
+
+    fn serialize_f64(self, _v: f64) -> Result<(), CborError> {
+        Err(Error::InvalidKey.into())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), CborError> {
+        self.serialize_str(v.encode_utf8(&mut [0; 4]))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), CborError> {
+        self.head(3, v.len() as u64)?;
+        self.write(v.as_bytes())
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), CborError> {
+        self.head(2, v.len() as u64)?;
+        self.write(v)
+    }
+
+    fn serialize_none(self) -> Result<(), CborError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), CborError> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), CborError> {
+        self.write(&[0xf6])
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), CborError> {
+        self.serialize_unit()
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), CborError> {
+        Err(Error::InvalidKey.into())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), CborError> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), CborError> {
+        Err(Error::InvalidKey.into())
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Self, CborError> {
+        self.len(4, len)?;
+        Ok(self)
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Self, CborError> {
+        self.serialize_seq(Some(len))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleStruct, CborError> {
+        Err(Error::InvalidKey.into())
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeTupleVariant, CborError> {
+        Err(Error::InvalidKey.into())
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Self, CborError> {
+        self.len(5, len)?;
+        Ok(self)
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStruct, CborError> {
+        Err(Error::InvalidKey.into())
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self::SerializeStructVariant, CborError> {
+        Err(Error::InvalidKey.into())
+    }
+
+    fn collect_str<T: core::fmt::Display + ?Sized>(self, _value: &T) -> Result<(), CborError> {
+        Err(Error::InvalidKey.into())
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<const N: usize> ser::SerializeSeq for &mut Serializer<'_, N> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CborError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CborError> {
+        Ok(())
+    }
+}
+
+impl<const N: usize> ser::SerializeTuple for &mut Serializer<'_, N> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CborError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CborError> {
+        Ok(())
+    }
+}
+
+impl<const N: usize> ser::SerializeMap for &mut Serializer<'_, N> {
+    type Ok = ();
+    type Error = CborError;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), CborError> {
+        key.serialize(&mut **self)
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), CborError> {
+        value.serialize(&mut **self)
+    }
+
+    fn end(self) -> Result<(), CborError> {
+        Ok(())
+    }
+}
@@ -0,0 +1,131 @@
+//! Encoding and decoding without serde
+//!
+//! The COSE_Key map only contains a few integers and byte strings, so it can be encoded directly
+//! from its data items and decoded with the [`StreamDecoder`].  This avoids instantiating the
+//! serde implementations, which matters for builds where code size is tight, and is all that is
+//! available without the `serde` feature.  The results are the same as for `to_bytes` and
+//! `take_from_slice`, except that byte strings that are too long for a coordinate are rejected
+//! with [`Error::InvalidLength`] even if the input is truncated.
+
+use core::mem::MaybeUninit;
+
+use crate::{Error, KeyFields, ParseOptions, PublicKey, RawCoseKey, StreamDecoder};
+
+impl RawCoseKey {
+    /// Writes the key in the canonical CTAP2 form to the start of `output` without using serde
+    /// and returns the length of the encoding.
+    ///
+    /// Returns [`Error::BufferTooSmall`] if the key does not fit into `output`.
+    pub fn encode(&self, output: &mut [u8]) -> Result<usize, Error> {
+        self.fields().encode(output)
+    }
+
+    /// Writes the key in the canonical CTAP2 form to the start of an uninitialized buffer and
+    /// returns the initialized prefix, see [`encode`][Self::encode].
+    ///
+    /// This avoids zeroing or copying into buffers that are provided by a transport, e.g. for DMA.
+    pub fn encode_uninit<'a>(
+        &self,
+        output: &'a mut [MaybeUninit<u8>],
+    ) -> Result<&'a mut [u8], Error> {
+        self.fields().encode_uninit(output)
+    }
+
+    /// Writes the key in the canonical CTAP2 form to `writer` without assembling the encoding in
+    /// memory first.
+    #[cfg(feature = "embedded-io")]
+    pub fn write_to<W: embedded_io::Write>(&self, writer: &mut W) -> Result<(), W::Error> {
+        self.fields().write_parts(|part| writer.write_all(part))
+    }
+
+    /// Decodes the COSE_Key at the start of `data` without using serde and returns it together
+    /// with the number of bytes it occupies.
+    pub fn decode(data: &[u8], options: ParseOptions) -> Result<(Self, usize), Error> {
+        StreamDecoder::new(options)
+            .feed_raw(data)?
+            .ok_or(Error::Truncated)
+    }
+}
+
+impl PublicKey {
+    /// Writes the key in the canonical CTAP2 form to the start of `output` without using serde
+    /// and returns the length of the encoding.
+    ///
+    /// Returns [`Error::BufferTooSmall`] if the key does not fit into `output`.
+    pub fn encode(&self, output: &mut [u8]) -> Result<usize, Error> {
+        self.fields().encode(output)
+    }
+
+    /// Writes the key in the canonical CTAP2 form to the start of an uninitialized buffer and
+    /// returns the initialized prefix, see [`encode`][Self::encode].
+    ///
+    /// This avoids zeroing or copying into buffers that are provided by a transport, e.g. for DMA.
+    pub fn encode_uninit<'a>(
+        &self,
+        output: &'a mut [MaybeUninit<u8>],
+    ) -> Result<&'a mut [u8], Error> {
+        self.fields().encode_uninit(output)
+    }
+
+    /// Writes the key in the canonical CTAP2 form to `writer` without assembling the encoding in
+    /// memory first.
+    #[cfg(feature = "embedded-io")]
+    pub fn write_to<W: embedded_io::Write>(&self, writer: &mut W) -> Result<(), W::Error> {
+        self.fields().write_parts(|part| writer.write_all(part))
+    }
+
+    /// Decodes the COSE_Key at the start of `data` without using serde and returns it together
+    /// with the number of bytes it occupies.
+    pub fn decode(data: &[u8], options: ParseOptions) -> Result<(Self, usize), Error> {
+        StreamDecoder::new(options)
+            .feed(data)?
+            .ok_or(Error::Truncated)
+    }
+}
+
+impl KeyFields<'_> {
+    fn encode(self, output: &mut [u8]) -> Result<usize, Error> {
+        self.copy_to(output, |byte, value| *byte = value)
+    }
+
+    fn encode_uninit(self, output: &mut [MaybeUninit<u8>]) -> Result<&mut [u8], Error> {
+        let len = self.copy_to(output, |byte, value| {
+            byte.write(value);
+        })?;
+        let init = output.get_mut(..len).ok_or(Error::BufferTooSmall)?;
+        // SAFETY: `copy_to` initialized the first `len` bytes, and `MaybeUninit<u8>` has the same
+        // layout as `u8`.
+        Ok(unsafe { &mut *(init as *mut [MaybeUninit<u8>] as *mut [u8]) })
+    }
+
+    // Copies the encoding to the start of `output` with `copy` and returns its length.
+    fn copy_to<T>(self, output: &mut [T], copy: impl Fn(&mut T, u8)) -> Result<usize, Error> {
+        let mut len = 0;
+        self.write_parts(|part| {
+            let bytes = output
+                .get_mut(len..len + part.len())
+                .ok_or(Error::BufferTooSmall)?;
+            for (byte, value) in bytes.iter_mut().zip(part) {
+                copy(byte, *value);
+            }
+            len += part.len();
+            Ok(())
+        })?;
+        Ok(len)
+    }
+
+    // Passes the encoding of the key to `write` in parts, i.e. the heads and payloads of the
+    // data items.
+    pub(crate) fn write_parts<E>(
+        self,
+        mut write: impl FnMut(&[u8]) -> Result<(), E>,
+    ) -> Result<(), E> {
+        for item in self.items() {
+            write(&item.head())?;
+            if !item.payload().is_empty() {
+                write(item.payload())?;
+            }
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,80 @@
+//! Helpers for COSE_Keys embedded in CTAP2/WebAuthn structures
+//!
+//! https://www.w3.org/TR/webauthn-3/#sctn-authenticator-data
+
+#[cfg(all(feature = "cbor", feature = "serde"))]
+use core::ops::Range;
+
+use crate::Alg;
+#[cfg(all(feature = "cbor", feature = "serde"))]
+use crate::{Error, ParseOptions, PublicKey};
+
+/// The algorithms supported for credential keys, in order of preference
+///
+/// Only the algorithms whose key type is enabled are included: ES256 with the `p256` feature and
+/// EdDSA with the `ed25519` feature.
+pub const CREDENTIAL_ALGS: &[Alg] = &[
+    #[cfg(feature = "p256")]
+    Alg::Es256,
+    #[cfg(feature = "ed25519")]
+    Alg::EdDsa,
+];
+
+/// Selects the algorithm for a new credential from the `alg` values of `pubKeyCredParams`.
+///
+/// The parameters are sorted by the preference of the relying party, so this returns the first
+/// algorithm that is contained in [`CREDENTIAL_ALGS`].  Unsupported values are skipped.
+pub fn select_alg<I: IntoIterator<Item = i32>>(algs: I) -> Option<Alg> {
+    algs.into_iter().find_map(|alg| {
+        CREDENTIAL_ALGS
+            .iter()
+            .copied()
+            .find(|supported| supported.to_i32() == alg)
+    })
+}
+
+#[cfg(all(feature = "cbor", feature = "serde"))]
+const FLAGS: usize = 32;
+#[cfg(all(feature = "cbor", feature = "serde"))]
+const FLAG_AT: u8 = 0x40;
+#[cfg(all(feature = "cbor", feature = "serde"))]
+const CREDENTIAL_ID_LENGTH: usize = 32 + 1 + 4 + 16;
+#[cfg(all(feature = "cbor", feature = "serde"))]
+const CREDENTIAL_ID: usize = CREDENTIAL_ID_LENGTH + 2;
+
+/// Parses the `credentialPublicKey` from the attested credential data in `auth_data`.
+///
+/// Returns the key together with the range of `auth_data` occupied by its encoding, so that
+/// callers can locate the extensions following it or forward the encoded key.
+#[cfg(all(feature = "cbor", feature = "serde"))]
+pub fn credential_public_key(auth_data: &[u8]) -> Result<(PublicKey, Range<usize>), Error> {
+    credential_public_key_with(auth_data, ParseOptions::default())
+}
+
+/// Parses the `credentialPublicKey` from the attested credential data in `auth_data` using the
+/// given options.
+///
+/// See [`credential_public_key`].
+#[cfg(all(feature = "cbor", feature = "serde"))]
+pub fn credential_public_key_with(
+    auth_data: &[u8],
+    options: ParseOptions,
+) -> Result<(PublicKey, Range<usize>), Error> {
+    let flags = auth_data.get(FLAGS).ok_or(Error::Truncated)?;
+    if flags & FLAG_AT == 0 {
+        return Err(Error::MissingAttestedCredentialData);
+    }
+    let length = auth_data
+        .get(CREDENTIAL_ID_LENGTH..CREDENTIAL_ID)
+        .and_then(|length| length.try_into().ok())
+        .ok_or(Error::Truncated)?;
+    // the offsets can only overflow if `auth_data` is shorter than they are
+    let start = CREDENTIAL_ID
+        .checked_add(u16::from_be_bytes(length).into())
+        .ok_or(Error::Truncated)?;
+    let data = auth_data.get(start..).ok_or(Error::Truncated)?;
+
+    let (key, len) = PublicKey::take_from_slice(data, options)?;
+    let end = start.checked_add(len).ok_or(Error::Truncated)?;
+    Ok((key, start..end))
+}
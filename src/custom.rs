@@ -0,0 +1,659 @@
+//! Support for key types that are defined outside of this crate with [`define_cose_key`]
+//!
+//! The items of this module are used by the code generated by the macro and are not part of the
+//! public API.
+
+use core::cmp::Ordering;
+use core::fmt::{self, Formatter};
+
+use serde::de::{MapAccess, Unexpected, Visitor};
+use serde::ser::SerializeMap as _;
+pub use serde::{de::DeserializeSeed, Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::base64url::{self, Base64Url};
+use crate::display;
+use crate::error::{write_parts, Part};
+use crate::map::{self, MapFields, SeenFields};
+use crate::order::{self, Item};
+use crate::{bytes_len, int_len, label, Alg, Crv, Error, Key, KeySeed, Kty, ParseOptions};
+
+// The fields are tracked in a bit set, see `KeySpec::index`.
+pub const MAX_PARAMS: usize = 61;
+
+/// Defines a COSE_Key type with fixed `kty`, `alg` and `crv` and byte string parameters
+///
+/// The generated struct has a `[u8; N]` field for each parameter and implements `Serialize`
+/// and `Deserialize` for the canonical CTAP2 form.  Deserialization checks `kty`, `alg` and `crv`
+/// and requires every parameter to have exactly the given length.  As for the key types of this
+/// crate, `alg` is optional unless [`ParseOptions::require_alg`] is set, and `deserialize_with`
+/// accepts [`ParseOptions`].  `deserialize_in_place_with` and the `DeserializeSeed`
+/// implementation for `&mut` references write the parameters directly into an existing value, so
+/// that large keys are not copied on the stack.  The struct also provides the constants `KTY`, `ALG`, `CRV` and
+/// `MAX_ENCODED_LEN` and the method `encoded_len`, and it implements
+/// [`CoseKeyType`](crate::CoseKeyType).  Its `Debug` output only shows the length and the first
+/// bytes of parameters that are longer than 64 bytes, e.g. post-quantum public keys.
+///
+/// Parameters can also be declared as `&'a [u8; N]` to borrow them from the input instead of
+/// copying them, e.g. for large keys that are only forwarded.  The struct then takes the lifetime
+/// parameter `'a`, and deserialization fails with [`Error::NotBorrowed`](crate::Error::NotBorrowed)
+/// if the deserializer cannot borrow from its input.
+///
+/// The parameters must be listed in canonical order, i.e. with decreasing labels, as they are
+/// serialized in this order.  If `crv` is `Some`, it uses the label -1.
+///
+/// ```
+/// use cosey::{define_cose_key, Alg, Kty};
+///
+/// define_cose_key! {
+///     /// A key for a private-use algorithm
+///     pub struct VendorPublicKey {
+///         kty: Kty::Okp,
+///         alg: Alg::Other(-65537),
+///         crv: None,
+///         /// The public key
+///         pub pk: [u8; 48] = -2,
+///     }
+/// }
+///
+/// let key = VendorPublicKey { pk: [0xff; 48] };
+/// assert_eq!(key.encoded_len(), VendorPublicKey::MAX_ENCODED_LEN);
+/// ```
+#[macro_export]
+macro_rules! define_cose_key {
+    (
+        $(#[$attr:meta])*
+        $vis:vis struct $name:ident $(<$lt:lifetime>)? {
+            kty: $kty:expr,
+            alg: $alg:expr,
+            crv: $crv:expr,
+            $(
+                $(#[$param_attr:meta])*
+                $param_vis:vis $param:ident: $(&$param_lt:lifetime)? [u8; $len:literal] = $label:literal,
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Clone, Eq, Hash, PartialEq)]
+        $vis struct $name $(<$lt>)? {
+            $(
+                $(#[$param_attr])*
+                $param_vis $param: $(&$param_lt)? [u8; $len],
+            )*
+        }
+
+        impl $(<$lt>)? $name $(<$lt>)? {
+            /// The key type (`kty`)
+            pub const KTY: $crate::Kty = $kty;
+            /// The algorithm (`alg`)
+            pub const ALG: $crate::Alg = $alg;
+            /// The curve (`crv`), if any
+            pub const CRV: ::core::option::Option<$crate::Crv> = $crv;
+            /// The maximum length of the canonical CBOR encoding of the key
+            pub const MAX_ENCODED_LEN: usize =
+                $crate::__private::encoded_len(&Self::SPEC, &[$($len),*]);
+
+            const SPEC: $crate::__private::KeySpec = $crate::__private::KeySpec {
+                kty: Self::KTY,
+                alg: Self::ALG,
+                crv: Self::CRV,
+                params: &[$(($label, ::core::stringify!($param))),*],
+            };
+
+            /// Returns the length of the canonical CBOR encoding of the key.
+            pub fn encoded_len(&self) -> usize {
+                $crate::__private::encoded_len(&Self::SPEC, &[$(self.$param.len()),*])
+            }
+
+            /// Deserializes a key using the given options.
+            pub fn deserialize_with<'de, D>(
+                deserializer: D,
+                options: $crate::ParseOptions,
+            ) -> ::core::result::Result<Self, D::Error>
+            where
+                D: $crate::__private::Deserializer<'de>,
+                $('de: $lt,)?
+            {
+                $(let mut $param: ::core::option::Option<$(&$param_lt)? [u8; $len]> = None;)*
+                let params: &mut [&mut dyn $crate::__private::Param<'de>] =
+                    &mut [$(&mut $param),*];
+                $crate::__private::deserialize(deserializer, options, &Self::SPEC, params)?;
+                ::core::result::Result::Ok(Self {
+                    $($param: $crate::__private::required($param)?,)*
+                })
+            }
+
+            /// Deserializes a key into `place` using the given options.
+            ///
+            /// The parameters are written directly into `place` without a temporary copy on the
+            /// stack.  If an error is returned, `place` may have been partially overwritten.
+            pub fn deserialize_in_place_with<'de, D>(
+                deserializer: D,
+                place: &mut Self,
+                options: $crate::ParseOptions,
+            ) -> ::core::result::Result<(), D::Error>
+            where
+                D: $crate::__private::Deserializer<'de>,
+                $('de: $lt,)?
+            {
+                let params: &mut [&mut dyn $crate::__private::Param<'de>] =
+                    &mut [$(&mut place.$param),*];
+                $crate::__private::deserialize(deserializer, options, &Self::SPEC, params)
+            }
+        }
+
+        const _: () = ::core::assert!(
+            $name::SPEC.params.len() <= $crate::__private::MAX_PARAMS,
+            "too many parameters",
+        );
+
+        impl $(<$lt>)? $crate::__private::Serialize for $name $(<$lt>)? {
+            fn serialize<S>(&self, serializer: S) -> ::core::result::Result<S::Ok, S::Error>
+            where
+                S: $crate::__private::Serializer,
+            {
+                $crate::__private::serialize(serializer, &Self::SPEC, &[$(&self.$param[..]),*])
+            }
+        }
+
+        impl<'de $(, $lt)?> $crate::__private::Deserialize<'de> for $name $(<$lt>)?
+        where
+            $('de: $lt,)?
+        {
+            fn deserialize<D>(deserializer: D) -> ::core::result::Result<Self, D::Error>
+            where
+                D: $crate::__private::Deserializer<'de>,
+            {
+                Self::deserialize_with(deserializer, ::core::default::Default::default())
+            }
+
+            fn deserialize_in_place<D>(
+                deserializer: D,
+                place: &mut Self,
+            ) -> ::core::result::Result<(), D::Error>
+            where
+                D: $crate::__private::Deserializer<'de>,
+            {
+                Self::deserialize_in_place_with(
+                    deserializer,
+                    place,
+                    ::core::default::Default::default(),
+                )
+            }
+        }
+
+        /// Deserializes a key into the referenced value, see `deserialize_in_place_with`.
+        impl<'de $(, $lt)?> $crate::__private::DeserializeSeed<'de> for &mut $name $(<$lt>)?
+        where
+            $('de: $lt,)?
+        {
+            type Value = ();
+
+            fn deserialize<D>(self, deserializer: D) -> ::core::result::Result<(), D::Error>
+            where
+                D: $crate::__private::Deserializer<'de>,
+            {
+                $name::deserialize_in_place_with(
+                    deserializer,
+                    self,
+                    ::core::default::Default::default(),
+                )
+            }
+        }
+
+        impl $(<$lt>)? ::core::fmt::Debug for $name $(<$lt>)? {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                f.debug_struct(::core::stringify!($name))
+                    $(.field(::core::stringify!($param), &$crate::__private::DebugBytes(&self.$param[..])))*
+                    .finish()
+            }
+        }
+
+        impl $(<$lt>)? ::core::fmt::Display for $name $(<$lt>)? {
+            fn fmt(&self, f: &mut ::core::fmt::Formatter<'_>) -> ::core::fmt::Result {
+                $crate::__private::display(f, &Self::SPEC, &[$(&self.$param[..]),*])
+            }
+        }
+
+        /// Keys are ordered by their canonical CBOR encoding.
+        impl $(<$lt>)? ::core::cmp::Ord for $name $(<$lt>)? {
+            fn cmp(&self, other: &Self) -> ::core::cmp::Ordering {
+                $crate::__private::cmp(&[$(&self.$param[..]),*], &[$(&other.$param[..]),*])
+            }
+        }
+
+        impl $(<$lt>)? ::core::cmp::PartialOrd for $name $(<$lt>)? {
+            fn partial_cmp(&self, other: &Self) -> ::core::option::Option<::core::cmp::Ordering> {
+                ::core::option::Option::Some(::core::cmp::Ord::cmp(self, other))
+            }
+        }
+
+        impl $(<$lt>)? $crate::CoseKeyType for $name $(<$lt>)? {
+            const KTY: $crate::Kty = $kty;
+            const ALG: $crate::Alg = $alg;
+            const CRV: ::core::option::Option<$crate::Crv> = $crv;
+            const PARAMS: &'static [(i8, usize)] = &[$(($label, $len)),*];
+            const MAX_ENCODED_LEN: usize = $name::MAX_ENCODED_LEN;
+        }
+    };
+}
+
+/// The constants and parameters of a key type
+pub struct KeySpec {
+    pub kty: Kty,
+    pub alg: Alg,
+    pub crv: Option<Crv>,
+    /// The labels and names of the byte string parameters
+    pub params: &'static [(i8, &'static str)],
+}
+
+impl KeySpec {
+    // The index of the field with the given label in canonical order: kty, alg, crv (if any) and
+    // the parameters.
+    fn index(&self, label: i64) -> Option<usize> {
+        let offset = self.offset();
+        match label {
+            1 => Some(0),
+            3 => Some(1),
+            -1 if self.crv.is_some() => Some(2),
+            _ => self
+                .params
+                .iter()
+                .position(|(param, _)| i64::from(*param) == label)
+                .map(|i| offset + i),
+        }
+    }
+
+    fn name(&self, index: usize) -> &'static str {
+        match (index, self.crv) {
+            (0, _) => "kty",
+            (1, _) => "alg",
+            (2, Some(_)) => "crv",
+            _ => self.param(index).map_or("", |(_, name)| name),
+        }
+    }
+
+    fn label(&self, index: usize) -> i8 {
+        match (index, self.crv) {
+            (0, _) => label::KTY,
+            (1, _) => label::ALG,
+            (2, Some(_)) => label::ec2::CRV,
+            _ => self.param(index).map_or(0, |(label, _)| *label),
+        }
+    }
+
+    // The parameter with the given index, see `index`.
+    fn param(&self, index: usize) -> Option<&(i8, &'static str)> {
+        self.params.get(index.checked_sub(self.offset())?)
+    }
+
+    // The index of the first parameter
+    fn offset(&self) -> usize {
+        if self.crv.is_some() {
+            3
+        } else {
+            2
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.params.len() + self.offset()
+    }
+}
+
+/// A byte string parameter
+pub trait Param<'de> {
+    fn set(&mut self, label: i8, value: &[u8]) -> Result<(), Error>;
+
+    fn set_borrowed(&mut self, label: i8, value: &'de [u8]) -> Result<(), Error> {
+        self.set(label, value)
+    }
+
+    /// Sets the parameter from an unpadded base64url string, see
+    /// [`ParseOptions::human_readable`].
+    fn set_base64url(&mut self, label: i8, value: &str) -> Result<(), Error>;
+}
+
+fn check_length<const N: usize>(label: i8, value: &[u8]) -> Result<&[u8; N], Error> {
+    value.try_into().map_err(|_| Error::InvalidLength {
+        label,
+        expected: N,
+        actual: value.len(),
+    })
+}
+
+impl<const N: usize> Param<'_> for Option<[u8; N]> {
+    fn set(&mut self, label: i8, value: &[u8]) -> Result<(), Error> {
+        *self = Some(*check_length(label, value)?);
+        Ok(())
+    }
+
+    fn set_base64url(&mut self, label: i8, value: &str) -> Result<(), Error> {
+        let mut param = [0; N];
+        base64url::decode(label, value, &mut param)?;
+        *self = Some(param);
+        Ok(())
+    }
+}
+
+impl<const N: usize> Param<'_> for [u8; N] {
+    fn set(&mut self, label: i8, value: &[u8]) -> Result<(), Error> {
+        *self = *check_length(label, value)?;
+        Ok(())
+    }
+
+    // decodes in place, as `base64url::decode` does not write anything on error
+    fn set_base64url(&mut self, label: i8, value: &str) -> Result<(), Error> {
+        base64url::decode(label, value, self)
+    }
+}
+
+impl<'de: 'a, 'a, const N: usize> Param<'de> for &'a [u8; N] {
+    fn set(&mut self, label: i8, _value: &[u8]) -> Result<(), Error> {
+        Err(Error::NotBorrowed { label })
+    }
+
+    fn set_borrowed(&mut self, label: i8, value: &'de [u8]) -> Result<(), Error> {
+        *self = check_length(label, value)?;
+        Ok(())
+    }
+
+    fn set_base64url(&mut self, label: i8, _value: &str) -> Result<(), Error> {
+        Err(Error::NotBorrowed { label })
+    }
+}
+
+impl<'de: 'a, 'a, const N: usize> Param<'de> for Option<&'a [u8; N]> {
+    fn set(&mut self, label: i8, _value: &[u8]) -> Result<(), Error> {
+        Err(Error::NotBorrowed { label })
+    }
+
+    fn set_borrowed(&mut self, label: i8, value: &'de [u8]) -> Result<(), Error> {
+        *self = Some(check_length(label, value)?);
+        Ok(())
+    }
+
+    fn set_base64url(&mut self, label: i8, _value: &str) -> Result<(), Error> {
+        Err(Error::NotBorrowed { label })
+    }
+}
+
+struct ParamSeed<'a, 'de> {
+    param: &'a mut dyn Param<'de>,
+    label: i8,
+    human_readable: bool,
+}
+
+impl<'de> DeserializeSeed<'de> for ParamSeed<'_, 'de> {
+    type Value = Result<(), Error>;
+
+    fn deserialize<D>(mut self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        if self.human_readable && deserializer.is_human_readable() {
+            deserializer.deserialize_str(self)
+        } else {
+            self.human_readable = false;
+            deserializer.deserialize_bytes(self)
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for ParamSeed<'_, 'de> {
+    type Value = Result<(), Error>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        if self.human_readable {
+            formatter.write_str("a base64url string")
+        } else {
+            formatter.write_str("a byte string")
+        }
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        if !self.human_readable {
+            return Err(E::invalid_type(Unexpected::Str(v), &self));
+        }
+        Ok(self.param.set_base64url(self.label, v))
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(self.param.set(self.label, v))
+    }
+
+    fn visit_borrowed_bytes<E: serde::de::Error>(self, v: &'de [u8]) -> Result<Self::Value, E> {
+        Ok(self.param.set_borrowed(self.label, v))
+    }
+}
+
+struct KeyVisitor<'a, 'b, 'de> {
+    options: ParseOptions,
+    spec: &'a KeySpec,
+    params: &'a mut [&'b mut dyn Param<'de>],
+    fields: SeenFields,
+}
+
+impl<'de> MapFields<'de> for KeyVisitor<'_, '_, 'de> {
+    type Field = usize;
+
+    fn key(&mut self, key: Key) -> Result<Option<usize>, Error> {
+        let field = match key {
+            Key::Label(label) => self.spec.index(label).ok_or(Error::UnknownField(label)),
+            Key::UnknownText => Err(Error::UnknownTextField),
+        };
+        self.fields
+            .check(field.map(|index| (index, self.spec.name(index))))
+    }
+
+    fn read_value<V>(&mut self, index: usize, map: &mut V) -> Result<Result<(), Error>, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        let label = self.spec.label(index);
+        if let Some(param) = index
+            .checked_sub(self.spec.offset())
+            .and_then(|index| self.params.get_mut(index))
+        {
+            return map.next_value_seed(ParamSeed {
+                param: &mut **param,
+                label,
+                human_readable: self.options.human_readable,
+            });
+        }
+
+        let value: i64 = map.next_value()?;
+        let unsupported = Error::UnsupportedValue { label, value };
+        let result = match label {
+            label::KTY => match Kty::try_from(value) {
+                Ok(kty) if kty == self.spec.kty => Ok(()),
+                Ok(kty) => Err(Error::WrongKty {
+                    expected: self.spec.kty,
+                    actual: kty,
+                }),
+                Err(_) => Err(unsupported),
+            },
+            label::ALG => match Alg::try_from(value) {
+                Ok(alg) if alg == self.spec.alg => self.options.check_alg(alg),
+                Ok(alg) => Err(Error::WrongAlg {
+                    expected: self.spec.alg,
+                    actual: alg,
+                }),
+                Err(_) => Err(unsupported),
+            },
+            _ => match (Crv::try_from(value), self.spec.crv) {
+                (Ok(crv), Some(expected)) if crv == expected => Ok(()),
+                (Ok(crv), Some(expected)) => Err(Error::WrongCrv {
+                    expected,
+                    actual: crv,
+                }),
+                _ => Err(unsupported),
+            },
+        };
+        Ok(result)
+    }
+}
+
+impl KeyVisitor<'_, '_, '_> {
+    fn finish(&self) -> Result<(), Error> {
+        let optional = |index| index == 1 && !self.options.require_alg;
+        match (0..self.spec.len()).find(|&index| !self.fields.contains(index) && !optional(index)) {
+            Some(index) => Err(Error::MissingField(self.spec.name(index))),
+            None => Ok(()),
+        }
+    }
+}
+
+impl<'de> Visitor<'de> for KeyVisitor<'_, '_, 'de> {
+    type Value = Result<(), Error>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("a COSE_Key")
+    }
+
+    fn visit_map<V>(mut self, mut map: V) -> Result<Self::Value, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        let seed = KeySeed::new(self.options);
+        if let Err(err) = map::visit_entries(&mut self, &mut map, seed)? {
+            return Ok(Err(err));
+        }
+        Ok(self.finish())
+    }
+}
+
+pub fn deserialize<'de, D>(
+    deserializer: D,
+    options: ParseOptions,
+    spec: &KeySpec,
+    params: &mut [&mut dyn Param<'de>],
+) -> Result<(), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let visitor = KeyVisitor {
+        options,
+        spec,
+        params,
+        fields: SeenFields::new(options),
+    };
+    deserializer
+        .deserialize_map(visitor)?
+        .map_err(Error::into_de_error)
+}
+
+pub fn required<T, E: serde::de::Error>(value: Option<T>) -> Result<T, E> {
+    value.ok_or_else(|| Error::InvalidKey.into_de_error())
+}
+
+// Parameters longer than this are abbreviated in the `Debug` output.
+const MAX_DEBUG_LEN: usize = 64;
+// The number of bytes that are shown for abbreviated parameters
+const DEBUG_PREFIX_LEN: usize = 8;
+
+/// Formats a parameter for `Debug`, showing only the length and a prefix of long parameters
+pub struct DebugBytes<'a>(pub &'a [u8]);
+
+impl fmt::Debug for DebugBytes<'_> {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        if self.0.len() <= MAX_DEBUG_LEN {
+            return self.0.fmt(f);
+        }
+        let prefix = self.0.get(..DEBUG_PREFIX_LEN).unwrap_or(self.0);
+        write_parts(
+            f,
+            &[
+                Part::Str("<"),
+                Part::Uint(self.0.len() as u64),
+                Part::Str(" bytes: "),
+                Part::Hex(prefix),
+                Part::Str("...>"),
+            ],
+        )
+    }
+}
+
+/// Formats the one-line summary of a key for `Display`
+pub fn display(f: &mut Formatter<'_>, spec: &KeySpec, params: &[&[u8]]) -> fmt::Result {
+    let mut named = [("", &[][..]); MAX_PARAMS];
+    for (named, ((_, name), value)) in named.iter_mut().zip(spec.params.iter().zip(params)) {
+        *named = (*name, *value);
+    }
+    let named = named.get(..params.len()).ok_or(fmt::Error)?;
+    display::summary(f, spec.kty, spec.alg, spec.crv, named)
+}
+
+// A byte string, which is written as unpadded base64url for human-readable serializers
+pub(crate) struct ByteStr<'a>(pub(crate) &'a [u8]);
+
+impl Serialize for ByteStr<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(&Base64Url(self.0))
+        } else {
+            serializer.serialize_bytes(self.0)
+        }
+    }
+}
+
+// A map label, which is written as a decimal string for human-readable serializers because JSON
+// only allows text keys
+pub(crate) struct LabelKey(pub(crate) i8);
+
+impl Serialize for LabelKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        if serializer.is_human_readable() {
+            serializer.collect_str(self)
+        } else {
+            serializer.serialize_i8(self.0)
+        }
+    }
+}
+
+impl fmt::Display for LabelKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write_parts(f, &[Part::Int(self.0.into())])
+    }
+}
+
+pub fn serialize<S: Serializer>(
+    serializer: S,
+    spec: &KeySpec,
+    params: &[&[u8]],
+) -> Result<S::Ok, S::Error> {
+    let mut map = serializer.serialize_map(Some(spec.len()))?;
+    map.serialize_entry(&LabelKey(label::KTY), &spec.kty)?;
+    map.serialize_entry(&LabelKey(label::ALG), &spec.alg)?;
+    if let Some(crv) = &spec.crv {
+        map.serialize_entry(&LabelKey(label::ec2::CRV), crv)?;
+    }
+    for ((label, _), value) in spec.params.iter().zip(params) {
+        map.serialize_entry(&LabelKey(*label), &ByteStr(value))?;
+    }
+    map.end()
+}
+
+// All other fields of keys of the same type are equal.
+pub fn cmp(params: &[&[u8]], other: &[&[u8]]) -> Ordering {
+    order::cmp(
+        params.iter().map(|param| Item::Bytes(param)),
+        other.iter().map(|param| Item::Bytes(param)),
+    )
+}
+
+pub const fn encoded_len(spec: &KeySpec, lens: &[usize]) -> usize {
+    // the number of fields is limited by MAX_PARAMS, so the map head has one or two bytes
+    let fields = spec.params.len() + if spec.crv.is_some() { 3 } else { 2 };
+    let mut len = if fields < 24 { 1 } else { 2 };
+    len += 1 + int_len(spec.kty.to_i32() as i64);
+    len += 1 + int_len(spec.alg.to_i32() as i64);
+    if let Some(crv) = spec.crv {
+        len += 1 + int_len(crv.to_i32() as i64);
+    }
+    let (mut params, mut lens) = (spec.params, lens);
+    while let ([(label, _), rest @ ..], [param_len, lens_rest @ ..]) = (params, lens) {
+        len += int_len(*label as i64) + bytes_len(*param_len);
+        (params, lens) = (rest, lens_rest);
+    }
+    len
+}
@@ -0,0 +1,133 @@
+//! One-line summaries of keys for logs and command line tools
+//!
+//! Keys are formatted as the key type, curve and algorithm followed by the parameters, for
+//! example `EC2/P-256/ES256 x=ab12… y=cd34…`.  Only the first bytes of each parameter are shown
+//! unless the alternate flag (`{:#}`) is used.
+
+use core::fmt::{self, Formatter};
+
+#[cfg(feature = "ecdh")]
+use crate::EcdhEsHkdf256PublicKey;
+#[cfg(feature = "ed25519")]
+use crate::Ed25519PublicKey;
+#[cfg(feature = "p256")]
+use crate::P256PublicKey;
+#[cfg(feature = "totp")]
+use crate::TotpPublicKey;
+#[cfg(feature = "x25519")]
+use crate::X25519PublicKey;
+#[cfg(any(
+    feature = "ecdh",
+    feature = "ed25519",
+    feature = "p256",
+    feature = "x25519",
+    feature = "serde"
+))]
+use crate::{Alg, Crv, Kty};
+use crate::{CoseKeyType, PublicKey};
+
+// The number of bytes that are shown for each parameter without the alternate flag
+#[cfg(any(
+    feature = "ecdh",
+    feature = "ed25519",
+    feature = "p256",
+    feature = "x25519",
+    feature = "serde"
+))]
+const PREFIX_LEN: usize = 2;
+
+#[cfg(any(
+    feature = "ecdh",
+    feature = "ed25519",
+    feature = "p256",
+    feature = "x25519",
+    feature = "serde"
+))]
+pub(crate) fn summary(
+    f: &mut Formatter<'_>,
+    kty: Kty,
+    alg: Alg,
+    crv: Option<Crv>,
+    params: &[(&str, &[u8])],
+) -> fmt::Result {
+    write!(f, "{kty}/")?;
+    if let Some(crv) = crv {
+        write!(f, "{crv}/")?;
+    }
+    write!(f, "{alg}")?;
+    for (name, value) in params {
+        write!(f, " {name}=")?;
+        let abbreviated = !f.alternate() && value.len() > PREFIX_LEN;
+        let shown = if abbreviated { PREFIX_LEN } else { value.len() };
+        for byte in value.iter().take(shown) {
+            write!(f, "{byte:02x}")?;
+        }
+        if abbreviated {
+            f.write_str("…")?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(any(
+    feature = "ecdh",
+    feature = "ed25519",
+    feature = "p256",
+    feature = "x25519"
+))]
+fn key_summary<K: CoseKeyType>(f: &mut Formatter<'_>, params: &[(&str, &[u8])]) -> fmt::Result {
+    summary(f, K::KTY, K::ALG, K::CRV, params)
+}
+
+#[cfg(feature = "p256")]
+impl fmt::Display for P256PublicKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        key_summary::<Self>(f, &[("x", &self.x), ("y", &self.y)])
+    }
+}
+
+#[cfg(feature = "ecdh")]
+impl fmt::Display for EcdhEsHkdf256PublicKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        key_summary::<Self>(f, &[("x", &self.x), ("y", &self.y)])
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl fmt::Display for Ed25519PublicKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        key_summary::<Self>(f, &[("x", &self.x)])
+    }
+}
+
+#[cfg(feature = "totp")]
+impl fmt::Display for TotpPublicKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        // the algorithm is registered as ESP256, see `Alg::Totp`
+        write!(f, "{}/TOTP", Self::KTY)
+    }
+}
+
+#[cfg(feature = "x25519")]
+impl fmt::Display for X25519PublicKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        key_summary::<Self>(f, &[("x", &self.pub_key)])
+    }
+}
+
+impl fmt::Display for PublicKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            #[cfg(feature = "p256")]
+            Self::P256Key(key) => key.fmt(f),
+            #[cfg(feature = "ecdh")]
+            Self::EcdhEsHkdf256Key(key) => key.fmt(f),
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519Key(key) => key.fmt(f),
+            #[cfg(feature = "totp")]
+            Self::TotpKey(key) => key.fmt(f),
+            #[cfg(feature = "x25519")]
+            Self::X25519Key(key) => key.fmt(f),
+        }
+    }
+}
@@ -0,0 +1,99 @@
+//! Ephemeral-static ECDH on P-256
+//!
+//! [`EphemeralKey`] performs the key agreement of ECDH-ES (RFC 9053, section 6.3.1) and of the
+//! CTAP2 PIN/UV auth protocols: it generates an ephemeral key pair, provides its public key as
+//! an [`EcdhEsHkdf256PublicKey`] for the header or the `keyAgreement` parameter, and computes the
+//! shared secret with the peer's static key.  Deriving keys from the shared secret depends on the
+//! protocol and is left to the caller.
+//!
+//! This module requires the `p256-curve` and `rand_core` features.
+
+use core::fmt::{self, Formatter};
+
+use p256_curve::elliptic_curve::sec1::FromEncodedPoint as _;
+use p256_curve::{ecdh::EphemeralSecret, EncodedPoint};
+use rand_core::CryptoRngCore;
+use zeroize::{Zeroize as _, ZeroizeOnDrop};
+
+use crate::keypair::p256_coordinates;
+use crate::{EcdhEsHkdf256PublicKey, Error};
+
+/// An ephemeral P-256 key pair
+pub struct EphemeralKey {
+    secret: EphemeralSecret,
+}
+
+impl EphemeralKey {
+    /// Generates a new key pair.
+    pub fn generate<R: CryptoRngCore>(rng: &mut R) -> Self {
+        Self {
+            secret: EphemeralSecret::random(rng),
+        }
+    }
+
+    /// Returns the public key of this key pair.
+    pub fn public_key(&self) -> EcdhEsHkdf256PublicKey {
+        let (x, y) = p256_coordinates(&self.secret.public_key());
+        EcdhEsHkdf256PublicKey { x, y }
+    }
+
+    /// Computes the shared secret with the given public key.
+    ///
+    /// Returns [`Error::InvalidKey`] if the public key is not a point on the curve.
+    pub fn agree(&self, peer: &EcdhEsHkdf256PublicKey) -> Result<SharedSecret, Error> {
+        let point = EncodedPoint::from_affine_coordinates(&peer.x.into(), &peer.y.into(), false);
+        let peer = Option::from(p256_curve::PublicKey::from_encoded_point(&point))
+            .ok_or(Error::InvalidKey)?;
+        let mut secret = SharedSecret([0; 32]);
+        secret
+            .0
+            .copy_from_slice(self.secret.diffie_hellman(&peer).raw_secret_bytes());
+        Ok(secret)
+    }
+}
+
+impl fmt::Debug for EphemeralKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("EphemeralKey").finish_non_exhaustive()
+    }
+}
+
+/// The shared secret of a key agreement, the x-coordinate of the shared point
+///
+/// The secret is zeroized when it is dropped.  It does not implement `PartialEq` as comparisons
+/// of secrets should be constant-time, see [`as_bytes`][Self::as_bytes].
+#[derive(Clone)]
+pub struct SharedSecret([u8; 32]);
+
+impl SharedSecret {
+    /// Returns the bytes of the shared secret.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+}
+
+impl Drop for SharedSecret {
+    fn drop(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for SharedSecret {}
+
+impl fmt::Debug for SharedSecret {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("SharedSecret").finish_non_exhaustive()
+    }
+}
+
+/// Generates an ephemeral key pair and computes the shared secret with the given public key.
+///
+/// Returns the ephemeral public key, which must be sent to the peer, and the shared secret.
+pub fn agree_ephemeral<R: CryptoRngCore>(
+    rng: &mut R,
+    peer: &EcdhEsHkdf256PublicKey,
+) -> Result<(EcdhEsHkdf256PublicKey, SharedSecret), Error> {
+    let key = EphemeralKey::generate(rng);
+    let secret = key.agree(peer)?;
+    Ok((key.public_key(), secret))
+}
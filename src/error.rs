@@ -0,0 +1,328 @@
+use core::fmt::{self, Formatter};
+
+use crate::{Alg, Crv, KeyOp, Kty, Label};
+
+/// Errors returned by the parsing helpers of this crate
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Error {
+    /// The input ended before the expected data
+    ///
+    /// This is also returned if the input ends within a CBOR data item, so that incomplete input
+    /// can be distinguished from malformed input.
+    Truncated,
+    /// The authenticator data does not contain attested credential data
+    MissingAttestedCredentialData,
+    /// The input is not a COSE_Key map or a field has an invalid encoding
+    InvalidKey,
+    /// A required field is missing
+    MissingField(&'static str),
+    /// An integer or length is not encoded in the shortest possible form
+    NonMinimal,
+    /// The key has a semantic tag that is not accepted, see
+    /// [`ParseOptions::tags`][crate::ParseOptions::tags]
+    UnexpectedTag(u64),
+    /// The input contains data after the COSE_Key, which occupies the given number of bytes
+    TrailingData { consumed: usize },
+    /// A field with the given label is not known to this crate
+    UnknownField(i64),
+    /// A field with a text label is not known to this crate, see
+    /// [`ParseOptions::text_labels`][crate::ParseOptions::text_labels]
+    UnknownTextField,
+    /// A byte or text string is longer than the limit set with
+    /// [`ParseOptions::max_string_len`][crate::ParseOptions::max_string_len]
+    StringTooLong { max: usize, actual: u64 },
+    /// The serialized key does not fit into the output buffer
+    BufferTooSmall,
+    /// The key was stored in a version of the storage format that is not supported, see
+    /// [`RawCoseKey::from_stored`][crate::RawCoseKey::from_stored]
+    UnsupportedVersion(u64),
+    /// A field occurs more than once
+    DuplicateField(&'static str),
+    /// The fields are not in canonical order
+    NonCanonicalOrder,
+    /// The value of the field with the given label is not supported by this crate
+    UnsupportedValue { label: i8, value: i64 },
+    /// The key type does not match the expected key type
+    WrongKty { expected: Kty, actual: Kty },
+    /// The algorithm does not match the expected algorithm
+    WrongAlg { expected: Alg, actual: Alg },
+    /// The curve does not match the expected curve
+    WrongCrv { expected: Crv, actual: Crv },
+    /// The key material for the field with the given label does not have the expected length
+    InvalidLength {
+        label: i8,
+        expected: usize,
+        actual: usize,
+    },
+    /// The field with the given label must be borrowed from the input, but the deserializer
+    /// only provides a temporary copy, e.g. because it reads from a stream
+    NotBorrowed { label: i8 },
+    /// The field with the given label is a point of small order or the identity, see
+    /// [`ParseOptions::reject_small_order`][crate::ParseOptions::reject_small_order]
+    SmallOrder { label: i8 },
+    /// The algorithm is not allowed by the policy set with
+    /// [`ParseOptions::allowed_algs`][crate::ParseOptions::allowed_algs]
+    DisallowedAlg(Alg),
+    /// The coordinate with the given label is not less than the field prime, see
+    /// [`ParseOptions::check_coordinate_range`][crate::ParseOptions::check_coordinate_range]
+    CoordinateOutOfRange { label: i8 },
+    /// `key_ops` has more than [`MAX_KEY_OPS`][crate::MAX_KEY_OPS] entries or a text entry that
+    /// is longer than [`MAX_KEY_OP_LEN`][crate::MAX_KEY_OP_LEN] bytes
+    KeyOpsTooLong,
+    /// `key_ops` does not permit the given operation, see
+    /// [`RawCoseKey::assert_permits`][crate::RawCoseKey::assert_permits] and
+    /// [`ParseOptions::enforce_key_ops`][crate::ParseOptions::enforce_key_ops]
+    KeyOpNotPermitted(KeyOp),
+    /// The field with the given label is not a valid unpadded base64url string, see
+    /// [`ParseOptions::human_readable`][crate::ParseOptions::human_readable]
+    InvalidBase64Url { label: i8 },
+}
+
+impl Error {
+    /// Returns the label of the field that caused the error, if any.
+    pub fn label(&self) -> Option<i8> {
+        match self {
+            Self::MissingField(field) | Self::DuplicateField(field) => {
+                Label::from_name(field).map(|label| label as i8)
+            }
+            Self::UnsupportedValue { label, .. }
+            | Self::InvalidLength { label, .. }
+            | Self::NotBorrowed { label }
+            | Self::SmallOrder { label }
+            | Self::CoordinateOutOfRange { label }
+            | Self::InvalidBase64Url { label } => Some(*label),
+            Self::WrongKty { .. } => Some(Label::Kty as i8),
+            Self::WrongAlg { .. } | Self::DisallowedAlg(_) => Some(Label::Alg as i8),
+            Self::WrongCrv { .. } => Some(Label::Crv as i8),
+            Self::KeyOpsTooLong | Self::KeyOpNotPermitted(_) => Some(Label::KeyOps as i8),
+            _ => None,
+        }
+    }
+
+    /// Returns a numeric code for the kind of the error.
+    ///
+    /// The codes do not change between releases, so they can be logged instead of the message on
+    /// targets where the message is too expensive to keep.
+    pub const fn code(&self) -> u8 {
+        match self {
+            Self::Truncated => 1,
+            Self::MissingAttestedCredentialData => 2,
+            Self::InvalidKey => 3,
+            Self::MissingField(_) => 4,
+            Self::NonMinimal => 5,
+            Self::UnexpectedTag(_) => 6,
+            Self::TrailingData { .. } => 7,
+            Self::UnknownField(_) => 8,
+            Self::UnknownTextField => 9,
+            Self::StringTooLong { .. } => 10,
+            Self::BufferTooSmall => 11,
+            Self::UnsupportedVersion(_) => 12,
+            Self::DuplicateField(_) => 13,
+            Self::NonCanonicalOrder => 14,
+            Self::UnsupportedValue { .. } => 15,
+            Self::WrongKty { .. } => 16,
+            Self::WrongAlg { .. } => 17,
+            Self::WrongCrv { .. } => 18,
+            Self::InvalidLength { .. } => 19,
+            Self::NotBorrowed { .. } => 20,
+            Self::SmallOrder { .. } => 21,
+            Self::DisallowedAlg(_) => 22,
+            Self::CoordinateOutOfRange { .. } => 23,
+            Self::KeyOpsTooLong => 24,
+            Self::KeyOpNotPermitted(_) => 25,
+            Self::InvalidBase64Url { .. } => 26,
+        }
+    }
+
+    // Converts the error for use in `Deserialize` implementations.  The message includes the
+    // label and the offending value to make interoperability issues easier to debug.
+    #[cfg(feature = "serde")]
+    pub(crate) fn into_de_error<E: serde::de::Error>(self) -> E {
+        E::custom(self)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        use Part::{Int, Str, Uint};
+
+        if let Some(label) = self.label() {
+            write_parts(f, &[Str("label "), Int(label.into()), Str(": ")])?;
+        }
+        let label_name = |label: i8| Label::try_from(i64::from(label)).map_or("value", Label::name);
+        match self {
+            Self::Truncated => f.write_str("unexpected end of input"),
+            Self::MissingAttestedCredentialData => f.write_str("missing attested credential data"),
+            Self::InvalidKey => f.write_str("invalid COSE_Key"),
+            Self::MissingField(field) => {
+                write_parts(f, &[Str("missing field `"), Str(field), Str("`")])
+            }
+            Self::DuplicateField(field) => {
+                write_parts(f, &[Str("duplicate field `"), Str(field), Str("`")])
+            }
+            Self::NonMinimal => f.write_str("non-minimal encoding"),
+            Self::UnexpectedTag(tag) => write_parts(f, &[Str("unexpected tag "), Uint(*tag)]),
+            Self::TrailingData { consumed } => write_parts(
+                f,
+                &[
+                    Str("trailing data after "),
+                    Uint(*consumed as u64),
+                    Str(" bytes"),
+                ],
+            ),
+            Self::UnknownField(label) => {
+                write_parts(f, &[Str("unknown field with label "), Int(*label)])
+            }
+            Self::UnknownTextField => f.write_str("unknown field with text label"),
+            Self::BufferTooSmall => f.write_str("buffer too small"),
+            Self::UnsupportedVersion(version) => write_parts(
+                f,
+                &[Str("unsupported storage format version "), Uint(*version)],
+            ),
+            Self::StringTooLong { max, actual } => write_parts(
+                f,
+                &[
+                    Str("string of "),
+                    Uint(*actual),
+                    Str(" bytes exceeds limit of "),
+                    Uint(*max as u64),
+                    Str(" bytes"),
+                ],
+            ),
+            Self::NonCanonicalOrder => f.write_str("public key data in wrong order"),
+            Self::UnsupportedValue { label, value } => write_parts(
+                f,
+                &[
+                    Str("unsupported "),
+                    Str(label_name(*label)),
+                    Str(" "),
+                    Int(*value),
+                ],
+            ),
+            Self::WrongKty { expected, actual } => {
+                write_mismatch(f, "kty", expected.to_i32(), actual.to_i32())
+            }
+            Self::WrongAlg { expected, actual } => {
+                write_mismatch(f, "alg", expected.to_i32(), actual.to_i32())
+            }
+            Self::WrongCrv { expected, actual } => {
+                write_mismatch(f, "crv", expected.to_i32(), actual.to_i32())
+            }
+            Self::InvalidLength {
+                label,
+                expected,
+                actual,
+            } => write_parts(
+                f,
+                &[
+                    Str("expected "),
+                    Uint(*expected as u64),
+                    Str(" bytes for "),
+                    Str(label_name(*label)),
+                    Str(", got "),
+                    Uint(*actual as u64),
+                ],
+            ),
+            Self::NotBorrowed { .. } => {
+                f.write_str("byte string cannot be borrowed from the input")
+            }
+            Self::SmallOrder { .. } => f.write_str("point of small order"),
+            Self::CoordinateOutOfRange { .. } => f.write_str("coordinate out of range"),
+            Self::KeyOpsTooLong => f.write_str("too many or too long key operations"),
+            Self::InvalidBase64Url { .. } => f.write_str("invalid base64url"),
+            Self::KeyOpNotPermitted(op) => {
+                let op = match op.name() {
+                    Some(name) => Str(name),
+                    None => Int(op.to_i32().into()),
+                };
+                write_parts(f, &[Str("key operation "), op, Str(" is not permitted")])
+            }
+            Self::DisallowedAlg(alg) => write_parts(
+                f,
+                &[
+                    Str("alg "),
+                    Int(alg.to_i32().into()),
+                    Str(" is not allowed"),
+                ],
+            ),
+        }
+    }
+}
+
+fn write_mismatch(f: &mut Formatter<'_>, name: &str, expected: i32, actual: i32) -> fmt::Result {
+    use Part::{Int, Str};
+
+    write_parts(
+        f,
+        &[
+            Str("expected "),
+            Str(name),
+            Str(" "),
+            Int(expected.into()),
+            Str(", got "),
+            Int(actual.into()),
+        ],
+    )
+}
+
+// A part of a message, see `write_parts`
+pub(crate) enum Part<'a> {
+    Str(&'a str),
+    Int(i64),
+    Uint(u64),
+    // Lowercase hex digits without separators
+    #[cfg(feature = "serde")]
+    Hex(&'a [u8]),
+}
+
+// Writes a message using only `Formatter::write_str`.  Formatting integers with `write!` links
+// the integer formatting of `core::fmt`, which is large compared to the rest of this crate, into
+// every build that displays an error, e.g. through `serde::de::Error::custom`.
+pub(crate) fn write_parts(f: &mut Formatter<'_>, parts: &[Part<'_>]) -> fmt::Result {
+    for part in parts {
+        match *part {
+            Part::Str(s) => f.write_str(s)?,
+            Part::Int(value) => {
+                if value < 0 {
+                    f.write_str("-")?;
+                }
+                write_uint(f, value.unsigned_abs())?;
+            }
+            Part::Uint(value) => write_uint(f, value)?,
+            #[cfg(feature = "serde")]
+            Part::Hex(bytes) => write_hex(f, bytes)?,
+        }
+    }
+    Ok(())
+}
+
+fn write_uint(f: &mut Formatter<'_>, mut value: u64) -> fmt::Result {
+    let mut digits = [0; 20];
+    let mut len = 0;
+    for digit in digits.iter_mut().rev() {
+        *digit = b'0' + (value % 10) as u8;
+        len += 1;
+        value /= 10;
+        if value == 0 {
+            break;
+        }
+    }
+    let digits = digits.get(digits.len() - len..).ok_or(fmt::Error)?;
+    // the digits are ASCII
+    f.write_str(core::str::from_utf8(digits).map_err(|_| fmt::Error)?)
+}
+
+#[cfg(feature = "serde")]
+fn write_hex(f: &mut Formatter<'_>, bytes: &[u8]) -> fmt::Result {
+    const DIGITS: &[u8; 16] = b"0123456789abcdef";
+    for byte in bytes {
+        let digits = [byte >> 4, byte & 0xf]
+            .map(|nibble| DIGITS.get(usize::from(nibble)).copied().unwrap_or(b'0'));
+        // the digits are ASCII
+        f.write_str(core::str::from_utf8(&digits).map_err(|_| fmt::Error)?)?;
+    }
+    Ok(())
+}
+
+impl core::error::Error for Error {}
@@ -0,0 +1,257 @@
+//! Helpers for fuzzing and robustness tests
+//!
+//! [`MALFORMED`] is a corpus of malformed and adversarial encodings that can be used to seed
+//! fuzzers, and [`check`] parses an input with all decoders of this crate and asserts that they
+//! agree.  Decoders may report different errors for the same input, e.g. the serde-based decoder
+//! returns [`Error::InvalidKey`] where the [`StreamDecoder`] knows which field is invalid, so only
+//! the result of [`PublicKey::from_slice_exact`] is returned.
+
+#[cfg(all(feature = "ed25519", feature = "p256"))]
+use crate::Alg;
+#[cfg(feature = "p256")]
+use crate::Crv;
+use crate::{Error, ParseOptions, PublicKey, RawCoseKey, StreamDecoder};
+
+/// A malformed encoding and the error returned for it
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Malformed {
+    /// What is wrong with the encoding
+    pub description: &'static str,
+    /// The encoding
+    pub data: &'static [u8],
+    /// The error of [`check`] with the default [`ParseOptions`]
+    pub error: Error,
+}
+
+/// Malformed encodings with duplicate fields, non-canonical order, truncations, wrong lengths,
+/// huge lengths and other adversarial inputs
+pub const MALFORMED: &[Malformed] = &[
+    Malformed {
+        description: "an empty input",
+        data: b"",
+        error: Error::Truncated,
+    },
+    #[cfg(feature = "p256")]
+    Malformed {
+        description: "a key truncated in a coordinate",
+        data: b"\xa5\x01\x02\x03\x26\x20\x01\x21\x58\x20\xff\xff",
+        error: Error::Truncated,
+    },
+    Malformed {
+        description: "an array instead of a map",
+        data: b"\x80",
+        error: Error::InvalidKey,
+    },
+    #[cfg(feature = "p256")]
+    Malformed {
+        description: "a duplicate `kty`",
+        data:
+            b"\xa5\x01\x02\x01\x02\x03\x26\x20\x01\x21\x58\x20\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \xff\xff",
+        error: Error::DuplicateField("kty"),
+    },
+    #[cfg(feature = "p256")]
+    Malformed {
+        description: "`crv` before `alg`",
+        data:
+            b"\xa5\x01\x02\x20\x01\x03\x26\x21\x58\x20\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \x22\x58\x20\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff",
+        error: Error::NonCanonicalOrder,
+    },
+    #[cfg(feature = "p256")]
+    Malformed {
+        description: "a coordinate that is too short",
+        data:
+            b"\xa5\x01\x02\x03\x26\x20\x01\x21\x58\x1f\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\x22\
+            \x58\x20\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff",
+        error: Error::InvalidLength {
+            label: -2,
+            expected: 32,
+            actual: 31,
+        },
+    },
+    #[cfg(feature = "p256")]
+    Malformed {
+        description: "a coordinate that is too long",
+        data:
+            b"\xa5\x01\x02\x03\x26\x20\x01\x21\x58\x21\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \xff\x22\x58\x20\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff",
+        error: Error::InvalidLength {
+            label: -2,
+            expected: 32,
+            actual: 33,
+        },
+    },
+    Malformed {
+        description: "a byte string with a length of 2^64 - 1",
+        data: b"\xa5\x01\x02\x03\x26\x20\x01\x21\x5b\xff\xff\xff\xff\xff\xff\xff\xff",
+        error: Error::Truncated,
+    },
+    Malformed {
+        description: "a map with 2^64 - 1 entries",
+        data: b"\xbb\xff\xff\xff\xff\xff\xff\xff\xff\x01\x02",
+        error: Error::Truncated,
+    },
+    #[cfg(feature = "totp")]
+    Malformed {
+        description: "a key followed by another byte",
+        data: b"\xa2\x01\x04\x03\x28\xff",
+        error: Error::TrailingData { consumed: 5 },
+    },
+    Malformed {
+        description: "a non-minimal encoding of `kty`",
+        data: b"\xa2\x01\x18\x04\x03\x28\x00",
+        error: Error::NonMinimal,
+    },
+    Malformed {
+        description: "a tagged value",
+        data: b"\xa2\x01\xc1\x04\x03\x28\x00",
+        error: Error::UnexpectedTag(1),
+    },
+    Malformed {
+        description: "a text label",
+        data: b"\xa3\x01\x04\x03\x28\x61\x78\x00",
+        error: Error::InvalidKey,
+    },
+    #[cfg(feature = "p256")]
+    Malformed {
+        description: "a P-256 key without `y`",
+        data:
+            b"\xa4\x01\x02\x03\x26\x20\x01\x21\x58\x20\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff",
+        error: Error::MissingField("y"),
+    },
+    #[cfg(all(feature = "ed25519", feature = "p256"))]
+    Malformed {
+        description: "an OKP key with `alg` ES256",
+        data:
+            b"\xa4\x01\x01\x03\x26\x20\x06\x21\x58\x20\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff",
+        error: Error::WrongAlg {
+            expected: Alg::EdDsa,
+            actual: Alg::Es256,
+        },
+    },
+    Malformed {
+        description: "an unknown `alg`",
+        data: b"\xa2\x01\x04\x03\x20",
+        error: Error::UnsupportedValue {
+            label: 3,
+            value: -1,
+        },
+    },
+    #[cfg(feature = "totp")]
+    Malformed {
+        description: "an unknown `kty`",
+        data: b"\xa2\x01\x0a\x03\x28",
+        error: Error::UnsupportedValue {
+            label: 1,
+            value: 10,
+        },
+    },
+    #[cfg(feature = "p256")]
+    Malformed {
+        description: "an ES256 key on P-384",
+        data:
+            b"\xa5\x01\x02\x03\x26\x20\x02\x21\x58\x20\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \x22\x58\x20\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\
+            \xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff\xff",
+        error: Error::WrongCrv {
+            expected: Crv::P256,
+            actual: Crv::P384,
+        },
+    },
+    Malformed {
+        description: "an unknown field nested 100 levels deep",
+        data:
+            b"\xa3\x01\x04\x03\x28\x0a\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\
+            \x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\
+            \x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\
+            \x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\
+            \x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\x81\
+            \x81\x00",
+        error: Error::InvalidKey,
+    },
+    Malformed {
+        description: "a float as `kty`",
+        data: b"\xa2\x01\xfa\x40\x00\x00\x00\x03\x28\x00",
+        error: Error::InvalidKey,
+    },
+    Malformed {
+        description: "a break without an indefinite-length item",
+        data: b"\xff",
+        error: Error::InvalidKey,
+    },
+    Malformed {
+        description: "a reserved additional information value",
+        data: b"\xa2\x01\x1c\x03\x28",
+        error: Error::InvalidKey,
+    },
+];
+
+/// Parses `data` with all decoders of this crate and returns the result of
+/// [`PublicKey::from_slice_exact`].
+///
+/// # Panics
+///
+/// Panics if the decoders disagree, i.e. if one of them accepts `data` and another one rejects it
+/// or returns a different key.
+pub fn check(data: &[u8], options: ParseOptions) -> Result<PublicKey, Error> {
+    let result = PublicKey::from_slice_exact(data, options);
+    let others = [
+        (
+            "RawCoseKey::from_slice_exact",
+            RawCoseKey::from_slice_exact(data, options)
+                .and_then(|raw| PublicKey::from_raw(raw, options)),
+        ),
+        ("StreamDecoder", stream(data, options, data.len())),
+        ("StreamDecoder fed bytewise", stream(data, options, 1)),
+    ];
+    for (decoder, other) in others {
+        let agrees = match (&result, &other) {
+            (Ok(key), Ok(other)) => key == other,
+            (Err(_), Err(_)) => true,
+            _ => false,
+        };
+        assert!(
+            agrees,
+            "{decoder} returned {other:?}, PublicKey::from_slice_exact returned {result:?}"
+        );
+    }
+    if let Ok(key) = &result {
+        let taken = PublicKey::take_from_slice(data, options);
+        assert_eq!(
+            taken,
+            Ok((key.clone(), data.len())),
+            "PublicKey::take_from_slice"
+        );
+    }
+    result
+}
+
+// Feeds `data` to a `StreamDecoder` in chunks of `chunk_size` bytes and expects it to consume all
+// of `data`.
+fn stream(data: &[u8], options: ParseOptions, chunk_size: usize) -> Result<PublicKey, Error> {
+    let mut decoder = StreamDecoder::new(options);
+    let mut consumed = 0;
+    for chunk in data.chunks(chunk_size.max(1)) {
+        if let Some((key, len)) = decoder.feed(chunk)? {
+            consumed += len;
+            return if consumed == data.len() {
+                Ok(key)
+            } else {
+                Err(Error::TrailingData { consumed })
+            };
+        }
+        consumed += chunk.len();
+    }
+    Err(Error::Truncated)
+}
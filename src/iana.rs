@@ -0,0 +1,283 @@
+//! Values of the IANA COSE registries
+//!
+//! The key types, algorithms, curves and key operations are generated by the build script from
+//! the registry extracts in the `iana` directory.  Besides the [`Kty`], [`Alg`], [`Crv`] and
+//! [`KeyOp`] enums, this module provides the values as integer constants and the key types that
+//! can be used with each algorithm and curve, and the labels of the key parameters.
+
+use core::fmt::{self, Formatter};
+#[cfg(feature = "serde")]
+use serde::{de::Expected, Deserialize, Serialize};
+
+use crate::error::{write_parts, Part};
+
+// Defines an enum for the values of a COSE registry.  Values that are not listed are represented
+// by the `Other` variant, so that keys with such values can still be parsed and inspected.
+macro_rules! registry {
+    (
+        $(#[$attr:meta])*
+        pub enum $name:ident {
+            $(
+                $(#[$variant_attr:meta])*
+                $variant:ident = $value:literal => $text:literal,
+            )*
+        }
+    ) => {
+        $(#[$attr])*
+        #[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+        #[non_exhaustive]
+        pub enum $name {
+            $(
+                $(#[$variant_attr])*
+                $variant,
+            )*
+            /// A value that is not listed above
+            ///
+            /// Use [`from_i32`][Self::from_i32] to construct values so that listed values are
+            /// always mapped to their variant.
+            Other(i32),
+        }
+
+        impl $name {
+            /// Returns the variant for the given value.
+            pub const fn from_i32(value: i32) -> Self {
+                match value {
+                    $($value => Self::$variant,)*
+                    _ => Self::Other(value),
+                }
+            }
+
+            /// Returns the name of the variant in the IANA registry, or `None` for values that are
+            /// not listed.
+            pub const fn name(self) -> Option<&'static str> {
+                match self {
+                    $(Self::$variant => Some($text),)*
+                    Self::Other(_) => None,
+                }
+            }
+
+            /// Returns the variant with the given name in the IANA registry, or `None` if the
+            /// name is not listed.
+            ///
+            /// Names are compared exactly, so `"ES256"` is found, but `"es256"` is not.
+            pub fn from_name(name: &str) -> Option<Self> {
+                match name {
+                    $($text => Some(Self::$variant),)*
+                    _ => None,
+                }
+            }
+
+            /// Returns the value of the variant.
+            pub const fn to_i32(self) -> i32 {
+                match self {
+                    $(Self::$variant => $value,)*
+                    Self::Other(value) => value,
+                }
+            }
+        }
+
+        impl From<i32> for $name {
+            fn from(value: i32) -> Self {
+                Self::from_i32(value)
+            }
+        }
+
+        impl From<$name> for i32 {
+            fn from(value: $name) -> Self {
+                value.to_i32()
+            }
+        }
+
+        impl TryFrom<i64> for $name {
+            type Error = core::num::TryFromIntError;
+
+            fn try_from(value: i64) -> Result<Self, Self::Error> {
+                i32::try_from(value).map(Self::from_i32)
+            }
+        }
+
+        /// Generates listed values more often than other values, so that fuzzers reach the code
+        /// paths of known key types.
+        #[cfg(feature = "arbitrary")]
+        impl<'a> ::arbitrary::Arbitrary<'a> for $name {
+            fn arbitrary(u: &mut ::arbitrary::Unstructured<'a>) -> ::arbitrary::Result<Self> {
+                if u.arbitrary()? {
+                    u.choose(&[$($value),*]).copied().map(Self::from_i32)
+                } else {
+                    u.arbitrary().map(Self::from_i32)
+                }
+            }
+        }
+
+        /// Generates listed values more often than other values, see the `Arbitrary`
+        /// implementation for fuzzing.
+        #[cfg(feature = "quickcheck")]
+        impl ::quickcheck::Arbitrary for $name {
+            fn arbitrary(g: &mut ::quickcheck::Gen) -> Self {
+                match g.choose(&[$($value),*]) {
+                    Some(value) if bool::arbitrary(g) => Self::from_i32(*value),
+                    _ => Self::from_i32(i32::arbitrary(g)),
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl Serialize for $name {
+            fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+            where
+                S: serde::Serializer,
+            {
+                serializer.serialize_i32(self.to_i32())
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl<'de> Deserialize<'de> for $name {
+            fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+            where
+                D: serde::Deserializer<'de>,
+            {
+                i32::deserialize(deserializer).map(Self::from_i32)
+            }
+        }
+
+        /// Formats the name in the IANA registry, or the value if it is not listed.
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                match self.name() {
+                    Some(name) => f.write_str(name),
+                    None => write_parts(f, &[Part::Int(self.to_i32().into())]),
+                }
+            }
+        }
+
+        #[cfg(feature = "serde")]
+        impl Expected for $name {
+            fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+                write_parts(f, &[Part::Int(self.to_i32().into())])
+            }
+        }
+    };
+}
+
+include!(concat!(env!("OUT_DIR"), "/iana.rs"));
+
+impl Alg {
+    /// The algorithm of TOTP keys, `-9`
+    ///
+    /// Trussed encodes TOTP keys as symmetric keys with the algorithm `-9`, which was not
+    /// registered at the time.  RFC 9864 has since registered `-9` as [`Esp256`][Self::Esp256],
+    /// so this is an alias of `Esp256` that keeps the encoding of existing keys.  TOTP keys are
+    /// told apart from ESP256 keys by their key type, and [`name`][Self::name] returns `ESP256`.
+    #[allow(non_upper_case_globals)]
+    pub const Totp: Self = Self::Esp256;
+}
+
+/// The purpose of keys for an algorithm, see [`Alg::key_use`]
+#[derive(Copy, Clone, Debug, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub enum KeyUse {
+    /// Creating and verifying signatures
+    Signature,
+    /// Key agreement, e.g. ECDH
+    KeyAgreement,
+    /// Creating and verifying MACs, including one-time passwords
+    Mac,
+    /// Any other use, e.g. encryption or key wrapping, or an algorithm that is not listed
+    Other,
+}
+
+impl KeyUse {
+    /// Returns the key operations that permit using a public key for this purpose.
+    ///
+    /// A public key is used to verify signatures and MACs, and to derive keys or bits in a key
+    /// agreement.  Trussed also uses TOTP keys to create MACs.  [`KeyUse::Other`] has no
+    /// operations, as it does not say what the key is used for.
+    pub const fn key_ops(self) -> &'static [KeyOp] {
+        match self {
+            Self::Signature => &[KeyOp::Verify],
+            Self::KeyAgreement => &[KeyOp::DeriveKey, KeyOp::DeriveBits],
+            Self::Mac => &[KeyOp::MacVerify, KeyOp::MacCreate],
+            Self::Other => &[],
+        }
+    }
+}
+
+/// Labels of the COSE Key Common Parameters and COSE Key Type Parameters registries
+///
+/// The labels of the key type parameters depend on the key type, so they are defined in a
+/// module for each key type.
+pub mod label {
+    /// `kty`
+    pub const KTY: i8 = 1;
+    /// `kid`
+    pub const KID: i8 = 2;
+    /// `alg`
+    pub const ALG: i8 = 3;
+    /// `key_ops`
+    pub const KEY_OPS: i8 = 4;
+    /// `Base IV`
+    pub const BASE_IV: i8 = 5;
+
+    /// Labels of the parameters of OKP keys
+    pub mod okp {
+        /// `crv`
+        pub const CRV: i8 = -1;
+        /// `x`
+        pub const X: i8 = -2;
+        /// `d`
+        pub const D: i8 = -4;
+    }
+
+    /// Labels of the parameters of EC2 keys
+    pub mod ec2 {
+        /// `crv`
+        pub const CRV: i8 = -1;
+        /// `x`
+        pub const X: i8 = -2;
+        /// `y`
+        pub const Y: i8 = -3;
+        /// `d`
+        pub const D: i8 = -4;
+    }
+
+    /// Labels of the parameters of RSA keys
+    pub mod rsa {
+        /// `n`
+        pub const N: i8 = -1;
+        /// `e`
+        pub const E: i8 = -2;
+        /// `d`
+        pub const D: i8 = -3;
+        /// `p`
+        pub const P: i8 = -4;
+        /// `q`
+        pub const Q: i8 = -5;
+        /// `dP`
+        pub const DP: i8 = -6;
+        /// `dQ`
+        pub const DQ: i8 = -7;
+        /// `qInv`
+        pub const QINV: i8 = -8;
+        /// `other`
+        pub const OTHER: i8 = -9;
+        /// `r_i`
+        pub const R_I: i8 = -10;
+        /// `d_i`
+        pub const D_I: i8 = -11;
+        /// `t_i`
+        pub const T_I: i8 = -12;
+    }
+
+    /// Labels of the parameters of symmetric keys
+    pub mod symmetric {
+        /// `k`
+        pub const K: i8 = -1;
+    }
+
+    /// Labels of the parameters of HSS-LMS keys
+    pub mod hss_lms {
+        /// `pub`
+        pub const PUB: i8 = -1;
+    }
+}
@@ -0,0 +1,70 @@
+//! Conveniences for hosts with `std`
+
+use std::io::{self, BufRead, ErrorKind, Write};
+use std::vec::Vec;
+
+use crate::{Error, ParseOptions, PublicKey, RawCoseKey, StreamDecoder};
+
+impl RawCoseKey {
+    /// Serializes the key in the canonical CTAP2 form.
+    pub fn to_vec(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::with_capacity(Self::MAX_ENCODED_LEN);
+        self.fields().write_parts(|part| {
+            bytes.extend_from_slice(part);
+            Ok::<_, Error>(())
+        })?;
+        Ok(bytes)
+    }
+
+    /// Serializes the key in the canonical CTAP2 form and writes it to `writer`.
+    ///
+    /// The encoding is written in parts, so `writer` should be buffered if it is expensive to
+    /// write to.
+    pub fn to_writer<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.fields().write_parts(|part| writer.write_all(part))
+    }
+}
+
+impl PublicKey {
+    /// Serializes the key in the canonical CTAP2 form.
+    pub fn to_vec(&self) -> Result<Vec<u8>, Error> {
+        let mut bytes = Vec::with_capacity(Self::MAX_ENCODED_LEN);
+        self.fields().write_parts(|part| {
+            bytes.extend_from_slice(part);
+            Ok::<_, Error>(())
+        })?;
+        Ok(bytes)
+    }
+
+    /// Serializes the key in the canonical CTAP2 form and writes it to `writer`.
+    ///
+    /// The encoding is written in parts, see [`RawCoseKey::to_writer`].
+    pub fn to_writer<W: Write>(&self, mut writer: W) -> io::Result<()> {
+        self.fields().write_parts(|part| writer.write_all(part))
+    }
+
+    /// Reads a COSE_Key from `reader` using the given options.
+    ///
+    /// Only the bytes of the key are consumed, so data following the key can be read from
+    /// `reader` afterwards.  Parsing errors are returned with [`ErrorKind::InvalidData`] and
+    /// the [`Error`] as the inner error.
+    pub fn from_reader<R: BufRead>(mut reader: R, options: ParseOptions) -> io::Result<Self> {
+        let mut decoder = StreamDecoder::new(options);
+        loop {
+            let data = reader.fill_buf()?;
+            if data.is_empty() {
+                return Err(io::Error::new(ErrorKind::UnexpectedEof, Error::Truncated));
+            }
+            let result = decoder.feed(data).map_err(invalid_data)?;
+            let consumed = result.as_ref().map_or(data.len(), |(_, len)| *len);
+            reader.consume(consumed);
+            if let Some((key, _)) = result {
+                return Ok(key);
+            }
+        }
+    }
+}
+
+fn invalid_data(error: Error) -> io::Error {
+    io::Error::new(ErrorKind::InvalidData, error)
+}
@@ -0,0 +1,192 @@
+//! The `key_ops` parameter of COSE_Keys
+//!
+//! RFC 9052 defines `key_ops` as a non-empty array of integers and text strings.  The integers
+//! are listed in [`KeyOp`].  Text strings are kept as they are, so keys with operation names, e.g.
+//! the JWK key operations of RFC 7517, section 4.3, are encoded again without changes.
+
+#[cfg(feature = "serde")]
+use core::fmt::{self, Formatter};
+
+use heapless::{String, Vec};
+#[cfg(feature = "serde")]
+use serde::de::{DeserializeSeed, IgnoredAny, SeqAccess, Unexpected, Visitor};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::label;
+use crate::{Error, KeyOp, KeyUse};
+
+/// The maximum number of entries of [`KeyOps`]
+pub const MAX_KEY_OPS: usize = 10;
+
+/// The maximum length of the text entries of [`KeyOps`] in bytes
+pub const MAX_KEY_OP_LEN: usize = 16;
+
+/// The entries of the `key_ops` parameter in their original order
+pub type KeyOps = Vec<KeyOpEntry, MAX_KEY_OPS>;
+
+/// An entry of the `key_ops` parameter
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub enum KeyOpEntry {
+    /// An integer, usually one of the values listed in [`KeyOp`]
+    Int(KeyOp),
+    /// A text string
+    Text(String<MAX_KEY_OP_LEN>),
+}
+
+impl KeyOpEntry {
+    /// Creates a text entry.
+    ///
+    /// Returns `None` if the text is longer than [`MAX_KEY_OP_LEN`] bytes.
+    pub fn text(text: &str) -> Option<Self> {
+        let mut string = String::new();
+        string.push_str(text).ok()?;
+        Some(Self::Text(string))
+    }
+
+    /// Returns the listed key operation of the entry, or `None` if it is not listed.
+    ///
+    /// Text entries are matched with the names of the IANA registry, e.g. `"MAC create"`, and
+    /// with the names of the JWK key operations, e.g. `"wrapKey"`.
+    pub fn op(&self) -> Option<KeyOp> {
+        let text = match self {
+            Self::Int(KeyOp::Other(_)) => return None,
+            Self::Int(op) => return Some(*op),
+            Self::Text(text) => text.as_str(),
+        };
+        KeyOp::from_name(text).or(match text {
+            "wrapKey" => Some(KeyOp::WrapKey),
+            "unwrapKey" => Some(KeyOp::UnwrapKey),
+            "deriveKey" => Some(KeyOp::DeriveKey),
+            "deriveBits" => Some(KeyOp::DeriveBits),
+            _ => None,
+        })
+    }
+}
+
+// Returns whether `key_ops` permits the operation.  A key without `key_ops` permits all
+// operations.
+pub(crate) fn permits(key_ops: Option<&[KeyOpEntry]>, op: KeyOp) -> bool {
+    key_ops.map_or(true, |key_ops| {
+        key_ops.iter().any(|entry| entry.op() == Some(op))
+    })
+}
+
+// Checks that `key_ops` permits one of the operations of the key use.
+pub(crate) fn check_use(key_ops: Option<&[KeyOpEntry]>, key_use: KeyUse) -> Result<(), Error> {
+    let ops = key_use.key_ops();
+    match ops.first() {
+        Some(op) if !ops.iter().any(|op| permits(key_ops, *op)) => {
+            Err(Error::KeyOpNotPermitted(*op))
+        }
+        _ => Ok(()),
+    }
+}
+
+impl From<KeyOp> for KeyOpEntry {
+    fn from(op: KeyOp) -> Self {
+        Self::Int(op)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for KeyOpEntry {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        match self {
+            Self::Int(op) => op.serialize(serializer),
+            Self::Text(text) => serializer.serialize_str(text),
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for KeyOpEntry {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_any(EntryVisitor)?
+            .map_err(Error::into_de_error)
+    }
+}
+
+// Reads an entry, reporting unsupported entries as `Error` so that they are not reduced to a
+// message.
+#[cfg(feature = "serde")]
+struct EntryVisitor;
+
+#[cfg(feature = "serde")]
+impl Visitor<'_> for EntryVisitor {
+    type Value = Result<KeyOpEntry, Error>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("an integer or text key operation")
+    }
+
+    fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Self::Value, E> {
+        Ok(KeyOp::try_from(v)
+            .map(KeyOpEntry::Int)
+            .map_err(|_| Error::UnsupportedValue {
+                label: label::KEY_OPS,
+                value: v,
+            }))
+    }
+
+    fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Self::Value, E> {
+        match i64::try_from(v) {
+            Ok(v) => self.visit_i64(v),
+            Err(_) => Err(E::invalid_value(Unexpected::Unsigned(v), &self)),
+        }
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(KeyOpEntry::text(v).ok_or(Error::KeyOpsTooLong))
+    }
+}
+
+// Reads the entries of `key_ops`.  An empty array is returned as it is and rejected when the key
+// is complete, as the streaming decoder only knows that the array is empty at its end.
+#[cfg(feature = "serde")]
+pub(crate) struct KeyOpsSeed;
+
+#[cfg(feature = "serde")]
+impl<'de> DeserializeSeed<'de> for KeyOpsSeed {
+    type Value = Result<KeyOps, Error>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for KeyOpsSeed {
+    type Value = Result<KeyOps, Error>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("an array of key operations")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut ops = KeyOps::new();
+        while let Some(entry) = seq.next_element_seed(EntrySeed)? {
+            let result = entry.and_then(|entry| ops.push(entry).map_err(|_| Error::KeyOpsTooLong));
+            if let Err(err) = result {
+                // see map::visit_entries
+                while seq.next_element::<IgnoredAny>()?.is_some() {}
+                return Ok(Err(err));
+            }
+        }
+        Ok(Ok(ops))
+    }
+}
+
+#[cfg(feature = "serde")]
+struct EntrySeed;
+
+#[cfg(feature = "serde")]
+impl<'de> DeserializeSeed<'de> for EntrySeed {
+    type Value = Result<KeyOpEntry, Error>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Self::Value, D::Error> {
+        deserializer.deserialize_any(EntryVisitor)
+    }
+}
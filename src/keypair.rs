@@ -0,0 +1,232 @@
+//! Key pairs with cosey-typed public keys
+//!
+//! The key pair types compute the public key of a private key with a crypto backend, so that
+//! COSE_Keys can be obtained from a private key or generated without combining several crates.
+//! Each type requires the feature of its public key type and a backend:
+//!
+//! - `p256-curve` for [`P256KeyPair`] and [`EcdhEsHkdf256KeyPair`]
+//! - `curve25519-dalek` and `sha2` for [`Ed25519KeyPair`]
+//! - `curve25519-dalek` for [`X25519KeyPair`]
+//!
+//! Generating key pairs additionally requires the `rand_core` feature, and deriving insecure key
+//! pairs for tests with `insecure_from_seed` requires the `sha2` and `test-vectors` features.
+
+#[cfg(any(
+    all(feature = "ecdh", feature = "p256-curve"),
+    all(feature = "ed25519", feature = "curve25519-dalek", feature = "sha2"),
+    all(feature = "p256", feature = "p256-curve"),
+    all(feature = "x25519", feature = "curve25519-dalek"),
+))]
+use crate::{CoseKeyType as _, Crv, PrivateKey};
+
+#[cfg(feature = "p256-curve")]
+use p256_curve::elliptic_curve::sec1::ToEncodedPoint as _;
+
+#[cfg(any(
+    all(feature = "ecdh", feature = "p256-curve"),
+    all(feature = "ed25519", feature = "curve25519-dalek", feature = "sha2"),
+    all(feature = "p256", feature = "p256-curve"),
+    all(feature = "x25519", feature = "curve25519-dalek"),
+))]
+macro_rules! key_pair {
+    (
+        $(#[$attr:meta])*
+        $name:ident($public:ident) {
+            crv: $crv:expr,
+            public_key: $public_key:expr,
+            coordinates: |$key:ident| $coordinates:expr $(,)?
+        }
+    ) => {
+        $(#[$attr])*
+        ///
+        /// The private key is zeroized when the key pair is dropped.  As comparisons of private
+        /// keys should be constant-time, key pairs do not implement `PartialEq`.
+        #[derive(Clone)]
+        pub struct $name {
+            secret: [u8; 32],
+            public_key: crate::$public,
+        }
+
+        impl $name {
+            /// Creates a key pair from the given private key.
+            ///
+            /// Returns `None` if the private key is not valid for the curve.  The rejected private
+            /// key is zeroized.
+            pub fn from_secret(mut secret: [u8; 32]) -> Option<Self> {
+                let Some(public_key) = $public_key(&secret) else {
+                    zeroize::Zeroize::zeroize(&mut secret);
+                    return None;
+                };
+                Some(Self { secret, public_key })
+            }
+
+            /// Generates a new key pair.
+            #[cfg(feature = "rand_core")]
+            pub fn generate<R: rand_core::CryptoRngCore>(rng: &mut R) -> Self {
+                // the candidates are zeroized, also if they are rejected
+                let mut secret = zeroize::Zeroizing::new([0; 32]);
+                loop {
+                    rng.fill_bytes(&mut *secret);
+                    if let Some(key_pair) = Self::from_secret(*secret) {
+                        return key_pair;
+                    }
+                }
+            }
+
+            /// Derives a key pair from a seed, for test fixtures only.
+            ///
+            /// **Not for production use:** the private key is the SHA-256 hash of the seed and a
+            /// fixed prefix, so anyone who knows the seed knows the private key.  The same seed
+            /// always yields the same key pair, also across releases.
+            #[cfg(all(feature = "sha2", feature = "test-vectors"))]
+            pub fn insecure_from_seed(seed: &[u8]) -> Self {
+                use sha2::{Digest as _, Sha256};
+
+                // retry with the next counter if the private key is not valid for the curve
+                let mut counter = 0u32;
+                loop {
+                    let secret = Sha256::new()
+                        .chain_update(b"cosey insecure test key\0")
+                        .chain_update(stringify!($name))
+                        .chain_update(counter.to_be_bytes())
+                        .chain_update(seed)
+                        .finalize();
+                    if let Some(key_pair) = Self::from_secret(secret.into()) {
+                        return key_pair;
+                    }
+                    counter = counter.wrapping_add(1);
+                }
+            }
+
+            /// Returns the public key.
+            pub fn public_key(&self) -> crate::$public {
+                self.public_key
+            }
+
+            /// Returns the private key.
+            pub fn secret(&self) -> &[u8; 32] {
+                &self.secret
+            }
+
+            /// Returns the key pair as a [`PrivateKey`] that includes the public key.
+            pub fn to_private_key(&self) -> PrivateKey {
+                let $key = &self.public_key;
+                let (x, y) = $coordinates;
+                PrivateKey {
+                    kty: crate::$public::KTY,
+                    alg: Some(crate::$public::ALG),
+                    crv: $crv,
+                    x: Some(x),
+                    y,
+                    d: self.secret,
+                }
+            }
+        }
+
+        impl Drop for $name {
+            fn drop(&mut self) {
+                zeroize::Zeroize::zeroize(&mut self.secret);
+            }
+        }
+
+        impl zeroize::ZeroizeOnDrop for $name {}
+
+        impl core::fmt::Debug for $name {
+            fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+                f.debug_struct(stringify!($name))
+                    .field("public_key", &self.public_key)
+                    .finish_non_exhaustive()
+            }
+        }
+    };
+}
+
+#[cfg(all(feature = "p256", feature = "p256-curve"))]
+key_pair! {
+    /// A P-256 key pair for ES256
+    P256KeyPair(P256PublicKey) {
+        crv: Crv::P256,
+        public_key: |secret| {
+            p256_public(secret).map(|(x, y)| crate::P256PublicKey { x, y })
+        },
+        coordinates: |key| (key.x, Some(key.y)),
+    }
+}
+
+#[cfg(all(feature = "ecdh", feature = "p256-curve"))]
+key_pair! {
+    /// A P-256 key pair for ECDH-ES + HKDF-256
+    EcdhEsHkdf256KeyPair(EcdhEsHkdf256PublicKey) {
+        crv: Crv::P256,
+        public_key: |secret| {
+            p256_public(secret).map(|(x, y)| crate::EcdhEsHkdf256PublicKey { x, y })
+        },
+        coordinates: |key| (key.x, Some(key.y)),
+    }
+}
+
+#[cfg(all(feature = "ed25519", feature = "curve25519-dalek", feature = "sha2"))]
+key_pair! {
+    /// An Ed25519 key pair
+    ///
+    /// The private key is the 32-byte seed of RFC 8032, section 5.1.5.
+    Ed25519KeyPair(Ed25519PublicKey) {
+        crv: Crv::Ed25519,
+        public_key: |secret| Some(crate::Ed25519PublicKey { x: ed25519_public(secret) }),
+        coordinates: |key| (key.x, None),
+    }
+}
+
+#[cfg(all(feature = "x25519", feature = "curve25519-dalek"))]
+key_pair! {
+    /// An X25519 key pair
+    X25519KeyPair(X25519PublicKey) {
+        crv: Crv::X25519,
+        public_key: |secret| Some(crate::X25519PublicKey { pub_key: x25519_public(secret) }),
+        coordinates: |key| (key.pub_key, None),
+    }
+}
+
+// Returns the coordinates of the public key of a P-256 private key, or `None` if the private key
+// is zero or not less than the group order
+#[cfg(feature = "p256-curve")]
+pub(crate) fn p256_public(secret: &[u8; 32]) -> Option<([u8; 32], [u8; 32])> {
+    // `from_slice` avoids a copy of the private key that would not be zeroized
+    let secret = p256_curve::SecretKey::from_slice(secret).ok()?;
+    Some(p256_coordinates(&secret.public_key()))
+}
+
+#[cfg(feature = "p256-curve")]
+pub(crate) fn p256_coordinates(public_key: &p256_curve::PublicKey) -> ([u8; 32], [u8; 32]) {
+    let point = public_key.to_encoded_point(false);
+    let mut coordinates = ([0; 32], [0; 32]);
+    // a public key is never the identity, so the point has both coordinates
+    if let (Some(x), Some(y)) = (point.x(), point.y()) {
+        coordinates.0.copy_from_slice(x);
+        coordinates.1.copy_from_slice(y);
+    }
+    coordinates
+}
+
+#[cfg(all(feature = "curve25519-dalek", feature = "sha2"))]
+pub(crate) fn ed25519_public(secret: &[u8; 32]) -> [u8; 32] {
+    use sha2::{Digest as _, Sha512};
+    use zeroize::{Zeroize as _, Zeroizing};
+
+    // the expanded secret and the scalar are the signing key, so they are zeroized
+    let mut hash = Sha512::digest(secret);
+    let mut scalar = Zeroizing::new([0; 32]);
+    scalar
+        .iter_mut()
+        .zip(hash.iter())
+        .for_each(|(s, h)| *s = *h);
+    (*hash).zeroize();
+    curve25519_dalek::EdwardsPoint::mul_base_clamped(*scalar)
+        .compress()
+        .to_bytes()
+}
+
+#[cfg(feature = "curve25519-dalek")]
+pub(crate) fn x25519_public(secret: &[u8; 32]) -> [u8; 32] {
+    curve25519_dalek::MontgomeryPoint::mul_base_clamped(*secret).to_bytes()
+}
@@ -1,4 +1,14 @@
 #![no_std]
+// Parsing must not panic on any input, so panicking operations are not used anywhere.
+#![deny(
+    clippy::expect_used,
+    clippy::indexing_slicing,
+    clippy::panic,
+    clippy::unreachable,
+    clippy::unwrap_used
+)]
+// Without a decoder, the checks applied to decoded keys are unused.
+#![cfg_attr(not(any(feature = "cbor", feature = "serde")), allow(dead_code))]
 //! # cosey
 //!
 //! Data types and serde for public COSE_Keys
@@ -16,6 +26,11 @@
 //!
 //! https://www.iana.org/assignments/cose/cose.xhtml#key-type-parameters
 //!
+//! The serde implementations are behind the default `serde` feature.  Without it, keys are
+//! encoded and decoded with the `encode` and `decode` methods of the `cbor` feature.
+//! Human-readable serializers like serde-json-core get labels as decimal strings and byte
+//! strings as unpadded base64url, which is read back with [`ParseOptions::human_readable`].
+//!
 //!
 //! Key Type 1 (OKP)
 //! -1: crv
@@ -43,33 +58,174 @@
    }
 */
 
+#[cfg(feature = "std")]
+extern crate std;
+
+#[cfg(not(any(
+    feature = "ecdh",
+    feature = "ed25519",
+    feature = "p256",
+    feature = "totp",
+    feature = "x25519"
+)))]
+compile_error!("at least one of the features ecdh, ed25519, p256, totp and x25519 must be enabled");
+
+#[cfg(feature = "serde")]
 use core::fmt::{self, Formatter};
 pub use heapless_bytes::Bytes;
+#[cfg(feature = "serde")]
 use serde::{
-    de::{Error as _, Expected, MapAccess, Unexpected},
+    de::{DeserializeSeed, MapAccess, Unexpected, Visitor},
     Deserialize, Serialize,
 };
+#[cfg(feature = "serde")]
 use serde_repr::{Deserialize_repr, Serialize_repr};
 
+#[cfg(feature = "serde")]
+use custom::{ByteStr, LabelKey};
+#[cfg(feature = "serde")]
+use error::{write_parts, Part};
+#[cfg(feature = "serde")]
+use key_ops::KeyOpsSeed;
+use map::{MapFields, SeenFields};
+
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+#[cfg(feature = "serde")]
+mod base64url;
+#[cfg(all(
+    any(feature = "ecdh", feature = "ed25519", feature = "p256"),
+    feature = "serde"
+))]
+mod borrowed;
+#[cfg(feature = "cbor")]
+mod cbor;
+#[cfg(feature = "cbor")]
+mod codec;
+pub mod ctap;
+#[cfg(feature = "serde")]
+mod custom;
+mod display;
+#[cfg(all(feature = "ecdh", feature = "p256-curve", feature = "rand_core"))]
+pub mod ecdh_es;
+mod error;
+#[cfg(feature = "fuzzing")]
+pub mod fuzzing;
+pub mod iana;
+#[cfg(feature = "std")]
+mod io;
+mod key_ops;
+#[cfg(any(feature = "curve25519-dalek", feature = "p256-curve"))]
+pub mod keypair;
+mod map;
+pub mod metadata;
+pub mod oid;
+mod options;
+mod order;
+pub mod oscore;
+mod private;
+#[cfg(feature = "quickcheck")]
+mod quickcheck;
+#[cfg(all(feature = "cbor", feature = "serde"))]
+mod storage;
+#[cfg(feature = "cbor")]
+mod stream;
+#[cfg(feature = "test-vectors")]
+pub mod test_vectors;
+
+#[cfg(all(feature = "ecdh", feature = "serde"))]
+pub use borrowed::EcdhEsHkdf256PublicKeyRef;
+#[cfg(all(feature = "ed25519", feature = "serde"))]
+pub use borrowed::Ed25519PublicKeyRef;
+#[cfg(all(feature = "p256", feature = "serde"))]
+pub use borrowed::P256PublicKeyRef;
+#[cfg(all(feature = "cbor", feature = "serde"))]
+pub use cbor::{peek_alg, peek_kty};
+pub use error::Error;
+pub use iana::{label, Alg, Crv, KeyOp, KeyUse, Kty};
+pub use key_ops::{KeyOpEntry, KeyOps, MAX_KEY_OPS, MAX_KEY_OP_LEN};
+#[cfg(all(feature = "ecdh", feature = "p256-curve"))]
+pub use keypair::EcdhEsHkdf256KeyPair;
+#[cfg(all(feature = "ed25519", feature = "curve25519-dalek", feature = "sha2"))]
+pub use keypair::Ed25519KeyPair;
+#[cfg(all(feature = "p256", feature = "p256-curve"))]
+pub use keypair::P256KeyPair;
+#[cfg(all(feature = "x25519", feature = "curve25519-dalek"))]
+pub use keypair::X25519KeyPair;
+pub use options::{Ec2Variant, ParseOptions, Profile, TagPolicy};
+pub use private::PrivateKey;
+#[cfg(all(feature = "cbor", feature = "serde"))]
+pub use storage::STORAGE_VERSION;
+#[cfg(feature = "cbor")]
+pub use stream::{KeySetDecoder, StreamDecoder};
+
+// Used by the code generated by `define_cose_key`
+#[cfg(feature = "serde")]
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::custom::*;
+}
+
 #[repr(i8)]
-#[derive(Clone, Debug, Eq, PartialEq, Serialize_repr, Deserialize_repr)]
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize_repr, Deserialize_repr))]
 enum Label {
-    Kty = 1,
-    Alg = 3,
-    Crv = -1,
-    X = -2,
-    Y = -3,
+    Kty = label::KTY,
+    Alg = label::ALG,
+    KeyOps = label::KEY_OPS,
+    Crv = label::ec2::CRV,
+    X = label::ec2::X,
+    Y = label::ec2::Y,
+}
+
+impl Label {
+    fn name(self) -> &'static str {
+        match self {
+            Self::Kty => "kty",
+            Self::Alg => "alg",
+            Self::KeyOps => "key_ops",
+            Self::Crv => "crv",
+            Self::X => "x",
+            Self::Y => "y",
+        }
+    }
+
+    fn from_name(name: &str) -> Option<Self> {
+        [
+            Self::Kty,
+            Self::Alg,
+            Self::KeyOps,
+            Self::Crv,
+            Self::X,
+            Self::Y,
+        ]
+        .into_iter()
+        .find(|label| label.name() == name)
+    }
+
+    // position in the canonical CTAP2 order, i.e. sorted by the encoded label
+    fn canonical_index(self) -> u8 {
+        match self {
+            Self::Kty => 0,
+            Self::Alg => 1,
+            Self::KeyOps => 2,
+            Self::Crv => 3,
+            Self::X => 4,
+            Self::Y => 5,
+        }
+    }
 }
 
 struct TryFromIntError;
 
-impl TryFrom<i8> for Label {
+impl TryFrom<i64> for Label {
     type Error = TryFromIntError;
 
-    fn try_from(label: i8) -> Result<Self, Self::Error> {
+    fn try_from(label: i64) -> Result<Self, Self::Error> {
         Ok(match label {
             1 => Self::Kty,
             3 => Self::Alg,
+            4 => Self::KeyOps,
             -1 => Self::Crv,
             -2 => Self::X,
             -3 => Self::Y,
@@ -80,196 +236,604 @@ impl TryFrom<i8> for Label {
     }
 }
 
-#[repr(i8)]
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize_repr, Deserialize_repr)]
-enum Kty {
-    Okp = 1,
-    Ec2 = 2,
-    Symmetric = 4,
+// A map key, see `ParseOptions::text_labels`
+pub(crate) enum Key {
+    Label(i64),
+    UnknownText,
 }
 
-impl Expected for Kty {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", *self as i8)
+impl Key {
+    pub(crate) fn from_text(text: &str) -> Self {
+        // JOSE parameter names (RFC 7518) or decimal integers
+        let label = match text {
+            "kty" => 1,
+            "kid" => 2,
+            "alg" => 3,
+            "key_ops" => 4,
+            "crv" => -1,
+            "x" => -2,
+            "y" => -3,
+            "d" => -4,
+            _ => match text.parse() {
+                Ok(label) => label,
+                Err(_) => return Self::UnknownText,
+            },
+        };
+        Self::Label(label)
     }
 }
 
-#[repr(i8)]
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize_repr, Deserialize_repr)]
-enum Alg {
-    Es256 = -7, // ECDSA with SHA-256
-    EdDsa = -8,
-    Totp = -9, // Unassigned, we use it for TOTP
+#[cfg(feature = "serde")]
+#[derive(Clone, Copy)]
+pub(crate) struct KeySeed {
+    pub(crate) text_labels: bool,
+    pub(crate) human_readable: bool,
+}
 
-    // MAC
-    // Hs256 = 5,
-    // Hs512 = 7,
+#[cfg(feature = "serde")]
+impl KeySeed {
+    pub(crate) fn new(options: ParseOptions) -> Self {
+        Self {
+            text_labels: options.text_labels,
+            human_readable: options.human_readable,
+        }
+    }
+}
 
-    // AEAD
-    // A128Gcm = 1,
-    // A256Gcm = 3,
-    // lots of AES-CCM, why??
-    // ChaCha20Poly1305 = 24,
+#[cfg(feature = "serde")]
+impl<'de> DeserializeSeed<'de> for KeySeed {
+    type Value = Key;
 
-    // Key Agreement
-    EcdhEsHkdf256 = -25, // ES = ephemeral-static
-}
+    fn deserialize<D>(self, deserializer: D) -> Result<Key, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        let human_readable = self.human_readable && deserializer.is_human_readable();
+        if !self.text_labels && !human_readable {
+            return i64::deserialize(deserializer).map(Key::Label);
+        }
+
+        struct KeyVisitor;
+
+        impl Visitor<'_> for KeyVisitor {
+            type Value = Key;
+
+            fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+                formatter.write_str("an integer or text label")
+            }
+
+            fn visit_i64<E: serde::de::Error>(self, v: i64) -> Result<Key, E> {
+                Ok(Key::Label(v))
+            }
 
-impl Expected for Alg {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", *self as i8)
+            fn visit_u64<E: serde::de::Error>(self, v: u64) -> Result<Key, E> {
+                i64::try_from(v)
+                    .map(Key::Label)
+                    .map_err(|_| E::invalid_value(Unexpected::Unsigned(v), &self))
+            }
+
+            fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Key, E> {
+                Ok(Key::from_text(v))
+            }
+        }
+
+        // JSON only has text keys, and serde-json-core does not support `deserialize_any`.
+        if human_readable {
+            deserializer.deserialize_str(KeyVisitor)
+        } else {
+            deserializer.deserialize_any(KeyVisitor)
+        }
     }
 }
 
-#[repr(i8)]
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Serialize_repr, Deserialize_repr)]
-enum Crv {
-    None = 0,
-    P256 = 1,
-    // P384 = 2,
-    // P512 = 3,
-    X25519 = 4,
-    // X448 = 5,
-    Ed25519 = 6,
-    // Ed448 = 7,
+// Reads a byte string with at most N bytes, reporting longer byte strings as `InvalidLength`
+// instead of failing in `Bytes`.  Human-readable formats have base64url strings instead.
+#[cfg(feature = "serde")]
+pub(crate) struct BytesSeed<const N: usize> {
+    pub(crate) label: i8,
+    pub(crate) human_readable: bool,
 }
 
-impl Expected for Crv {
-    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
-        write!(f, "{}", *self as i8)
+#[cfg(feature = "serde")]
+impl<'de, const N: usize> DeserializeSeed<'de> for BytesSeed<N> {
+    type Value = Result<Bytes<N>, Error>;
+
+    fn deserialize<D>(mut self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        if self.human_readable && deserializer.is_human_readable() {
+            deserializer.deserialize_str(self)
+        } else {
+            self.human_readable = false;
+            deserializer.deserialize_bytes(self)
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<const N: usize> Visitor<'_> for BytesSeed<N> {
+    type Value = Result<Bytes<N>, Error>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        let kind = if self.human_readable {
+            "a base64url string of at most "
+        } else {
+            "a byte string with at most "
+        };
+        write_parts(
+            formatter,
+            &[Part::Str(kind), Part::Uint(N as u64), Part::Str(" bytes")],
+        )
+    }
+
+    fn visit_bytes<E: serde::de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        Ok(Bytes::from_slice(v).map_err(|()| Error::InvalidLength {
+            label: self.label,
+            expected: N,
+            actual: v.len(),
+        }))
+    }
+
+    fn visit_str<E: serde::de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        if !self.human_readable {
+            return Err(E::invalid_type(Unexpected::Str(v), &self));
+        }
+        let decode = || {
+            let len = base64url::decoded_len(self.label, v)?;
+            let mut buffer = [0; N];
+            let output = buffer.get_mut(..len).ok_or(Error::InvalidLength {
+                label: self.label,
+                expected: N,
+                actual: len,
+            })?;
+            base64url::decode(self.label, v, output)?;
+            Bytes::from_slice(output).map_err(|()| Error::InvalidKey)
+        };
+        Ok(decode())
     }
 }
 
-// `Deserialize` can't be derived on untagged enum,
-// would need to "sniff" for correct (Kty, Alg, Crv) triple
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
-#[serde(untagged)]
+// `Deserialize` can't be derived on untagged enum without alloc, so we parse a `RawCoseKey`
+// and dispatch on the (Kty, Alg, Crv) triple instead
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize), serde(untagged))]
 pub enum PublicKey {
+    #[cfg(feature = "p256")]
     P256Key(P256PublicKey),
+    #[cfg(feature = "ecdh")]
     EcdhEsHkdf256Key(EcdhEsHkdf256PublicKey),
+    #[cfg(feature = "ed25519")]
     Ed25519Key(Ed25519PublicKey),
+    #[cfg(feature = "totp")]
     TotpKey(TotpPublicKey),
+    #[cfg(feature = "x25519")]
+    X25519Key(X25519PublicKey),
 }
 
+#[cfg(feature = "p256")]
 impl From<P256PublicKey> for PublicKey {
     fn from(key: P256PublicKey) -> Self {
         PublicKey::P256Key(key)
     }
 }
 
+#[cfg(feature = "ecdh")]
 impl From<EcdhEsHkdf256PublicKey> for PublicKey {
     fn from(key: EcdhEsHkdf256PublicKey) -> Self {
         PublicKey::EcdhEsHkdf256Key(key)
     }
 }
 
+#[cfg(feature = "ed25519")]
 impl From<Ed25519PublicKey> for PublicKey {
     fn from(key: Ed25519PublicKey) -> Self {
         PublicKey::Ed25519Key(key)
     }
 }
 
+#[cfg(feature = "totp")]
 impl From<TotpPublicKey> for PublicKey {
     fn from(key: TotpPublicKey) -> Self {
         PublicKey::TotpKey(key)
     }
 }
 
-#[derive(Clone, Debug, Default)]
-struct RawPublicKey {
-    kty: Option<Kty>,
-    alg: Option<Alg>,
-    crv: Option<Crv>,
-    x: Option<Bytes<32>>,
-    y: Option<Bytes<32>>,
+#[cfg(feature = "x25519")]
+impl From<X25519PublicKey> for PublicKey {
+    fn from(key: X25519PublicKey) -> Self {
+        PublicKey::X25519Key(key)
+    }
 }
 
-impl<'de> Deserialize<'de> for RawPublicKey {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+#[cfg(feature = "p256")]
+impl TryFrom<PublicKey> for P256PublicKey {
+    type Error = PublicKey;
+
+    fn try_from(key: PublicKey) -> Result<Self, PublicKey> {
+        match key {
+            #[cfg(feature = "p256")]
+            PublicKey::P256Key(key) => Ok(key),
+            #[allow(unreachable_patterns)]
+            _ => Err(key),
+        }
+    }
+}
+
+#[cfg(feature = "ecdh")]
+impl TryFrom<PublicKey> for EcdhEsHkdf256PublicKey {
+    type Error = PublicKey;
+
+    fn try_from(key: PublicKey) -> Result<Self, PublicKey> {
+        match key {
+            #[cfg(feature = "ecdh")]
+            PublicKey::EcdhEsHkdf256Key(key) => Ok(key),
+            #[allow(unreachable_patterns)]
+            _ => Err(key),
+        }
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl TryFrom<PublicKey> for Ed25519PublicKey {
+    type Error = PublicKey;
+
+    fn try_from(key: PublicKey) -> Result<Self, PublicKey> {
+        match key {
+            #[cfg(feature = "ed25519")]
+            PublicKey::Ed25519Key(key) => Ok(key),
+            #[allow(unreachable_patterns)]
+            _ => Err(key),
+        }
+    }
+}
+
+#[cfg(feature = "totp")]
+impl TryFrom<PublicKey> for TotpPublicKey {
+    type Error = PublicKey;
+
+    fn try_from(key: PublicKey) -> Result<Self, PublicKey> {
+        match key {
+            #[cfg(feature = "totp")]
+            PublicKey::TotpKey(key) => Ok(key),
+            #[allow(unreachable_patterns)]
+            _ => Err(key),
+        }
+    }
+}
+
+#[cfg(feature = "x25519")]
+impl TryFrom<PublicKey> for X25519PublicKey {
+    type Error = PublicKey;
+
+    fn try_from(key: PublicKey) -> Result<Self, PublicKey> {
+        match key {
+            #[cfg(feature = "x25519")]
+            PublicKey::X25519Key(key) => Ok(key),
+            #[allow(unreachable_patterns)]
+            _ => Err(key),
+        }
+    }
+}
+
+/// A COSE_Key with the fields known to this crate
+///
+/// Deserializing a `RawCoseKey` only checks the encoding and the order of the fields, but not
+/// whether they form a valid key.  This makes it possible to inspect a key before converting it
+/// into one of the key types with `TryFrom`, or to implement key types that are not supported by
+/// this crate.
+///
+/// `key_ops` is kept with its entries in their original order.  The key types do not keep it, but
+/// they can be restricted to the operations it permits, see [`RawCoseKey::assert_permits`] and
+/// [`ParseOptions::enforce_key_ops`].  As its entries may be integers or text strings, reading it requires a
+/// self-describing deserializer like the one used by the parsing functions of this crate, i.e.
+/// keys with `key_ops` cannot be deserialized with cbor-smol.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+#[non_exhaustive]
+pub struct RawCoseKey {
+    pub kty: Option<Kty>,
+    pub alg: Option<Alg>,
+    pub key_ops: Option<KeyOps>,
+    pub crv: Option<Crv>,
+    pub x: Option<Bytes<32>>,
+    pub y: Option<Bytes<32>>,
+}
+
+impl RawCoseKey {
+    /// The maximum length of the canonical CBOR encoding of a `RawCoseKey`
+    pub const MAX_ENCODED_LEN: usize = 1
+        + 3 * (1 + int_len(i32::MIN as i64))
+        + 2 * (1 + bytes_len(32))
+        + (1 + head_len(MAX_KEY_OPS as u64) + MAX_KEY_OPS * bytes_len(MAX_KEY_OP_LEN));
+
+    /// Returns the length of the canonical CBOR encoding of the key.
+    pub fn encoded_len(&self) -> usize {
+        let ints = [
+            self.kty.map(Kty::to_i32),
+            self.alg.map(Alg::to_i32),
+            self.crv.map(Crv::to_i32),
+        ];
+        let bytes = [&self.x, &self.y];
+        let key_ops = self.key_ops.as_ref().map(|key_ops| {
+            // text strings have the same heads as byte strings
+            let entries = key_ops.iter().map(|entry| match entry {
+                KeyOpEntry::Int(op) => int_len(op.to_i32().into()),
+                KeyOpEntry::Text(text) => bytes_len(text.len()),
+            });
+            1 + head_len(key_ops.len() as u64) + entries.sum::<usize>()
+        });
+        let fields = ints.iter().flatten().count()
+            + bytes.iter().copied().flatten().count()
+            + usize::from(key_ops.is_some());
+        // all labels are encoded in a single byte
+        head_len(fields as u64)
+            + key_ops.unwrap_or_default()
+            + ints
+                .into_iter()
+                .flatten()
+                .map(|value| 1 + int_len(value.into()))
+                .sum::<usize>()
+            + bytes
+                .into_iter()
+                .flatten()
+                .map(|value| 1 + bytes_len(value.len()))
+                .sum::<usize>()
+    }
+
+    /// Returns whether `key_ops` permits the given operation.
+    ///
+    /// Keys without `key_ops` permit all operations.  Text entries are matched by name, see
+    /// [`KeyOpEntry::op`].
+    pub fn permits(&self, op: KeyOp) -> bool {
+        key_ops::permits(self.key_ops.as_deref(), op)
+    }
+
+    /// Checks that `key_ops` permits the given operation.
+    ///
+    /// Returns [`Error::KeyOpNotPermitted`] if it does not, see [`permits`][Self::permits].
+    pub fn assert_permits(&self, op: KeyOp) -> Result<(), Error> {
+        if self.permits(op) {
+            Ok(())
+        } else {
+            Err(Error::KeyOpNotPermitted(op))
+        }
+    }
+
+    /// Checks that `key_ops` permits one of the operations of the given key use, see
+    /// [`KeyUse::key_ops`].
+    ///
+    /// Returns [`Error::KeyOpNotPermitted`] with the first operation of the key use if it does
+    /// not.  [`KeyUse::Other`] is always permitted.
+    pub fn assert_permits_use(&self, key_use: KeyUse) -> Result<(), Error> {
+        key_ops::check_use(self.key_ops.as_deref(), key_use)
+    }
+
+    /// Deserializes a key using the given options.
+    #[cfg(feature = "serde")]
+    pub fn deserialize_with<'de, D>(
+        deserializer: D,
+        options: ParseOptions,
+    ) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        struct IndexedVisitor;
+        Self::try_deserialize_with(deserializer, options)?.map_err(Error::into_de_error)
+    }
+
+    // Deserializes a key, returning errors in the structure of the map separately from errors
+    // of the deserializer so that they are not reduced to a message.
+    #[cfg(feature = "serde")]
+    pub(crate) fn try_deserialize_with<'de, D>(
+        deserializer: D,
+        options: ParseOptions,
+    ) -> Result<Result<Self, Error>, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct IndexedVisitor {
+            options: ParseOptions,
+        }
+
         impl<'de> serde::de::Visitor<'de> for IndexedVisitor {
-            type Value = RawPublicKey;
+            type Value = Result<RawCoseKey, Error>;
 
             fn expecting(&self, formatter: &mut core::fmt::Formatter) -> core::fmt::Result {
-                formatter.write_str("RawPublicKey")
+                formatter.write_str("RawCoseKey")
             }
 
-            fn visit_map<V>(self, mut map: V) -> Result<RawPublicKey, V::Error>
+            fn visit_map<V>(self, mut map: V) -> Result<Self::Value, V::Error>
             where
                 V: MapAccess<'de>,
             {
-                #[derive(PartialEq)]
-                enum Key {
-                    Label(Label),
-                    Unknown(i8),
-                    None,
+                let mut builder = RawCoseKeyBuilder::new(self.options);
+                let seed = KeySeed::new(self.options);
+                if let Err(err) = map::visit_entries(&mut builder, &mut map, seed)? {
+                    return Ok(Err(err));
                 }
+                Ok(builder.finish())
+            }
+        }
+        deserializer.deserialize_map(IndexedVisitor { options })
+    }
+}
 
-                fn next_key<'a, V: MapAccess<'a>>(map: &mut V) -> Result<Key, V::Error> {
-                    let key: Option<i8> = map.next_key()?;
-                    let key = match key {
-                        Some(key) => match Label::try_from(key) {
-                            Ok(label) => Key::Label(label),
-                            Err(_) => Key::Unknown(key),
-                        },
-                        None => Key::None,
-                    };
-                    Ok(key)
-                }
+// Collects the fields of a `RawCoseKey` and checks them against the `ParseOptions`.  This is
+// shared by the serde visitor and the streaming decoder.
+pub(crate) struct RawCoseKeyBuilder {
+    key: RawCoseKey,
+    fields: SeenFields,
+    options: ParseOptions,
+}
 
-                let mut public_key = RawPublicKey::default();
+impl RawCoseKeyBuilder {
+    pub(crate) fn new(options: ParseOptions) -> Self {
+        Self {
+            key: RawCoseKey::default(),
+            fields: SeenFields::new(options),
+            options,
+        }
+    }
 
-                // As we cannot deserialize arbitrary values with cbor-smol, we do not support
-                // unknown keys before a known key.  If there are unknown keys, they must be at the
-                // end.
+    // Sets the value of an integer field.  Values of enums are read as integers so that we can
+    // report unsupported values together with the label.
+    pub(crate) fn int(&mut self, label: Label, value: i64) -> Result<(), Error> {
+        fn parse<T: TryFrom<i64>>(label: Label, value: i64) -> Result<T, Error> {
+            T::try_from(value).map_err(|_| Error::UnsupportedValue {
+                label: label as i8,
+                value,
+            })
+        }
 
-                // only deserialize in canonical order
+        match label {
+            Label::Kty => self.key.kty = Some(parse(label, value)?),
+            Label::Alg => self.key.alg = Some(parse(label, value)?),
+            Label::Crv => self.key.crv = Some(parse(label, value)?),
+            Label::KeyOps | Label::X | Label::Y => return Err(Error::InvalidKey),
+        }
+        Ok(())
+    }
 
-                let mut key = next_key(&mut map)?;
+    // Sets the value of a byte string field.
+    pub(crate) fn bytes(&mut self, label: Label, bytes: Bytes<32>) -> Result<(), Error> {
+        match label {
+            Label::X => self.key.x = Some(bytes),
+            Label::Y => self.key.y = Some(bytes),
+            Label::Kty | Label::Alg | Label::KeyOps | Label::Crv => return Err(Error::InvalidKey),
+        }
+        Ok(())
+    }
 
-                if key == Key::Label(Label::Kty) {
-                    public_key.kty = Some(map.next_value()?);
-                    key = next_key(&mut map)?;
-                }
+    // Starts the `key_ops` field, whose entries are added with `key_op`.
+    #[cfg(feature = "cbor")]
+    pub(crate) fn start_key_ops(&mut self) {
+        self.key.key_ops = Some(KeyOps::new());
+    }
 
-                if key == Key::Label(Label::Alg) {
-                    public_key.alg = Some(map.next_value()?);
-                    key = next_key(&mut map)?;
-                }
+    // Adds an entry to the `key_ops` field.
+    #[cfg(feature = "cbor")]
+    pub(crate) fn key_op(&mut self, entry: KeyOpEntry) -> Result<(), Error> {
+        self.key
+            .key_ops
+            .get_or_insert_with(KeyOps::new)
+            .push(entry)
+            .map_err(|_| Error::KeyOpsTooLong)
+    }
 
-                if key == Key::Label(Label::Crv) {
-                    public_key.crv = Some(map.next_value()?);
-                    key = next_key(&mut map)?;
+    pub(crate) fn finish(self) -> Result<RawCoseKey, Error> {
+        let mut key = self.key;
+        // `key_ops` must not be empty
+        if key.key_ops.as_ref().is_some_and(KeyOps::is_empty) {
+            return Err(Error::InvalidKey);
+        }
+        if self.options.pad_short_coordinates && key.kty == Some(Kty::Ec2) {
+            let pad = |bytes: Bytes<32>| bytes_from_array(left_padded(&bytes));
+            key.x = key.x.map(pad);
+            key.y = key.y.map(pad);
+        }
+        match key.alg {
+            Some(alg) => self.options.check_alg(alg)?,
+            None if self.options.require_alg => return Err(Error::MissingField(Label::Alg.name())),
+            None => {}
+        }
+        if self.options.check_coordinate_range
+            && key.kty == Some(Kty::Ec2)
+            && key.crv == Some(Crv::P256)
+        {
+            for (label, coordinate) in [(Label::X, &key.x), (Label::Y, &key.y)] {
+                // coordinates of other lengths are rejected by the key types
+                let coordinate = coordinate.as_ref().map(Bytes::as_slice);
+                if coordinate.is_some_and(|c| c.len() == 32 && c >= P256_PRIME.as_slice()) {
+                    return Err(Error::CoordinateOutOfRange { label: label as i8 });
                 }
+            }
+        }
+        if self.options.reject_small_order && key.kty == Some(Kty::Okp) {
+            let blocklist = match key.crv {
+                Some(Crv::Ed25519) => ED25519_SMALL_ORDER,
+                Some(Crv::X25519) => X25519_SMALL_ORDER,
+                _ => &[],
+            };
+            if key.x.as_ref().is_some_and(|x| is_blocked(blocklist, x)) {
+                return Err(Error::SmallOrder {
+                    label: Label::X as i8,
+                });
+            }
+        }
+        Ok(key)
+    }
+}
 
-                if key == Key::Label(Label::X) {
-                    public_key.x = Some(map.next_value()?);
-                    key = next_key(&mut map)?;
-                }
+impl<'de> MapFields<'de> for RawCoseKeyBuilder {
+    type Field = Label;
 
-                if key == Key::Label(Label::Y) {
-                    public_key.y = Some(map.next_value()?);
-                    key = next_key(&mut map)?;
-                }
+    fn key(&mut self, key: Key) -> Result<Option<Label>, Error> {
+        let label = match key {
+            Key::Label(key) => Label::try_from(key).map_err(|_| Error::UnknownField(key)),
+            Key::UnknownText => Err(Error::UnknownTextField),
+        };
+        let known = label.as_ref().ok().copied();
+        let field = label.map(|label| (usize::from(label.canonical_index()), label.name()));
+        Ok(self.fields.check(field)?.and(known))
+    }
 
-                // if there is another key, it should be an unknown one
-                if matches!(key, Key::Label(_)) {
-                    Err(serde::de::Error::custom(
-                        "public key data in wrong order or with duplicates",
-                    ))
-                } else {
-                    Ok(public_key)
-                }
-            }
+    #[cfg(feature = "serde")]
+    fn read_value<V>(&mut self, label: Label, map: &mut V) -> Result<Result<(), Error>, V::Error>
+    where
+        V: MapAccess<'de>,
+    {
+        let result = match label {
+            Label::Kty | Label::Alg | Label::Crv => self.int(label, map.next_value()?),
+            Label::KeyOps => map
+                .next_value_seed(KeyOpsSeed)?
+                .map(|key_ops| self.key.key_ops = Some(key_ops)),
+            Label::X | Label::Y => map
+                .next_value_seed(BytesSeed {
+                    label: label as i8,
+                    human_readable: self.options.human_readable,
+                })?
+                .and_then(|bytes| self.bytes(label, bytes)),
+        };
+        Ok(result)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for RawCoseKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Self::deserialize_with(deserializer, ParseOptions::default())
+    }
+}
+
+// The fields of a key, borrowed from a `RawCoseKey` or one of the key types.  Keys are
+// serialized, encoded and compared through this view so that the key material is not copied.
+#[derive(Clone, Copy)]
+pub(crate) struct KeyFields<'a> {
+    pub(crate) kty: Option<Kty>,
+    pub(crate) alg: Option<Alg>,
+    pub(crate) key_ops: Option<&'a [KeyOpEntry]>,
+    pub(crate) crv: Option<Crv>,
+    pub(crate) x: Option<&'a [u8]>,
+    pub(crate) y: Option<&'a [u8]>,
+}
+
+impl<'a> KeyFields<'a> {
+    fn of<K: CoseKeyType>(x: Option<&'a [u8]>, y: Option<&'a [u8]>) -> Self {
+        Self {
+            kty: Some(K::KTY),
+            alg: Some(K::ALG),
+            key_ops: None,
+            crv: K::CRV,
+            x,
+            y,
         }
-        deserializer.deserialize_map(IndexedVisitor {})
     }
 }
 
-impl Serialize for RawPublicKey {
+#[cfg(feature = "serde")]
+impl Serialize for KeyFields<'_> {
     fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
     where
         S: serde::Serializer,
@@ -277,6 +841,7 @@ impl Serialize for RawPublicKey {
         let is_set = [
             self.kty.is_some(),
             self.alg.is_some(),
+            self.key_ops.is_some(),
             self.crv.is_some(),
             self.x.is_some(),
             self.y.is_some(),
@@ -287,207 +852,1190 @@ impl Serialize for RawPublicKey {
 
         //  1: kty
         if let Some(kty) = &self.kty {
-            map.serialize_entry(&(Label::Kty as i8), &(*kty as i8))?;
+            map.serialize_entry(&LabelKey(Label::Kty as i8), kty)?;
         }
         //  3: alg
         if let Some(alg) = &self.alg {
-            map.serialize_entry(&(Label::Alg as i8), &(*alg as i8))?;
+            map.serialize_entry(&LabelKey(Label::Alg as i8), alg)?;
+        }
+        //  4: key_ops
+        if let Some(key_ops) = self.key_ops {
+            map.serialize_entry(&LabelKey(Label::KeyOps as i8), key_ops)?;
         }
         // -1: crv
         if let Some(crv) = &self.crv {
-            map.serialize_entry(&(Label::Crv as i8), &(*crv as i8))?;
+            map.serialize_entry(&LabelKey(Label::Crv as i8), crv)?;
         }
         // -2: x
-        if let Some(x) = &self.x {
-            map.serialize_entry(&(Label::X as i8), x)?;
+        if let Some(x) = self.x {
+            map.serialize_entry(&LabelKey(Label::X as i8), &ByteStr(x))?;
         }
         // -3: y
-        if let Some(y) = &self.y {
-            map.serialize_entry(&(Label::Y as i8), y)?;
+        if let Some(y) = self.y {
+            map.serialize_entry(&LabelKey(Label::Y as i8), &ByteStr(y))?;
         }
 
         map.end()
     }
 }
 
-trait PublicKeyConstants {
+impl RawCoseKey {
+    pub(crate) fn fields(&self) -> KeyFields<'_> {
+        KeyFields {
+            kty: self.kty,
+            alg: self.alg,
+            key_ops: self.key_ops.as_deref(),
+            crv: self.crv,
+            x: self.x.as_ref().map(Bytes::as_slice),
+            y: self.y.as_ref().map(Bytes::as_slice),
+        }
+    }
+}
+
+macro_rules! impl_serialize {
+    ($($ty:ty),*) => {
+        $(
+            #[cfg(feature = "serde")]
+            impl Serialize for $ty {
+                fn serialize<S>(&self, serializer: S) -> core::result::Result<S::Ok, S::Error>
+                where
+                    S: serde::Serializer,
+                {
+                    self.fields().serialize(serializer)
+                }
+            }
+        )*
+    };
+}
+
+impl_serialize!(RawCoseKey);
+#[cfg(feature = "p256")]
+impl_serialize!(P256PublicKey);
+#[cfg(feature = "ecdh")]
+impl_serialize!(EcdhEsHkdf256PublicKey);
+#[cfg(feature = "ed25519")]
+impl_serialize!(Ed25519PublicKey);
+#[cfg(feature = "totp")]
+impl_serialize!(TotpPublicKey);
+#[cfg(feature = "x25519")]
+impl_serialize!(X25519PublicKey);
+
+/// The length of the coordinates of [`P256PublicKey`] and [`EcdhEsHkdf256PublicKey`]
+pub const P256_COORDINATE_SIZE: usize = 32;
+
+/// The length of the public key of [`Ed25519PublicKey`]
+pub const ED25519_PUBLIC_KEY_SIZE: usize = 32;
+
+/// The length of the public key of [`X25519PublicKey`]
+pub const X25519_PUBLIC_KEY_SIZE: usize = 32;
+
+/// The constants of a COSE_Key type
+///
+/// This trait is implemented by the key types of this crate and by the key types defined with
+/// [`define_cose_key`], so that generic code can be written for them.
+pub trait CoseKeyType {
+    /// The key type (`kty`)
     const KTY: Kty;
+    /// The algorithm (`alg`)
     const ALG: Alg;
-    const CRV: Crv;
+    /// The curve (`crv`), or `None` if the key type does not use curves
+    const CRV: Option<Crv>;
+    /// The labels and lengths of the byte string parameters in canonical order
+    const PARAMS: &'static [(i8, usize)];
+    /// The maximum length of the canonical CBOR encoding of the key
+    const MAX_ENCODED_LEN: usize = max_encoded_len::<Self>();
+
+    /// Returns whether keys of this type can be used with the given algorithm.
+    ///
+    /// See [`PublicKey::supports_alg`].
+    fn supports_alg(alg: i32) -> bool {
+        Self::ALG.to_i32() == alg
+    }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
-#[serde(into = "RawPublicKey")]
+#[cfg(feature = "p256")]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct P256PublicKey {
-    pub x: Bytes<32>,
-    pub y: Bytes<32>,
+    pub x: [u8; P256_COORDINATE_SIZE],
+    pub y: [u8; P256_COORDINATE_SIZE],
 }
 
-impl PublicKeyConstants for P256PublicKey {
+#[cfg(feature = "p256")]
+impl CoseKeyType for P256PublicKey {
     const KTY: Kty = Kty::Ec2;
     const ALG: Alg = Alg::Es256;
-    const CRV: Crv = Crv::P256;
+    const CRV: Option<Crv> = Some(Crv::P256);
+    const PARAMS: &'static [(i8, usize)] = &[
+        (Label::X as i8, P256_COORDINATE_SIZE),
+        (Label::Y as i8, P256_COORDINATE_SIZE),
+    ];
+}
+
+#[cfg(feature = "p256")]
+impl P256PublicKey {
+    /// The maximum length of the canonical CBOR encoding of the key
+    pub const MAX_ENCODED_LEN: usize = <Self as CoseKeyType>::MAX_ENCODED_LEN;
+
+    /// Returns the length of the canonical CBOR encoding of the key.
+    pub fn encoded_len(&self) -> usize {
+        Self::MAX_ENCODED_LEN
+    }
+
+    /// Returns whether both coordinates are less than the field prime of P-256.
+    ///
+    /// This is a cheap check for corrupted or malicious keys that does not check that the point
+    /// is on the curve.
+    pub fn coordinates_in_range(&self) -> bool {
+        self.x < P256_PRIME && self.y < P256_PRIME
+    }
+
+    /// Creates a key from its coordinates, checking that they have the required length.
+    pub fn new(x: &[u8], y: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            x: array_from_slice(Label::X, x)?,
+            y: array_from_slice(Label::Y, y)?,
+        })
+    }
+
+    /// Creates a key from its coordinates.
+    pub fn from_coordinates(x: [u8; 32], y: [u8; 32]) -> Self {
+        Self { x, y }
+    }
+
+    /// Returns the coordinates of the key.
+    pub fn into_coordinates(self) -> ([u8; 32], [u8; 32]) {
+        (self.x, self.y)
+    }
+}
+
+#[cfg(feature = "p256")]
+impl P256PublicKey {
+    fn fields(&self) -> KeyFields<'_> {
+        KeyFields::of::<Self>(Some(&self.x), Some(&self.y))
+    }
 }
 
-impl From<P256PublicKey> for RawPublicKey {
+#[cfg(feature = "p256")]
+impl From<P256PublicKey> for RawCoseKey {
     fn from(key: P256PublicKey) -> Self {
         Self {
             kty: Some(P256PublicKey::KTY),
             alg: Some(P256PublicKey::ALG),
-            crv: Some(P256PublicKey::CRV),
-            x: Some(key.x),
-            y: Some(key.y),
+            key_ops: None,
+            crv: P256PublicKey::CRV,
+            x: Some(bytes_from_array(key.x)),
+            y: Some(bytes_from_array(key.y)),
         }
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
-#[serde(into = "RawPublicKey")]
+#[cfg(feature = "ecdh")]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct EcdhEsHkdf256PublicKey {
-    pub x: Bytes<32>,
-    pub y: Bytes<32>,
+    pub x: [u8; P256_COORDINATE_SIZE],
+    pub y: [u8; P256_COORDINATE_SIZE],
 }
 
-impl PublicKeyConstants for EcdhEsHkdf256PublicKey {
+#[cfg(feature = "ecdh")]
+impl CoseKeyType for EcdhEsHkdf256PublicKey {
     const KTY: Kty = Kty::Ec2;
     const ALG: Alg = Alg::EcdhEsHkdf256;
-    const CRV: Crv = Crv::P256;
+    const CRV: Option<Crv> = Some(Crv::P256);
+    const PARAMS: &'static [(i8, usize)] = &[
+        (Label::X as i8, P256_COORDINATE_SIZE),
+        (Label::Y as i8, P256_COORDINATE_SIZE),
+    ];
 }
 
-impl From<EcdhEsHkdf256PublicKey> for RawPublicKey {
+#[cfg(feature = "ecdh")]
+impl EcdhEsHkdf256PublicKey {
+    /// The maximum length of the canonical CBOR encoding of the key
+    pub const MAX_ENCODED_LEN: usize = <Self as CoseKeyType>::MAX_ENCODED_LEN;
+
+    /// Returns the length of the canonical CBOR encoding of the key.
+    pub fn encoded_len(&self) -> usize {
+        Self::MAX_ENCODED_LEN
+    }
+
+    /// Returns whether both coordinates are less than the field prime of P-256.
+    ///
+    /// This is a cheap check for corrupted or malicious keys that does not check that the point
+    /// is on the curve.
+    pub fn coordinates_in_range(&self) -> bool {
+        self.x < P256_PRIME && self.y < P256_PRIME
+    }
+
+    /// Creates a key from its coordinates, checking that they have the required length.
+    pub fn new(x: &[u8], y: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            x: array_from_slice(Label::X, x)?,
+            y: array_from_slice(Label::Y, y)?,
+        })
+    }
+
+    /// Creates a key from its coordinates.
+    pub fn from_coordinates(x: [u8; 32], y: [u8; 32]) -> Self {
+        Self { x, y }
+    }
+
+    /// Returns the coordinates of the key.
+    pub fn into_coordinates(self) -> ([u8; 32], [u8; 32]) {
+        (self.x, self.y)
+    }
+}
+
+#[cfg(feature = "ecdh")]
+impl EcdhEsHkdf256PublicKey {
+    fn fields(&self) -> KeyFields<'_> {
+        KeyFields::of::<Self>(Some(&self.x), Some(&self.y))
+    }
+}
+
+#[cfg(feature = "ecdh")]
+impl From<EcdhEsHkdf256PublicKey> for RawCoseKey {
     fn from(key: EcdhEsHkdf256PublicKey) -> Self {
         Self {
             kty: Some(EcdhEsHkdf256PublicKey::KTY),
             alg: Some(EcdhEsHkdf256PublicKey::ALG),
-            crv: Some(EcdhEsHkdf256PublicKey::CRV),
-            x: Some(key.x),
-            y: Some(key.y),
+            key_ops: None,
+            crv: EcdhEsHkdf256PublicKey::CRV,
+            x: Some(bytes_from_array(key.x)),
+            y: Some(bytes_from_array(key.y)),
         }
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq, Serialize)]
-#[serde(into = "RawPublicKey")]
+#[cfg(feature = "ed25519")]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct Ed25519PublicKey {
-    pub x: Bytes<32>,
+    pub x: [u8; ED25519_PUBLIC_KEY_SIZE],
 }
 
-impl PublicKeyConstants for Ed25519PublicKey {
+#[cfg(feature = "ed25519")]
+impl CoseKeyType for Ed25519PublicKey {
     const KTY: Kty = Kty::Okp;
     const ALG: Alg = Alg::EdDsa;
-    const CRV: Crv = Crv::Ed25519;
+    const CRV: Option<Crv> = Some(Crv::Ed25519);
+    const PARAMS: &'static [(i8, usize)] = &[(Label::X as i8, ED25519_PUBLIC_KEY_SIZE)];
+}
+
+#[cfg(feature = "ed25519")]
+impl Ed25519PublicKey {
+    /// The maximum length of the canonical CBOR encoding of the key
+    pub const MAX_ENCODED_LEN: usize = <Self as CoseKeyType>::MAX_ENCODED_LEN;
+
+    /// Returns the length of the canonical CBOR encoding of the key.
+    pub fn encoded_len(&self) -> usize {
+        Self::MAX_ENCODED_LEN
+    }
+
+    /// Creates a key from its public key, checking that it has the required length.
+    pub fn new(x: &[u8]) -> Result<Self, Error> {
+        Self::try_from(x)
+    }
+
+    /// Returns whether the key is a point of small order or the identity.
+    ///
+    /// Signatures for such keys can be forged.  The check compares the encoding against the
+    /// encodings of the eight points of small order, including the non-canonical ones.
+    pub fn is_small_order(&self) -> bool {
+        is_blocked(ED25519_SMALL_ORDER, &self.x)
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl From<[u8; 32]> for Ed25519PublicKey {
+    fn from(x: [u8; 32]) -> Self {
+        Self { x }
+    }
+}
+
+#[cfg(feature = "curve25519-dalek")]
+#[cfg(feature = "ed25519")]
+impl Ed25519PublicKey {
+    /// Converts the key to the X25519 key of the birationally equivalent Montgomery point.
+    ///
+    /// Returns `None` if `x` is not a valid compressed Edwards point.  Requires the `x25519`
+    /// feature.
+    #[cfg(feature = "x25519")]
+    pub fn to_x25519(&self) -> Option<X25519PublicKey> {
+        let u = self.decompress()?.to_montgomery();
+        Some(u.to_bytes().into())
+    }
+
+    /// Returns whether `x` is the canonical encoding of a point on the curve.
+    ///
+    /// This does not check that the point is in the prime-order subgroup, see
+    /// [`is_small_order`][Self::is_small_order] for the points that are usually rejected.
+    pub fn is_valid_point(&self) -> bool {
+        self.decompress()
+            .is_some_and(|point| point.compress().as_bytes() == &self.x)
+    }
+
+    fn decompress(&self) -> Option<curve25519_dalek::EdwardsPoint> {
+        curve25519_dalek::edwards::CompressedEdwardsY(self.x).decompress()
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl TryFrom<&[u8]> for Ed25519PublicKey {
+    type Error = Error;
+
+    fn try_from(x: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            x: array_from_slice(Label::X, x)?,
+        })
+    }
 }
 
-impl From<Ed25519PublicKey> for RawPublicKey {
+#[cfg(feature = "ed25519")]
+impl Ed25519PublicKey {
+    fn fields(&self) -> KeyFields<'_> {
+        KeyFields::of::<Self>(Some(&self.x), None)
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl From<Ed25519PublicKey> for RawCoseKey {
     fn from(key: Ed25519PublicKey) -> Self {
         Self {
             kty: Some(Ed25519PublicKey::KTY),
             alg: Some(Ed25519PublicKey::ALG),
-            crv: Some(Ed25519PublicKey::CRV),
-            x: Some(key.x),
+            key_ops: None,
+            crv: Ed25519PublicKey::CRV,
+            x: Some(bytes_from_array(key.x)),
             y: None,
         }
     }
 }
 
-#[derive(Clone, Debug, Default, Eq, PartialEq, Serialize)]
-#[serde(into = "RawPublicKey")]
+#[cfg(feature = "totp")]
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
 pub struct TotpPublicKey {}
 
-impl PublicKeyConstants for TotpPublicKey {
+#[cfg(feature = "totp")]
+impl CoseKeyType for TotpPublicKey {
     const KTY: Kty = Kty::Symmetric;
     const ALG: Alg = Alg::Totp;
-    const CRV: Crv = Crv::None;
+    const CRV: Option<Crv> = None;
+    const PARAMS: &'static [(i8, usize)] = &[];
+}
+
+#[cfg(feature = "totp")]
+impl TotpPublicKey {
+    /// The maximum length of the canonical CBOR encoding of the key
+    pub const MAX_ENCODED_LEN: usize = <Self as CoseKeyType>::MAX_ENCODED_LEN;
+
+    /// Returns the length of the canonical CBOR encoding of the key.
+    pub fn encoded_len(&self) -> usize {
+        Self::MAX_ENCODED_LEN
+    }
 }
 
-impl From<TotpPublicKey> for RawPublicKey {
+#[cfg(feature = "totp")]
+impl TotpPublicKey {
+    fn fields(&self) -> KeyFields<'_> {
+        KeyFields::of::<Self>(None, None)
+    }
+}
+
+#[cfg(feature = "totp")]
+impl From<TotpPublicKey> for RawCoseKey {
     fn from(_key: TotpPublicKey) -> Self {
         Self {
             kty: Some(TotpPublicKey::KTY),
             alg: Some(TotpPublicKey::ALG),
-            crv: None,
+            key_ops: None,
+            crv: TotpPublicKey::CRV,
             x: None,
             y: None,
         }
     }
 }
 
-#[derive(Clone, Debug, Eq, PartialEq)]
+#[cfg(feature = "x25519")]
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
 pub struct X25519PublicKey {
-    pub pub_key: Bytes<32>,
+    pub pub_key: [u8; X25519_PUBLIC_KEY_SIZE],
+}
+
+#[cfg(feature = "x25519")]
+impl X25519PublicKey {
+    /// Creates a key from its public key, checking that it has the required length.
+    pub fn new(pub_key: &[u8]) -> Result<Self, Error> {
+        Self::try_from(pub_key)
+    }
+
+    /// Returns whether the key is a point of small order or the identity.
+    ///
+    /// A key agreement with such a key results in a shared secret that is known to an attacker,
+    /// e.g. all zeros.  The check compares the u-coordinate against the u-coordinates of the
+    /// points of small order, including the non-canonical ones.
+    pub fn is_small_order(&self) -> bool {
+        is_blocked(X25519_SMALL_ORDER, &self.pub_key)
+    }
+}
+
+// The field prime of P-256 in big-endian, see SEC 2, section 2.4.2
+const P256_PRIME: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+    0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+];
+
+// The encodings of the points of small order on Ed25519, see libsodium.  Both signs of x are
+// covered as the most significant bit is ignored.
+const ED25519_SMALL_ORDER: &[[u8; 32]] = &[
+    // 0 (order 4)
+    [0x00; 32],
+    // 1 (order 1)
+    [
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00,
+    ],
+    // order 8
+    [
+        0x26, 0xe8, 0x95, 0x8f, 0xc2, 0xb2, 0x27, 0xb0, 0x45, 0xc3, 0xf4, 0x89, 0xf2, 0xef, 0x98,
+        0xf0, 0xd5, 0xdf, 0xac, 0x05, 0xd3, 0xc6, 0x33, 0x39, 0xb1, 0x38, 0x02, 0x88, 0x6d, 0x53,
+        0xfc, 0x05,
+    ],
+    // order 8
+    [
+        0xc7, 0x17, 0x6a, 0x70, 0x3d, 0x4d, 0xd8, 0x4f, 0xba, 0x3c, 0x0b, 0x76, 0x0d, 0x10, 0x67,
+        0x0f, 0x2a, 0x20, 0x53, 0xfa, 0x2c, 0x39, 0xcc, 0xc6, 0x4e, 0xc7, 0xfd, 0x77, 0x92, 0xac,
+        0x03, 0x7a,
+    ],
+    // p - 1 (order 2)
+    P_MINUS_ONE,
+    // p, i.e. 0 (order 4)
+    P,
+    // p + 1, i.e. 1 (order 1)
+    P_PLUS_ONE,
+];
+
+// The u-coordinates of the points of small order on Curve25519, see libsodium.  The most
+// significant bit is ignored as specified in RFC 7748.
+const X25519_SMALL_ORDER: &[[u8; 32]] = &[
+    // 0 (order 4)
+    [0x00; 32],
+    // 1 (order 1)
+    [
+        0x01, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        0x00, 0x00,
+    ],
+    // order 8
+    [
+        0xe0, 0xeb, 0x7a, 0x7c, 0x3b, 0x41, 0xb8, 0xae, 0x16, 0x56, 0xe3, 0xfa, 0xf1, 0x9f, 0xc4,
+        0x6a, 0xda, 0x09, 0x8d, 0xeb, 0x9c, 0x32, 0xb1, 0xfd, 0x86, 0x62, 0x05, 0x16, 0x5f, 0x49,
+        0xb8, 0x00,
+    ],
+    // order 8
+    [
+        0x5f, 0x9c, 0x95, 0xbc, 0xa3, 0x50, 0x8c, 0x24, 0xb1, 0xd0, 0xb1, 0x55, 0x9c, 0x83, 0xef,
+        0x5b, 0x04, 0x44, 0x5c, 0xc4, 0x58, 0x1c, 0x8e, 0x86, 0xd8, 0x22, 0x4e, 0xdd, 0xd0, 0x9f,
+        0x11, 0x57,
+    ],
+    // p - 1 (order 2)
+    P_MINUS_ONE,
+    // p, i.e. 0 (order 4)
+    P,
+    // p + 1, i.e. 1 (order 1)
+    P_PLUS_ONE,
+];
+
+// p = 2^255 - 19 and its neighbours in little-endian
+const P_MINUS_ONE: [u8; 32] = field_element(0xec);
+const P: [u8; 32] = field_element(0xed);
+const P_PLUS_ONE: [u8; 32] = field_element(0xee);
+
+const fn field_element(low: u8) -> [u8; 32] {
+    let mut bytes = [0xff; 32];
+    bytes[0] = low;
+    bytes[31] = 0x7f;
+    bytes
+}
+
+// Compares the encoding of a point against the blocklist, ignoring the most significant bit.
+fn is_blocked(blocklist: &[[u8; 32]], point: &[u8]) -> bool {
+    let Some((last, rest)) = point.split_last() else {
+        return false;
+    };
+    blocklist
+        .iter()
+        .any(|blocked| blocked.split_last() == Some((&(last & 0x7f), rest)))
+}
+
+#[cfg(feature = "x25519")]
+impl From<[u8; 32]> for X25519PublicKey {
+    fn from(pub_key: [u8; 32]) -> Self {
+        Self { pub_key }
+    }
+}
+
+#[cfg(feature = "x25519")]
+impl TryFrom<&[u8]> for X25519PublicKey {
+    type Error = Error;
+
+    fn try_from(pub_key: &[u8]) -> Result<Self, Error> {
+        Ok(Self {
+            pub_key: array_from_slice(Label::X, pub_key)?,
+        })
+    }
+}
+
+/// X25519 keys are encoded as OKP keys on X25519 with ECDH-ES+HKDF-256 as the algorithm.
+#[cfg(feature = "x25519")]
+impl CoseKeyType for X25519PublicKey {
+    const KTY: Kty = Kty::Okp;
+    const ALG: Alg = Alg::EcdhEsHkdf256;
+    const CRV: Option<Crv> = Some(Crv::X25519);
+    const PARAMS: &'static [(i8, usize)] = &[(Label::X as i8, X25519_PUBLIC_KEY_SIZE)];
+}
+
+#[cfg(feature = "x25519")]
+impl X25519PublicKey {
+    /// The maximum length of the canonical CBOR encoding of the key
+    pub const MAX_ENCODED_LEN: usize = <Self as CoseKeyType>::MAX_ENCODED_LEN;
+
+    /// Returns the length of the canonical CBOR encoding of the key.
+    pub fn encoded_len(&self) -> usize {
+        Self::MAX_ENCODED_LEN
+    }
+
+    fn fields(&self) -> KeyFields<'_> {
+        KeyFields::of::<Self>(Some(&self.pub_key), None)
+    }
+}
+
+#[cfg(feature = "x25519")]
+impl From<X25519PublicKey> for RawCoseKey {
+    fn from(key: X25519PublicKey) -> Self {
+        Self {
+            kty: Some(X25519PublicKey::KTY),
+            alg: Some(X25519PublicKey::ALG),
+            key_ops: None,
+            crv: X25519PublicKey::CRV,
+            x: Some(bytes_from_array(key.pub_key)),
+            y: None,
+        }
+    }
+}
+
+// The length of the CBOR head with the given argument
+const fn head_len(argument: u64) -> usize {
+    match argument {
+        0..=23 => 1,
+        24..=0xff => 2,
+        0x100..=0xffff => 3,
+        0x1_0000..=0xffff_ffff => 5,
+        _ => 9,
+    }
+}
+
+// The length of the CBOR encoding of an integer
+pub(crate) const fn int_len(value: i64) -> usize {
+    if value < 0 {
+        head_len(!value as u64)
+    } else {
+        head_len(value as u64)
+    }
+}
+
+// The length of the CBOR encoding of a byte string with the given length
+pub(crate) const fn bytes_len(len: usize) -> usize {
+    head_len(len as u64) + len
+}
+
+// The length of the encoding of a key without the byte string parameters
+const fn fixed_len<K: CoseKeyType + ?Sized>() -> usize {
+    let mut len = 1 + 1 + int_len(K::KTY.to_i32() as i64) + 1 + int_len(K::ALG.to_i32() as i64);
+    if let Some(crv) = K::CRV {
+        len += 1 + int_len(crv.to_i32() as i64);
+    }
+    len
+}
+
+// The length of the encoding of a key of type `K`
+const fn max_encoded_len<K: CoseKeyType + ?Sized>() -> usize {
+    let mut len = fixed_len::<K>();
+    let mut params = K::PARAMS;
+    while let [(label, param_len), rest @ ..] = params {
+        len += int_len(*label as i64) + bytes_len(*param_len);
+        params = rest;
+    }
+    len
+}
+
+const fn max(a: usize, b: usize) -> usize {
+    if a > b {
+        a
+    } else {
+        b
+    }
+}
+
+#[cfg(any(
+    feature = "ecdh",
+    feature = "ed25519",
+    feature = "p256",
+    feature = "x25519"
+))]
+fn array_from_slice<const N: usize>(label: Label, slice: &[u8]) -> Result<[u8; N], Error> {
+    slice.try_into().map_err(|_| Error::InvalidLength {
+        label: label as i8,
+        expected: N,
+        actual: slice.len(),
+    })
+}
+
+fn bytes_from_array<const N: usize>(array: [u8; N]) -> Bytes<N> {
+    let mut bytes = Bytes::new();
+    bytes.resize_to_capacity();
+    bytes.copy_from_slice(&array);
+    bytes
 }
 
-fn check_key_constants<K: PublicKeyConstants, E: serde::de::Error>(
+fn left_padded<const N: usize>(bytes: &Bytes<N>) -> [u8; N] {
+    let mut array = [0; N];
+    array[N - bytes.len()..].copy_from_slice(bytes);
+    array
+}
+
+// Checks that a required byte string is present and has the expected length.
+#[cfg(any(
+    feature = "ecdh",
+    feature = "ed25519",
+    feature = "p256",
+    feature = "x25519"
+))]
+fn check_length<const N: usize>(label: Label, bytes: Option<Bytes<N>>) -> Result<[u8; N], Error> {
+    let bytes = bytes.ok_or(Error::MissingField(label.name()))?;
+    array_from_slice(label, &bytes)
+}
+
+// Converts a raw key with `from_raw` and, if `ParseOptions::enforce_key_ops` is set, checks that
+// its `key_ops` permit the use of the converted key.
+fn from_raw_enforced<K: Clone + Into<PublicKey>>(
+    mut raw: RawCoseKey,
+    options: ParseOptions,
+    from_raw: impl FnOnce(RawCoseKey) -> Result<K, Error>,
+) -> Result<K, Error> {
+    // the key types do not keep `key_ops`
+    let key_ops = raw.key_ops.take().filter(|_| options.enforce_key_ops);
+    let key = from_raw(raw)?;
+    if let Some(key_ops) = key_ops {
+        key_ops::check_use(Some(&key_ops), key.clone().into().key_use())?;
+    }
+    Ok(key)
+}
+
+fn check_key_constants<K: CoseKeyType>(
     kty: Option<Kty>,
     alg: Option<Alg>,
     crv: Option<Crv>,
-) -> Result<(), E> {
-    let kty = kty.ok_or_else(|| E::missing_field("kty"))?;
+) -> Result<(), Error> {
+    let kty = kty.ok_or(Error::MissingField("kty"))?;
     if kty != K::KTY {
-        return Err(E::invalid_value(Unexpected::Signed(kty as _), &K::KTY));
+        return Err(Error::WrongKty {
+            expected: K::KTY,
+            actual: kty,
+        });
     }
     if let Some(alg) = alg {
         if alg != K::ALG {
-            return Err(E::invalid_value(Unexpected::Signed(alg as _), &K::ALG));
+            return Err(Error::WrongAlg {
+                expected: K::ALG,
+                actual: alg,
+            });
         }
     }
-    if K::CRV != Crv::None {
-        let crv = crv.ok_or_else(|| E::missing_field("crv"))?;
-        if crv != K::CRV {
-            return Err(E::invalid_value(Unexpected::Signed(crv as _), &K::CRV));
+    if let Some(expected) = K::CRV {
+        let crv = crv.ok_or(Error::MissingField("crv"))?;
+        if crv != expected {
+            return Err(Error::WrongCrv {
+                expected,
+                actual: crv,
+            });
         }
     }
     Ok(())
 }
 
-impl<'de> serde::Deserialize<'de> for P256PublicKey {
-    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
-    where
-        D: serde::Deserializer<'de>,
-    {
-        let RawPublicKey {
+#[cfg(feature = "p256")]
+impl TryFrom<RawCoseKey> for P256PublicKey {
+    type Error = Error;
+
+    fn try_from(raw: RawCoseKey) -> Result<Self, Error> {
+        Self::from_raw(raw)
+    }
+}
+
+#[cfg(feature = "p256")]
+impl P256PublicKey {
+    fn from_raw(raw: RawCoseKey) -> Result<Self, Error> {
+        let RawCoseKey {
             kty,
             alg,
             crv,
             x,
             y,
-        } = RawPublicKey::deserialize(deserializer)?;
-        check_key_constants::<P256PublicKey, D::Error>(kty, alg, crv)?;
-        let x = x.ok_or_else(|| D::Error::missing_field("x"))?;
-        let y = y.ok_or_else(|| D::Error::missing_field("y"))?;
+            ..
+        } = raw;
+        check_key_constants::<P256PublicKey>(kty, alg, crv)?;
+        let x = check_length(Label::X, x)?;
+        let y = check_length(Label::Y, y)?;
         Ok(Self { x, y })
     }
 }
 
-impl<'de> serde::Deserialize<'de> for EcdhEsHkdf256PublicKey {
+#[cfg(all(feature = "p256", feature = "serde"))]
+impl P256PublicKey {
+    /// Deserializes a key using the given options.
+    pub fn deserialize_with<'de, D>(
+        deserializer: D,
+        options: ParseOptions,
+    ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        RawCoseKey::try_deserialize_with(deserializer, options)?
+            .and_then(|raw| from_raw_enforced(raw, options, Self::from_raw))
+            .map_err(Error::into_de_error)
+    }
+}
+
+#[cfg(all(feature = "p256", feature = "serde"))]
+impl<'de> serde::Deserialize<'de> for P256PublicKey {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let RawPublicKey {
+        Self::deserialize_with(deserializer, ParseOptions::default())
+    }
+}
+
+#[cfg(feature = "ecdh")]
+impl TryFrom<RawCoseKey> for EcdhEsHkdf256PublicKey {
+    type Error = Error;
+
+    fn try_from(raw: RawCoseKey) -> Result<Self, Error> {
+        Self::from_raw(raw)
+    }
+}
+
+#[cfg(feature = "ecdh")]
+impl EcdhEsHkdf256PublicKey {
+    fn from_raw(raw: RawCoseKey) -> Result<Self, Error> {
+        let RawCoseKey {
             kty,
             alg,
             crv,
             x,
             y,
-        } = RawPublicKey::deserialize(deserializer)?;
-        check_key_constants::<EcdhEsHkdf256PublicKey, D::Error>(kty, alg, crv)?;
-        let x = x.ok_or_else(|| D::Error::missing_field("x"))?;
-        let y = y.ok_or_else(|| D::Error::missing_field("y"))?;
+            ..
+        } = raw;
+        check_key_constants::<EcdhEsHkdf256PublicKey>(kty, alg, crv)?;
+        let x = check_length(Label::X, x)?;
+        let y = check_length(Label::Y, y)?;
         Ok(Self { x, y })
     }
 }
 
-impl<'de> serde::Deserialize<'de> for Ed25519PublicKey {
+#[cfg(all(feature = "ecdh", feature = "serde"))]
+impl EcdhEsHkdf256PublicKey {
+    /// Deserializes a key using the given options.
+    pub fn deserialize_with<'de, D>(
+        deserializer: D,
+        options: ParseOptions,
+    ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        RawCoseKey::try_deserialize_with(deserializer, options)?
+            .and_then(|raw| from_raw_enforced(raw, options, Self::from_raw))
+            .map_err(Error::into_de_error)
+    }
+}
+
+#[cfg(all(feature = "ecdh", feature = "serde"))]
+impl<'de> serde::Deserialize<'de> for EcdhEsHkdf256PublicKey {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: serde::Deserializer<'de>,
     {
-        let RawPublicKey {
+        Self::deserialize_with(deserializer, ParseOptions::default())
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl TryFrom<RawCoseKey> for Ed25519PublicKey {
+    type Error = Error;
+
+    fn try_from(raw: RawCoseKey) -> Result<Self, Error> {
+        Self::from_raw(raw)
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl Ed25519PublicKey {
+    fn from_raw(raw: RawCoseKey) -> Result<Self, Error> {
+        let RawCoseKey {
             kty, alg, crv, x, ..
-        } = RawPublicKey::deserialize(deserializer)?;
-        check_key_constants::<Ed25519PublicKey, D::Error>(kty, alg, crv)?;
-        let x = x.ok_or_else(|| D::Error::missing_field("x"))?;
+        } = raw;
+        check_key_constants::<Ed25519PublicKey>(kty, alg, crv)?;
+        let x = check_length(Label::X, x)?;
         Ok(Self { x })
     }
 }
+
+#[cfg(all(feature = "ed25519", feature = "serde"))]
+impl Ed25519PublicKey {
+    /// Deserializes a key using the given options.
+    pub fn deserialize_with<'de, D>(
+        deserializer: D,
+        options: ParseOptions,
+    ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        RawCoseKey::try_deserialize_with(deserializer, options)?
+            .and_then(|raw| from_raw_enforced(raw, options, Self::from_raw))
+            .map_err(Error::into_de_error)
+    }
+}
+
+#[cfg(all(feature = "ed25519", feature = "serde"))]
+impl<'de> serde::Deserialize<'de> for Ed25519PublicKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Self::deserialize_with(deserializer, ParseOptions::default())
+    }
+}
+
+#[cfg(feature = "totp")]
+impl TryFrom<RawCoseKey> for TotpPublicKey {
+    type Error = Error;
+
+    fn try_from(raw: RawCoseKey) -> Result<Self, Error> {
+        Self::from_raw(raw)
+    }
+}
+
+#[cfg(feature = "totp")]
+impl TotpPublicKey {
+    fn from_raw(raw: RawCoseKey) -> Result<Self, Error> {
+        let RawCoseKey { kty, alg, crv, .. } = raw;
+        check_key_constants::<TotpPublicKey>(kty, alg, crv)?;
+        Ok(Self {})
+    }
+}
+
+#[cfg(feature = "x25519")]
+impl TryFrom<RawCoseKey> for X25519PublicKey {
+    type Error = Error;
+
+    fn try_from(raw: RawCoseKey) -> Result<Self, Error> {
+        Self::from_raw(raw)
+    }
+}
+
+#[cfg(feature = "x25519")]
+impl X25519PublicKey {
+    fn from_raw(raw: RawCoseKey) -> Result<Self, Error> {
+        let RawCoseKey {
+            kty, alg, crv, x, ..
+        } = raw;
+        check_key_constants::<X25519PublicKey>(kty, alg, crv)?;
+        let pub_key = check_length(Label::X, x)?;
+        Ok(Self { pub_key })
+    }
+
+    /// Deserializes a key using the given options.
+    #[cfg(feature = "serde")]
+    pub fn deserialize_with<'de, D>(
+        deserializer: D,
+        options: ParseOptions,
+    ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        RawCoseKey::try_deserialize_with(deserializer, options)?
+            .and_then(|raw| from_raw_enforced(raw, options, Self::from_raw))
+            .map_err(Error::into_de_error)
+    }
+}
+
+#[cfg(all(feature = "x25519", feature = "serde"))]
+impl<'de> serde::Deserialize<'de> for X25519PublicKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Self::deserialize_with(deserializer, ParseOptions::default())
+    }
+}
+
+impl PublicKey {
+    /// The maximum length of the canonical CBOR encoding of any variant
+    pub const MAX_ENCODED_LEN: usize = {
+        let len = 0;
+        #[cfg(feature = "p256")]
+        let len = max(len, P256PublicKey::MAX_ENCODED_LEN);
+        #[cfg(feature = "ecdh")]
+        let len = max(len, EcdhEsHkdf256PublicKey::MAX_ENCODED_LEN);
+        #[cfg(feature = "ed25519")]
+        let len = max(len, Ed25519PublicKey::MAX_ENCODED_LEN);
+        #[cfg(feature = "totp")]
+        let len = max(len, TotpPublicKey::MAX_ENCODED_LEN);
+        #[cfg(feature = "x25519")]
+        let len = max(len, X25519PublicKey::MAX_ENCODED_LEN);
+        len
+    };
+
+    /// Returns the length of the canonical CBOR encoding of the key.
+    pub fn encoded_len(&self) -> usize {
+        match self {
+            #[cfg(feature = "p256")]
+            Self::P256Key(key) => key.encoded_len(),
+            #[cfg(feature = "ecdh")]
+            Self::EcdhEsHkdf256Key(key) => key.encoded_len(),
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519Key(key) => key.encoded_len(),
+            #[cfg(feature = "totp")]
+            Self::TotpKey(key) => key.encoded_len(),
+            #[cfg(feature = "x25519")]
+            Self::X25519Key(key) => key.encoded_len(),
+        }
+    }
+
+    /// Deserializes a key using the given options.
+    #[cfg(feature = "serde")]
+    pub fn deserialize_with<'de, D>(
+        deserializer: D,
+        options: ParseOptions,
+    ) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        RawCoseKey::try_deserialize_with(deserializer, options)?
+            .and_then(|raw| Self::from_raw(raw, options))
+            .map_err(Error::into_de_error)
+    }
+
+    /// Converts a raw key into the matching variant.
+    ///
+    /// The options select the variant for EC2 keys without `alg`, see
+    /// [`ParseOptions::ec2_without_alg`], and whether `key_ops` is enforced, see
+    /// [`ParseOptions::enforce_key_ops`].
+    pub fn from_raw(raw: RawCoseKey, options: ParseOptions) -> Result<Self, Error> {
+        from_raw_enforced(raw, options, |raw| Self::select(raw, options))
+    }
+
+    fn select(raw: RawCoseKey, options: ParseOptions) -> Result<Self, Error> {
+        fn unsupported(label: Label, value: i32) -> Error {
+            Error::UnsupportedValue {
+                label: label as i8,
+                value: value.into(),
+            }
+        }
+
+        // only the algorithms of the enabled key types are supported
+        match raw.alg {
+            #[cfg(feature = "p256")]
+            Some(Alg::Es256) => {}
+            #[cfg(feature = "ed25519")]
+            Some(Alg::EdDsa) => {}
+            #[cfg(feature = "totp")]
+            Some(Alg::Totp) => {}
+            #[cfg(any(feature = "ecdh", feature = "x25519"))]
+            Some(Alg::EcdhEsHkdf256) => {}
+            None => {}
+            Some(alg) => return Err(unsupported(Label::Alg, alg.to_i32())),
+        }
+
+        match (raw.kty, raw.alg) {
+            #[cfg(feature = "ecdh")]
+            (Some(Kty::Ec2), Some(Alg::EcdhEsHkdf256)) => {
+                EcdhEsHkdf256PublicKey::from_raw(raw).map(Self::from)
+            }
+            (Some(Kty::Ec2), None) => match options.ec2_without_alg {
+                #[cfg(feature = "p256")]
+                Ec2Variant::P256 => P256PublicKey::from_raw(raw).map(Self::from),
+                #[cfg(feature = "ecdh")]
+                Ec2Variant::EcdhEsHkdf256 => EcdhEsHkdf256PublicKey::from_raw(raw).map(Self::from),
+                Ec2Variant::Reject => Err(Error::MissingField("alg")),
+                // the key type selected by the options is not enabled
+                #[allow(unreachable_patterns)]
+                _ => Err(unsupported(Label::Kty, Kty::Ec2.to_i32())),
+            },
+            #[cfg(feature = "p256")]
+            (Some(Kty::Ec2), _) => P256PublicKey::from_raw(raw).map(Self::from),
+            #[cfg(feature = "x25519")]
+            (Some(Kty::Okp), Some(Alg::EcdhEsHkdf256)) => {
+                X25519PublicKey::from_raw(raw).map(Self::from)
+            }
+            #[cfg(feature = "x25519")]
+            (Some(Kty::Okp), None) if raw.crv == Some(Crv::X25519) => {
+                X25519PublicKey::from_raw(raw).map(Self::from)
+            }
+            #[cfg(feature = "ed25519")]
+            (Some(Kty::Okp), _) => Ed25519PublicKey::from_raw(raw).map(Self::from),
+            #[cfg(all(feature = "x25519", not(feature = "ed25519")))]
+            (Some(Kty::Okp), _) => X25519PublicKey::from_raw(raw).map(Self::from),
+            #[cfg(feature = "totp")]
+            (Some(Kty::Symmetric), _) => TotpPublicKey::from_raw(raw).map(Self::from),
+            (Some(kty), _) => Err(unsupported(Label::Kty, kty.to_i32())),
+            (None, _) => Err(Error::MissingField("kty")),
+        }
+    }
+
+    /// Returns the algorithm of the key.
+    pub fn alg(&self) -> Alg {
+        match self {
+            #[cfg(feature = "p256")]
+            Self::P256Key(_) => P256PublicKey::ALG,
+            #[cfg(feature = "ecdh")]
+            Self::EcdhEsHkdf256Key(_) => EcdhEsHkdf256PublicKey::ALG,
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519Key(_) => Ed25519PublicKey::ALG,
+            #[cfg(feature = "totp")]
+            Self::TotpKey(_) => TotpPublicKey::ALG,
+            #[cfg(feature = "x25519")]
+            Self::X25519Key(_) => X25519PublicKey::ALG,
+        }
+    }
+
+    /// Returns whether both keys have the same key material, ignoring the algorithm.
+    ///
+    /// For example, a P-256 key for ES256 is the same key as an ECDH-ES+HKDF-256 key with the same
+    /// point.
+    pub fn same_key(&self, other: &PublicKey) -> bool {
+        let fields = self.fields();
+        let other = other.fields();
+        (fields.kty, fields.crv, fields.x, fields.y) == (other.kty, other.crv, other.x, other.y)
+    }
+
+    pub(crate) fn fields(&self) -> KeyFields<'_> {
+        match self {
+            #[cfg(feature = "p256")]
+            Self::P256Key(key) => key.fields(),
+            #[cfg(feature = "ecdh")]
+            Self::EcdhEsHkdf256Key(key) => key.fields(),
+            #[cfg(feature = "ed25519")]
+            Self::Ed25519Key(key) => key.fields(),
+            #[cfg(feature = "totp")]
+            Self::TotpKey(key) => key.fields(),
+            #[cfg(feature = "x25519")]
+            Self::X25519Key(key) => key.fields(),
+        }
+    }
+
+    /// Returns whether the key can be used with the given algorithm.
+    ///
+    /// As required by RFC 8152, Section 7, a key can only be used with the algorithm it is
+    /// restricted to, e.g. an ES256 key can not be used for ECDH-ES.
+    pub fn supports_alg(&self, alg: i32) -> bool {
+        self.alg().to_i32() == alg
+    }
+
+    /// Returns what the key is used for, based on its algorithm.
+    ///
+    /// TOTP keys are used for MACs, although their algorithm is registered as ESP256, see
+    /// [`Alg::Totp`].
+    pub fn key_use(&self) -> KeyUse {
+        match self {
+            #[cfg(feature = "totp")]
+            Self::TotpKey(_) => KeyUse::Mac,
+            #[allow(unreachable_patterns)]
+            _ => self.alg().key_use(),
+        }
+    }
+
+    /// Returns the P-256 key if this is a [`PublicKey::P256Key`].
+    #[cfg(feature = "p256")]
+    pub fn as_p256(&self) -> Option<&P256PublicKey> {
+        match self {
+            Self::P256Key(key) => Some(key),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// Returns the ECDH-ES+HKDF-256 key if this is a [`PublicKey::EcdhEsHkdf256Key`].
+    #[cfg(feature = "ecdh")]
+    pub fn as_ecdh_es_hkdf256(&self) -> Option<&EcdhEsHkdf256PublicKey> {
+        match self {
+            Self::EcdhEsHkdf256Key(key) => Some(key),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// Returns the Ed25519 key if this is a [`PublicKey::Ed25519Key`].
+    #[cfg(feature = "ed25519")]
+    pub fn as_ed25519(&self) -> Option<&Ed25519PublicKey> {
+        match self {
+            Self::Ed25519Key(key) => Some(key),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// Returns the TOTP key if this is a [`PublicKey::TotpKey`].
+    #[cfg(feature = "totp")]
+    pub fn as_totp(&self) -> Option<&TotpPublicKey> {
+        match self {
+            Self::TotpKey(key) => Some(key),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+
+    /// Returns the X25519 key if this is a [`PublicKey::X25519Key`].
+    #[cfg(feature = "x25519")]
+    pub fn as_x25519(&self) -> Option<&X25519PublicKey> {
+        match self {
+            Self::X25519Key(key) => Some(key),
+            #[allow(unreachable_patterns)]
+            _ => None,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for PublicKey {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Self::deserialize_with(deserializer, ParseOptions::default())
+    }
+}
+
+impl TryFrom<RawCoseKey> for PublicKey {
+    type Error = Error;
+
+    fn try_from(raw: RawCoseKey) -> Result<Self, Error> {
+        Self::from_raw(raw, ParseOptions::default())
+    }
+}
+
+impl From<PublicKey> for RawCoseKey {
+    fn from(key: PublicKey) -> Self {
+        match key {
+            #[cfg(feature = "p256")]
+            PublicKey::P256Key(key) => key.into(),
+            #[cfg(feature = "ecdh")]
+            PublicKey::EcdhEsHkdf256Key(key) => key.into(),
+            #[cfg(feature = "ed25519")]
+            PublicKey::Ed25519Key(key) => key.into(),
+            #[cfg(feature = "totp")]
+            PublicKey::TotpKey(key) => key.into(),
+            #[cfg(feature = "x25519")]
+            PublicKey::X25519Key(key) => key.into(),
+        }
+    }
+}
@@ -0,0 +1,137 @@
+//! Deserialization of CBOR maps with integer labels
+//!
+//! The COSE_Key types and the other map types of this crate apply the same policy to the fields of
+//! a map: duplicates are always rejected, unknown fields are skipped unless
+//! [`deny_unknown_fields`][ParseOptions::deny_unknown_fields] is set, and the known fields must be
+//! in canonical order unless [`any_order`][ParseOptions::any_order] is set.  [`SeenFields`]
+//! implements this policy and [`visit_entries`] reads the entries of a map with it.
+
+#[cfg(feature = "serde")]
+use serde::de::{IgnoredAny, MapAccess};
+
+#[cfg(feature = "serde")]
+use crate::KeySeed;
+use crate::{Error, Key, ParseOptions};
+
+// The known fields of a map that have been read, tracked by their index in the canonical order.
+// Maps have at most 64 known fields.
+pub(crate) struct SeenFields {
+    options: ParseOptions,
+    seen: u64,
+    previous: Option<usize>,
+}
+
+impl SeenFields {
+    pub(crate) fn new(options: ParseOptions) -> Self {
+        Self {
+            options,
+            seen: 0,
+            previous: None,
+        }
+    }
+
+    // Checks the next field of the map, given by its index and name or the error for an unknown
+    // field.  Returns the index of the field whose value should be read next, or `None` if the
+    // value should be skipped.
+    pub(crate) fn check(
+        &mut self,
+        field: Result<(usize, &'static str), Error>,
+    ) -> Result<Option<usize>, Error> {
+        // Unknown fields may appear in any position and are not considered for the canonical
+        // order.  Duplicate unknown fields are not detected as their values are ignored.
+        let (index, name) = match field {
+            Ok(field) => field,
+            Err(err) if self.options.deny_unknown_fields => return Err(err),
+            Err(_) => return Ok(None),
+        };
+        let bit = Self::bit(index).ok_or(Error::InvalidKey)?;
+        // Duplicates are always rejected, and take precedence over order errors.
+        if self.seen & bit != 0 {
+            return Err(Error::DuplicateField(name));
+        }
+        if !self.options.any_order && self.previous.is_some_and(|previous| previous > index) {
+            return Err(Error::NonCanonicalOrder);
+        }
+        self.seen |= bit;
+        self.previous = Some(index);
+        Ok(Some(index))
+    }
+
+    // Returns whether the field with the given index has been read.
+    #[cfg(feature = "serde")]
+    pub(crate) fn contains(&self, index: usize) -> bool {
+        Self::bit(index).is_some_and(|bit| self.seen & bit != 0)
+    }
+
+    fn bit(index: usize) -> Option<u64> {
+        1u64.checked_shl(u32::try_from(index).ok()?)
+    }
+}
+
+// Returns the index and name of the field with the given key in a map whose known fields have
+// the given labels and names, in canonical order.
+#[cfg(feature = "serde")]
+pub(crate) fn find_field(
+    labels: &[i8],
+    names: &[&'static str],
+    key: Key,
+) -> Result<(usize, &'static str), Error> {
+    let key = match key {
+        Key::Label(key) => key,
+        Key::UnknownText => return Err(Error::UnknownTextField),
+    };
+    labels
+        .iter()
+        .zip(names)
+        .position(|(label, _)| i64::from(*label) == key)
+        .and_then(|index| Some((index, *names.get(index)?)))
+        .ok_or(Error::UnknownField(key))
+}
+
+// The fields of a map that is read with `visit_entries`
+pub(crate) trait MapFields<'de> {
+    type Field;
+
+    // Returns the field whose value should be read next, or `None` if the value should be
+    // skipped, usually with `SeenFields::check`.
+    fn key(&mut self, key: Key) -> Result<Option<Self::Field>, Error>;
+
+    // Reads the value of the given field.
+    #[cfg(feature = "serde")]
+    fn read_value<V: MapAccess<'de>>(
+        &mut self,
+        field: Self::Field,
+        map: &mut V,
+    ) -> Result<Result<(), Error>, V::Error>;
+}
+
+// Reads the entries of a map into `fields`, returning errors in the structure of the map
+// separately from errors of the deserializer so that they are not reduced to a message.
+//
+// Skipped values are still consumed as some deserializers reject maps that are not read
+// completely.  For the same reason, the rest of the map is skipped if an entry is rejected.  We
+// do not rely on the size hint, so indefinite-length maps are supported if the deserializer
+// supports them (ciborium and serde_cbor do, cbor-smol does not).
+#[cfg(feature = "serde")]
+pub(crate) fn visit_entries<'de, F, V>(
+    fields: &mut F,
+    map: &mut V,
+    seed: KeySeed,
+) -> Result<Result<(), Error>, V::Error>
+where
+    F: MapFields<'de>,
+    V: MapAccess<'de>,
+{
+    while let Some(key) = map.next_key_seed(seed)? {
+        let result = match fields.key(key) {
+            Ok(Some(field)) => fields.read_value(field, map)?,
+            Ok(None) => map.next_value::<IgnoredAny>().map(|_| Ok(()))?,
+            Err(err) => map.next_value::<IgnoredAny>().map(|_| Err(err))?,
+        };
+        if let Err(err) = result {
+            while map.next_entry::<IgnoredAny, IgnoredAny>()?.is_some() {}
+            return Ok(Err(err));
+        }
+    }
+    Ok(Ok(()))
+}
@@ -0,0 +1,250 @@
+//! Public keys with operational metadata
+//!
+//! [`KeyWithMetadata`] pairs a [`PublicKey`] with the metadata that key stores need for key
+//! rotation: a key ID, a validity period and a usage counter.  It is encoded as a CBOR map with
+//! the integer labels in [`label`] and the key as a COSE_Key.  Times are given in seconds since
+//! the Unix epoch, like the `nbf` and `exp` claims of CWTs.
+
+#[cfg(feature = "serde")]
+use core::fmt::{self, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::de::{DeserializeSeed, MapAccess, Visitor};
+#[cfg(feature = "serde")]
+use serde::ser::SerializeMap as _;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::custom::{ByteStr, LabelKey};
+#[cfg(feature = "serde")]
+use crate::map::{self, MapFields, SeenFields};
+use crate::{Bytes, PublicKey};
+#[cfg(feature = "serde")]
+use crate::{BytesSeed, Error, Key, KeySeed, ParseOptions};
+
+/// The maximum length of the key ID of [`KeyWithMetadata`]
+pub const MAX_KID_LEN: usize = 32;
+
+/// The labels of the map
+pub mod label {
+    /// The key
+    pub const KEY: i8 = 1;
+    /// The key ID
+    pub const KID: i8 = 2;
+    /// The start of the validity period
+    pub const NOT_BEFORE: i8 = 3;
+    /// The end of the validity period
+    pub const NOT_AFTER: i8 = 4;
+    /// The usage counter
+    pub const USE_COUNT: i8 = 5;
+}
+
+#[cfg(feature = "serde")]
+const LABELS: [i8; 5] = [
+    label::KEY,
+    label::KID,
+    label::NOT_BEFORE,
+    label::NOT_AFTER,
+    label::USE_COUNT,
+];
+
+#[cfg(feature = "serde")]
+const NAMES: [&str; 5] = ["key", "kid", "not_before", "not_after", "use_count"];
+
+/// A public key with a key ID, a validity period and a usage counter
+///
+/// Only the key is required.  The validity period includes both `not_before` and `not_after`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct KeyWithMetadata {
+    /// The key
+    pub key: PublicKey,
+    /// The key ID
+    pub kid: Option<Bytes<MAX_KID_LEN>>,
+    /// The time from which the key is valid
+    pub not_before: Option<u64>,
+    /// The time until which the key is valid
+    pub not_after: Option<u64>,
+    /// The number of times the key has been used
+    pub use_count: u64,
+}
+
+impl KeyWithMetadata {
+    /// Creates an entry for the given key without metadata.
+    pub fn new(key: PublicKey) -> Self {
+        Self {
+            key,
+            kid: None,
+            not_before: None,
+            not_after: None,
+            use_count: 0,
+        }
+    }
+
+    /// Returns whether the key is valid at the given time.
+    pub fn is_valid_at(&self, time: u64) -> bool {
+        self.not_before
+            .map_or(true, |not_before| not_before <= time)
+            && self.not_after.map_or(true, |not_after| time <= not_after)
+    }
+
+    /// Increments the usage counter, saturating at `u64::MAX`.
+    pub fn record_use(&mut self) {
+        self.use_count = self.use_count.saturating_add(1);
+    }
+
+    /// Deserializes an entry using the given options.
+    ///
+    /// [`any_order`][ParseOptions::any_order],
+    /// [`deny_unknown_fields`][ParseOptions::deny_unknown_fields] and
+    /// [`human_readable`][ParseOptions::human_readable] apply to the entry.  The key is
+    /// deserialized with the default options, except for `human_readable`.
+    #[cfg(feature = "serde")]
+    pub fn deserialize_with<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        options: ParseOptions,
+    ) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_map(MetadataVisitor {
+                fields: Fields::default(),
+                seen: SeenFields::new(options),
+                human_readable: options.human_readable,
+            })?
+            .map_err(Error::into_de_error)
+    }
+}
+
+impl From<PublicKey> for KeyWithMetadata {
+    fn from(key: PublicKey) -> Self {
+        Self::new(key)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for KeyWithMetadata {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields = 2
+            + usize::from(self.kid.is_some())
+            + usize::from(self.not_before.is_some())
+            + usize::from(self.not_after.is_some());
+        let mut map = serializer.serialize_map(Some(fields))?;
+        map.serialize_entry(&LabelKey(label::KEY), &self.key)?;
+        if let Some(kid) = &self.kid {
+            map.serialize_entry(&LabelKey(label::KID), &ByteStr(kid))?;
+        }
+        if let Some(not_before) = &self.not_before {
+            map.serialize_entry(&LabelKey(label::NOT_BEFORE), not_before)?;
+        }
+        if let Some(not_after) = &self.not_after {
+            map.serialize_entry(&LabelKey(label::NOT_AFTER), not_after)?;
+        }
+        map.serialize_entry(&LabelKey(label::USE_COUNT), &self.use_count)?;
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for KeyWithMetadata {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::deserialize_with(deserializer, ParseOptions::default())
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Default)]
+struct Fields {
+    key: Option<PublicKey>,
+    kid: Option<Bytes<MAX_KID_LEN>>,
+    not_before: Option<u64>,
+    not_after: Option<u64>,
+    use_count: Option<u64>,
+}
+
+#[cfg(feature = "serde")]
+struct PublicKeySeed(ParseOptions);
+
+#[cfg(feature = "serde")]
+impl<'de> DeserializeSeed<'de> for PublicKeySeed {
+    type Value = PublicKey;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<PublicKey, D::Error> {
+        PublicKey::deserialize_with(deserializer, self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+struct MetadataVisitor {
+    fields: Fields,
+    seen: SeenFields,
+    human_readable: bool,
+}
+
+#[cfg(feature = "serde")]
+impl<'de> MapFields<'de> for MetadataVisitor {
+    type Field = usize;
+
+    fn key(&mut self, key: Key) -> Result<Option<usize>, Error> {
+        self.seen.check(map::find_field(&LABELS, &NAMES, key))
+    }
+
+    fn read_value<V: MapAccess<'de>>(
+        &mut self,
+        index: usize,
+        map: &mut V,
+    ) -> Result<Result<(), Error>, V::Error> {
+        let fields = &mut self.fields;
+        let Some(&label) = LABELS.get(index) else {
+            return Ok(Err(Error::InvalidKey));
+        };
+        match label {
+            label::KEY => {
+                let options = ParseOptions::new().human_readable(self.human_readable);
+                fields.key = Some(map.next_value_seed(PublicKeySeed(options))?);
+            }
+            label::KID => {
+                let seed = BytesSeed::<MAX_KID_LEN> {
+                    label,
+                    human_readable: self.human_readable,
+                };
+                match map.next_value_seed(seed)? {
+                    Ok(kid) => fields.kid = Some(kid),
+                    Err(err) => return Ok(Err(err)),
+                }
+            }
+            label::NOT_BEFORE => fields.not_before = Some(map.next_value()?),
+            label::NOT_AFTER => fields.not_after = Some(map.next_value()?),
+            _ => fields.use_count = Some(map.next_value()?),
+        }
+        Ok(Ok(()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for MetadataVisitor {
+    type Value = Result<KeyWithMetadata, Error>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("KeyWithMetadata")
+    }
+
+    fn visit_map<V: MapAccess<'de>>(mut self, mut map: V) -> Result<Self::Value, V::Error> {
+        let seed = KeySeed {
+            text_labels: false,
+            human_readable: self.human_readable,
+        };
+        if let Err(err) = map::visit_entries(&mut self, &mut map, seed)? {
+            return Ok(Err(err));
+        }
+        let fields = self.fields;
+        let Some(key) = fields.key else {
+            return Ok(Err(Error::MissingField("key")));
+        };
+        Ok(Ok(KeyWithMetadata {
+            key,
+            kid: fields.kid,
+            not_before: fields.not_before,
+            not_after: fields.not_after,
+            use_count: fields.use_count.unwrap_or_default(),
+        }))
+    }
+}
@@ -0,0 +1,173 @@
+//! Mapping between COSE values and X.509 `AlgorithmIdentifier`s
+//!
+//! Object identifiers are given as the contents octets of their DER encoding, i.e. without the
+//! `OBJECT IDENTIFIER` tag and length.  This is the form in which they appear when walking a
+//! certificate with a DER parser.
+//!
+//! https://www.rfc-editor.org/rfc/rfc4055#section-5
+//! https://www.rfc-editor.org/rfc/rfc5480#section-2.1.1
+//! https://www.rfc-editor.org/rfc/rfc5758#section-3.2
+//! https://www.rfc-editor.org/rfc/rfc8410#section-3
+
+use crate::{Alg, Crv, Kty};
+
+/// id-ecPublicKey (1.2.840.10045.2.1)
+pub const ID_EC_PUBLIC_KEY: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x02, 0x01];
+/// secp256r1 (1.2.840.10045.3.1.7)
+pub const SECP256R1: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x03, 0x01, 0x07];
+/// secp384r1 (1.3.132.0.34)
+pub const SECP384R1: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x22];
+/// secp521r1 (1.3.132.0.35)
+pub const SECP521R1: &[u8] = &[0x2b, 0x81, 0x04, 0x00, 0x23];
+/// ecdsa-with-SHA256 (1.2.840.10045.4.3.2)
+pub const ECDSA_WITH_SHA256: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x02];
+/// ecdsa-with-SHA384 (1.2.840.10045.4.3.3)
+pub const ECDSA_WITH_SHA384: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x03];
+/// ecdsa-with-SHA512 (1.2.840.10045.4.3.4)
+pub const ECDSA_WITH_SHA512: &[u8] = &[0x2a, 0x86, 0x48, 0xce, 0x3d, 0x04, 0x03, 0x04];
+/// rsaEncryption (1.2.840.113549.1.1.1)
+pub const RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x01];
+/// sha1WithRSAEncryption (1.2.840.113549.1.1.5)
+pub const SHA1_WITH_RSA_ENCRYPTION: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x05];
+/// id-RSASSA-PSS (1.2.840.113549.1.1.10)
+pub const ID_RSASSA_PSS: &[u8] = &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0a];
+/// sha256WithRSAEncryption (1.2.840.113549.1.1.11)
+pub const SHA256_WITH_RSA_ENCRYPTION: &[u8] =
+    &[0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x0b];
+/// id-X25519 (1.3.101.110)
+pub const ID_X25519: &[u8] = &[0x2b, 0x65, 0x6e];
+/// id-Ed25519 (1.3.101.112)
+pub const ID_ED25519: &[u8] = &[0x2b, 0x65, 0x70];
+
+/// The DER encoding of the `RSASSA-PSS-params` of PS256: SHA-256, MGF1 with SHA-256 and a salt
+/// length of 32 bytes
+pub const PS256_PARAMS: &[u8] = &[
+    0x30, 0x34, // SEQUENCE
+    0xa0, 0x0f, 0x30, 0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, // hashAlgorithm: id-sha256, NULL
+    0xa1, 0x1c, 0x30, 0x1a, 0x06, 0x09, 0x2a, 0x86, 0x48, 0x86, 0xf7, 0x0d, 0x01, 0x01, 0x08, 0x30,
+    0x0d, 0x06, 0x09, 0x60, 0x86, 0x48, 0x01, 0x65, 0x03, 0x04, 0x02, 0x01, 0x05,
+    0x00, // maskGenAlgorithm: id-mgf1, id-sha256
+    0xa2, 0x03, 0x02, 0x01, 0x20, // saltLength: 32
+];
+
+/// The `parameters` of an [`AlgorithmIdentifier`]
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Parameters<'a> {
+    /// The parameters are omitted
+    Absent,
+    /// The parameters are `NULL`, as required for RSA
+    Null,
+    /// The parameters are an OID, e.g. the curve of EC public keys
+    Oid(&'a [u8]),
+    /// The parameters are of another type and given as their complete DER encoding, including
+    /// the tag, e.g. [`PS256_PARAMS`]
+    Der(&'a [u8]),
+}
+
+/// An X.509 `AlgorithmIdentifier`
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct AlgorithmIdentifier<'a> {
+    /// The OID of the algorithm
+    pub algorithm: &'a [u8],
+    /// The parameters of the algorithm, whose type depends on the algorithm
+    pub parameters: Parameters<'a>,
+}
+
+/// The `AlgorithmIdentifier` of RSA public keys, rsaEncryption with `NULL` parameters
+///
+/// RSA keys have no curve, so this is not returned by [`public_key_algorithm`].
+pub const RSA_PUBLIC_KEY: AlgorithmIdentifier<'static> = AlgorithmIdentifier {
+    algorithm: RSA_ENCRYPTION,
+    parameters: Parameters::Null,
+};
+
+/// The `AlgorithmIdentifier` of a signature made with `alg`.
+///
+/// As cosey only supports Ed25519, `EdDSA` maps to id-Ed25519.  The fully specified algorithms of
+/// RFC 9864 map to the same identifiers as their polymorphic counterparts, e.g. both `ESP256` and
+/// `ES256` map to ecdsa-with-SHA256.
+pub fn signature_algorithm(alg: Alg) -> Option<AlgorithmIdentifier<'static>> {
+    let (algorithm, parameters) = match alg {
+        Alg::Es256 | Alg::Esp256 => (ECDSA_WITH_SHA256, Parameters::Absent),
+        Alg::Es384 | Alg::Esp384 => (ECDSA_WITH_SHA384, Parameters::Absent),
+        Alg::Es512 | Alg::Esp512 => (ECDSA_WITH_SHA512, Parameters::Absent),
+        Alg::EdDsa | Alg::Ed25519 => (ID_ED25519, Parameters::Absent),
+        Alg::Rs256 => (SHA256_WITH_RSA_ENCRYPTION, Parameters::Null),
+        Alg::Rs1 => (SHA1_WITH_RSA_ENCRYPTION, Parameters::Null),
+        Alg::Ps256 => (ID_RSASSA_PSS, Parameters::Der(PS256_PARAMS)),
+        _ => return None,
+    };
+    Some(AlgorithmIdentifier {
+        algorithm,
+        parameters,
+    })
+}
+
+/// The COSE algorithm of a signature with the given `AlgorithmIdentifier`.
+///
+/// As required by RFC 4055, the parameters of RSA signatures may be `NULL` or absent.
+///
+/// The polymorphic algorithms are returned, as used by WebAuthn, e.g. `ES256` for
+/// ecdsa-with-SHA256 and `EdDSA` for id-Ed25519.  The ECDSA identifiers do not determine the curve,
+/// so they cannot be mapped to the fully specified `ESP256`, `ESP384` and `ESP512`.  Ed25519 is
+/// treated the same way for consistency, although `Alg::Ed25519` would be exact.
+pub fn signature_alg(identifier: AlgorithmIdentifier<'_>) -> Option<Alg> {
+    use Parameters::{Absent, Der, Null};
+
+    match (identifier.algorithm, identifier.parameters) {
+        (ECDSA_WITH_SHA256, Absent) => Some(Alg::Es256),
+        (ECDSA_WITH_SHA384, Absent) => Some(Alg::Es384),
+        (ECDSA_WITH_SHA512, Absent) => Some(Alg::Es512),
+        (ID_ED25519, Absent) => Some(Alg::EdDsa),
+        (SHA256_WITH_RSA_ENCRYPTION, Absent | Null) => Some(Alg::Rs256),
+        (SHA1_WITH_RSA_ENCRYPTION, Absent | Null) => Some(Alg::Rs1),
+        (ID_RSASSA_PSS, Der(PS256_PARAMS)) => Some(Alg::Ps256),
+        _ => None,
+    }
+}
+
+/// The `AlgorithmIdentifier` of a `SubjectPublicKeyInfo` for a key of type `kty` on curve `crv`.
+///
+/// For RSA keys, use [`RSA_PUBLIC_KEY`].
+pub fn public_key_algorithm(kty: Kty, crv: Crv) -> Option<AlgorithmIdentifier<'static>> {
+    let (algorithm, parameters) = match (kty, crv) {
+        (Kty::Ec2, Crv::P256) => (ID_EC_PUBLIC_KEY, Parameters::Oid(SECP256R1)),
+        (Kty::Ec2, Crv::P384) => (ID_EC_PUBLIC_KEY, Parameters::Oid(SECP384R1)),
+        (Kty::Ec2, Crv::P521) => (ID_EC_PUBLIC_KEY, Parameters::Oid(SECP521R1)),
+        (Kty::Okp, Crv::X25519) => (ID_X25519, Parameters::Absent),
+        (Kty::Okp, Crv::Ed25519) => (ID_ED25519, Parameters::Absent),
+        _ => return None,
+    };
+    Some(AlgorithmIdentifier {
+        algorithm,
+        parameters,
+    })
+}
+
+/// The COSE key type and curve of a `SubjectPublicKeyInfo` with the given `AlgorithmIdentifier`.
+///
+/// Returns `None` for RSA keys, see [`public_key_kty`].
+pub fn public_key_kty_crv(identifier: AlgorithmIdentifier<'_>) -> Option<(Kty, Crv)> {
+    use Parameters::{Absent, Oid};
+
+    match (identifier.algorithm, identifier.parameters) {
+        (ID_EC_PUBLIC_KEY, Oid(SECP256R1)) => Some((Kty::Ec2, Crv::P256)),
+        (ID_EC_PUBLIC_KEY, Oid(SECP384R1)) => Some((Kty::Ec2, Crv::P384)),
+        (ID_EC_PUBLIC_KEY, Oid(SECP521R1)) => Some((Kty::Ec2, Crv::P521)),
+        (ID_X25519, Absent) => Some((Kty::Okp, Crv::X25519)),
+        (ID_ED25519, Absent) => Some((Kty::Okp, Crv::Ed25519)),
+        _ => None,
+    }
+}
+
+/// The COSE key type of a `SubjectPublicKeyInfo` with the given `AlgorithmIdentifier`.
+///
+/// Unlike [`public_key_kty_crv`], this also supports RSA keys, whose parameters may be `NULL` or
+/// absent.
+pub fn public_key_kty(identifier: AlgorithmIdentifier<'_>) -> Option<Kty> {
+    match (identifier.algorithm, identifier.parameters) {
+        (RSA_ENCRYPTION, Parameters::Absent | Parameters::Null) => Some(Kty::Rsa),
+        _ => public_key_kty_crv(identifier).map(|(kty, _)| kty),
+    }
+}
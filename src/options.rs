@@ -0,0 +1,261 @@
+use crate::{Alg, Error};
+
+/// Options for deserializing COSE_Keys
+///
+/// The default options only accept fields in canonical order with minimal encodings and without
+/// tags.  Unlike [`Profile::Ctap2Canonical`], they do not require `alg` and ignore unknown fields.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub struct ParseOptions {
+    pub(crate) any_order: bool,
+    pub(crate) allow_non_minimal: bool,
+    pub(crate) tags: TagPolicy,
+    pub(crate) deny_unknown_fields: bool,
+    pub(crate) text_labels: bool,
+    pub(crate) human_readable: bool,
+    pub(crate) require_alg: bool,
+    pub(crate) pad_short_coordinates: bool,
+    pub(crate) ec2_without_alg: Ec2Variant,
+    pub(crate) max_string_len: Option<usize>,
+    pub(crate) reject_small_order: bool,
+    pub(crate) allowed_algs: Option<&'static [Alg]>,
+    pub(crate) check_coordinate_range: bool,
+    pub(crate) enforce_key_ops: bool,
+}
+
+impl ParseOptions {
+    /// Returns the default options.
+    pub const fn new() -> Self {
+        Self {
+            any_order: false,
+            allow_non_minimal: false,
+            tags: TagPolicy::Reject,
+            deny_unknown_fields: false,
+            text_labels: false,
+            human_readable: false,
+            require_alg: false,
+            pad_short_coordinates: false,
+            ec2_without_alg: Ec2Variant::P256,
+            max_string_len: None,
+            reject_small_order: false,
+            allowed_algs: None,
+            check_coordinate_range: false,
+            enforce_key_ops: false,
+        }
+    }
+
+    /// Returns the options for the given profile.
+    pub const fn from_profile(profile: Profile) -> Self {
+        match profile {
+            Profile::Ctap2Canonical => Self::new().require_alg(true).deny_unknown_fields(true),
+            Profile::Rfc9052Lenient => Self::new()
+                .any_order(true)
+                .allow_non_minimal(true)
+                .tags(TagPolicy::Accept),
+        }
+    }
+
+    /// Accepts fields in any order instead of only in canonical order.
+    ///
+    /// Duplicate fields are still rejected.
+    pub const fn any_order(mut self, any_order: bool) -> Self {
+        self.any_order = any_order;
+        self
+    }
+
+    /// Accepts integers and lengths that are not encoded in the shortest possible form.
+    ///
+    /// This only applies to the parsing functions of this crate.  If a key is deserialized with
+    /// another deserializer, it depends on the deserializer whether such encodings are accepted.
+    pub const fn allow_non_minimal(mut self, allow_non_minimal: bool) -> Self {
+        self.allow_non_minimal = allow_non_minimal;
+        self
+    }
+
+    /// Sets how semantic tags are handled.
+    ///
+    /// This only applies to the parsing functions of this crate.  If a key is deserialized with
+    /// another deserializer, it depends on the deserializer whether tags are accepted.
+    pub const fn tags(mut self, tags: TagPolicy) -> Self {
+        self.tags = tags;
+        self
+    }
+
+    /// Rejects fields with labels that are not known to this crate instead of ignoring them.
+    pub const fn deny_unknown_fields(mut self, deny_unknown_fields: bool) -> Self {
+        self.deny_unknown_fields = deny_unknown_fields;
+        self
+    }
+
+    /// Accepts text labels in addition to integer labels.
+    ///
+    /// This is a workaround for broken clients that encode labels as decimal strings (`"-1"`) or
+    /// use the JOSE parameter names (`"crv"`).  Other text labels are treated as unknown fields.
+    /// It requires a self-describing deserializer like the one used by the parsing functions of
+    /// this crate, i.e. it is not supported by cbor-smol.
+    pub const fn text_labels(mut self, text_labels: bool) -> Self {
+        self.text_labels = text_labels;
+        self
+    }
+
+    /// Reads the form written by the `Serialize` implementations for human-readable serializers:
+    /// labels as strings and byte strings as unpadded base64url strings.
+    ///
+    /// This is required for JSON deserializers like serde-json-core, which only have text keys
+    /// and no byte strings.  Labels may be decimal integers or JOSE parameter names as with
+    /// [`text_labels`][Self::text_labels].  The option is ignored by deserializers that are not
+    /// human-readable, like the one used by the parsing functions of this crate.  It is not the
+    /// default because cbor-smol claims to be human-readable, so it must not be set for
+    /// cbor-smol.  `key_ops` can only be read if the deserializer supports `deserialize_any`,
+    /// which serde-json-core does not.
+    pub const fn human_readable(mut self, human_readable: bool) -> Self {
+        self.human_readable = human_readable;
+        self
+    }
+
+    /// Rejects keys without an `alg` field with [`Error::MissingField`][crate::Error::MissingField].
+    ///
+    /// By default, `alg` is optional as specified in RFC 9052.  The FIDO specifications require
+    /// it for credential public keys.
+    pub const fn require_alg(mut self, require_alg: bool) -> Self {
+        self.require_alg = require_alg;
+        self
+    }
+
+    /// Accepts coordinates of EC2 keys that are shorter than required and pads them with leading
+    /// zeros.
+    ///
+    /// This is a workaround for encoders that strip leading zero bytes from the coordinates.
+    pub const fn pad_short_coordinates(mut self, pad_short_coordinates: bool) -> Self {
+        self.pad_short_coordinates = pad_short_coordinates;
+        self
+    }
+
+    /// Sets the [`PublicKey`][crate::PublicKey] variant for EC2 keys on P-256 without an `alg`
+    /// field.
+    pub const fn ec2_without_alg(mut self, variant: Ec2Variant) -> Self {
+        self.ec2_without_alg = variant;
+        self
+    }
+
+    /// Rejects byte and text strings that are longer than the given number of bytes with
+    /// [`Error::StringTooLong`][crate::Error::StringTooLong].
+    ///
+    /// The limit applies to all strings in the key, including the values of unknown fields, and is
+    /// checked before the string is read.  The key material of the known fields is always limited
+    /// to the length required by the key type.  This only applies to the parsing functions of this
+    /// crate.
+    pub const fn max_string_len(mut self, max_string_len: Option<usize>) -> Self {
+        self.max_string_len = max_string_len;
+        self
+    }
+
+    /// Rejects OKP keys on Ed25519 or X25519 that are a point of small order or the identity with
+    /// [`Error::SmallOrder`][crate::Error::SmallOrder].
+    ///
+    /// Such keys force the result of a key agreement to a value that is known to an attacker, and
+    /// Ed25519 signatures with such keys can be forged.  The points are detected by their encoding,
+    /// see [`Ed25519PublicKey::is_small_order`][crate::Ed25519PublicKey::is_small_order] and
+    /// [`X25519PublicKey::is_small_order`][crate::X25519PublicKey::is_small_order].
+    pub const fn reject_small_order(mut self, reject_small_order: bool) -> Self {
+        self.reject_small_order = reject_small_order;
+        self
+    }
+
+    /// Rejects keys with an algorithm that is not in `algs` with
+    /// [`Error::DisallowedAlg`].
+    ///
+    /// This can be used to enforce a policy like the approved algorithms of a certification.  The
+    /// policy applies to the parsing functions and to `deserialize_with` of all key types.  Keys
+    /// without an `alg` field are only rejected if [`require_alg`][Self::require_alg] is set.
+    pub const fn allowed_algs(mut self, algs: Option<&'static [Alg]>) -> Self {
+        self.allowed_algs = algs;
+        self
+    }
+
+    /// Rejects EC2 keys on P-256 with a coordinate that is not less than the field prime with
+    /// [`Error::CoordinateOutOfRange`].
+    ///
+    /// Such coordinates indicate a corrupted or malicious key.  The check only compares the
+    /// coordinates with the prime, see
+    /// [`P256PublicKey::coordinates_in_range`][crate::P256PublicKey::coordinates_in_range].
+    pub const fn check_coordinate_range(mut self, check_coordinate_range: bool) -> Self {
+        self.check_coordinate_range = check_coordinate_range;
+        self
+    }
+
+    /// Rejects keys whose `key_ops` field does not permit what the key is used for with
+    /// [`Error::KeyOpNotPermitted`].
+    ///
+    /// The use of a key is determined after the conversion, see
+    /// [`PublicKey::key_use`][crate::PublicKey::key_use] and
+    /// [`KeyUse::key_ops`][crate::KeyUse::key_ops].  For example, a P-256 key with only the
+    /// `verify` operation is accepted as a signature key but rejected as an ECDH-ES+HKDF-256 key.
+    /// Keys without `key_ops` are always accepted.  The check applies to the parsing functions
+    /// and to `deserialize_with` of all public key types, but not to [`RawCoseKey`][crate::RawCoseKey]
+    /// or the `TryFrom` conversions.
+    pub const fn enforce_key_ops(mut self, enforce_key_ops: bool) -> Self {
+        self.enforce_key_ops = enforce_key_ops;
+        self
+    }
+
+    pub(crate) fn check_alg(&self, alg: Alg) -> Result<(), Error> {
+        match self.allowed_algs {
+            Some(algs) if !algs.contains(&alg) => Err(Error::DisallowedAlg(alg)),
+            _ => Ok(()),
+        }
+    }
+}
+
+/// The [`PublicKey`][crate::PublicKey] variant for EC2 keys on P-256 without an `alg` field
+///
+/// Such keys could be used both for signatures and for key agreement.  This only affects
+/// deserialization of `PublicKey`, the key types themselves always accept a missing `alg`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum Ec2Variant {
+    /// Deserialize as [`PublicKey::P256Key`][crate::PublicKey::P256Key]
+    #[default]
+    P256,
+    /// Deserialize as [`PublicKey::EcdhEsHkdf256Key`][crate::PublicKey::EcdhEsHkdf256Key]
+    EcdhEsHkdf256,
+    /// Reject the key as ambiguous
+    Reject,
+}
+
+impl From<Profile> for ParseOptions {
+    fn from(profile: Profile) -> Self {
+        Self::from_profile(profile)
+    }
+}
+
+/// Predefined [`ParseOptions`] for common use cases
+///
+/// The options of a profile can be adjusted further with the builder methods of `ParseOptions`.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum Profile {
+    /// Only accept keys in the canonical CTAP2 form, as sent by authenticators
+    ///
+    /// `alg` is required as for credential public keys, and unknown fields are rejected because
+    /// authenticators only send the parameters of the key type.
+    #[default]
+    Ctap2Canonical,
+    /// Accept any key that is valid according to RFC 9052, e.g. from clients or other tools
+    ///
+    /// Fields can appear in any order, integers do not have to be encoded minimally and tags are
+    /// ignored.  `alg` is optional and unknown fields are ignored.
+    Rfc9052Lenient,
+}
+
+/// The handling of CBOR semantic tags in the input
+///
+/// Keys without a tag are always accepted.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq)]
+pub enum TagPolicy {
+    /// Reject all tags
+    #[default]
+    Reject,
+    /// Ignore tags on the key and on the values of its fields
+    Accept,
+    /// Only accept the given tag on the key, and ignore tags on the values of its fields
+    Only(u64),
+}
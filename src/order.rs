@@ -0,0 +1,160 @@
+//! Ordering of keys by their canonical CBOR encoding
+//!
+//! The encodings are compared item by item without serializing the keys.  As CBOR data items are
+//! self-delimiting, this gives the same result as comparing the complete encodings bytewise.
+
+use core::cmp::Ordering;
+
+use heapless::Vec;
+
+#[cfg(feature = "ecdh")]
+use crate::EcdhEsHkdf256PublicKey;
+#[cfg(feature = "ed25519")]
+use crate::Ed25519PublicKey;
+#[cfg(feature = "p256")]
+use crate::P256PublicKey;
+#[cfg(feature = "totp")]
+use crate::TotpPublicKey;
+#[cfg(feature = "x25519")]
+use crate::X25519PublicKey;
+use crate::{label, Alg, Crv, KeyFields, KeyOpEntry, Kty, PublicKey, RawCoseKey};
+
+// A data item of the canonical encoding of a key
+#[derive(Clone, Copy)]
+pub(crate) enum Item<'a> {
+    Map(usize),
+    Array(usize),
+    Int(i64),
+    Bytes(&'a [u8]),
+    Text(&'a str),
+}
+
+impl<'a> Item<'a> {
+    pub(crate) fn head(self) -> Vec<u8, 9> {
+        let (major, value) = match self {
+            Self::Map(len) => (5, len as u64),
+            Self::Array(len) => (4, len as u64),
+            Self::Int(value) if value < 0 => (1, !value as u64),
+            Self::Int(value) => (0, value as u64),
+            Self::Bytes(bytes) => (2, bytes.len() as u64),
+            Self::Text(text) => (3, text.len() as u64),
+        };
+        let bytes = value.to_be_bytes();
+        let (info, argument): (u8, &[u8]) = match value {
+            0..=23 => (bytes[7], &[]),
+            24..=0xff => (24, &bytes[7..]),
+            0x100..=0xffff => (25, &bytes[6..]),
+            0x1_0000..=0xffff_ffff => (26, &bytes[4..]),
+            _ => (27, &bytes),
+        };
+        let mut head = Vec::new();
+        // the initial byte and an argument of at most eight bytes always fit
+        head.push(major << 5 | info).ok();
+        head.extend_from_slice(argument).ok();
+        head
+    }
+
+    pub(crate) fn payload(self) -> &'a [u8] {
+        match self {
+            Self::Bytes(bytes) => bytes,
+            Self::Text(text) => text.as_bytes(),
+            _ => &[],
+        }
+    }
+
+    fn encoding_cmp(self, other: Self) -> Ordering {
+        let head = self.head();
+        let other_head = other.head();
+        head.iter()
+            .chain(self.payload())
+            .cmp(other_head.iter().chain(other.payload()))
+    }
+}
+
+// Compares two sequences of data items by their encoding.
+pub(crate) fn cmp<'a, 'b>(
+    items: impl IntoIterator<Item = Item<'a>>,
+    other: impl IntoIterator<Item = Item<'b>>,
+) -> Ordering {
+    let mut other = other.into_iter();
+    for item in items {
+        let Some(other_item) = other.next() else {
+            return Ordering::Greater;
+        };
+        match item.encoding_cmp(other_item) {
+            Ordering::Equal => {}
+            ordering => return ordering,
+        }
+    }
+    if other.next().is_some() {
+        Ordering::Less
+    } else {
+        Ordering::Equal
+    }
+}
+
+impl<'a> KeyFields<'a> {
+    pub(crate) fn items(self) -> impl Iterator<Item = Item<'a>> {
+        let int = |label: i8, value: Option<i32>| {
+            value.map(|value| [Item::Int(label.into()), Item::Int(value.into())])
+        };
+        let bytes = |label: i8, value: Option<&'a [u8]>| {
+            value.map(|value| [Item::Int(label.into()), Item::Bytes(value)])
+        };
+        // the fields before and after key_ops in canonical order
+        let before = [
+            int(label::KTY, self.kty.map(Kty::to_i32)),
+            int(label::ALG, self.alg.map(Alg::to_i32)),
+        ];
+        let after = [
+            int(label::ec2::CRV, self.crv.map(Crv::to_i32)),
+            bytes(label::ec2::X, self.x),
+            bytes(label::ec2::Y, self.y),
+        ];
+        let fields =
+            before.iter().chain(&after).flatten().count() + usize::from(self.key_ops.is_some());
+        let key_ops = self.key_ops.into_iter().flat_map(|key_ops| {
+            let head = [Item::Int(label::KEY_OPS.into()), Item::Array(key_ops.len())];
+            head.into_iter()
+                .chain(key_ops.iter().map(|entry| match entry {
+                    KeyOpEntry::Int(op) => Item::Int(op.to_i32().into()),
+                    KeyOpEntry::Text(text) => Item::Text(text),
+                }))
+        });
+        core::iter::once(Item::Map(fields))
+            .chain(before.into_iter().flatten().flatten())
+            .chain(key_ops)
+            .chain(after.into_iter().flatten().flatten())
+    }
+}
+
+macro_rules! impl_ord {
+    ($($ty:ty),*) => {
+        $(
+            /// Keys are ordered by their canonical CBOR encoding.
+            impl Ord for $ty {
+                fn cmp(&self, other: &Self) -> Ordering {
+                    cmp(self.fields().items(), other.fields().items())
+                }
+            }
+
+            impl PartialOrd for $ty {
+                fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+                    Some(self.cmp(other))
+                }
+            }
+        )*
+    };
+}
+
+impl_ord!(RawCoseKey, PublicKey);
+#[cfg(feature = "p256")]
+impl_ord!(P256PublicKey);
+#[cfg(feature = "ecdh")]
+impl_ord!(EcdhEsHkdf256PublicKey);
+#[cfg(feature = "ed25519")]
+impl_ord!(Ed25519PublicKey);
+#[cfg(feature = "totp")]
+impl_ord!(TotpPublicKey);
+#[cfg(feature = "x25519")]
+impl_ord!(X25519PublicKey);
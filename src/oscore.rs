@@ -0,0 +1,243 @@
+//! Input material of OSCORE security contexts
+//!
+//! [`OscoreInputMaterial`] holds the inputs of the key derivation of an OSCORE security context
+//! (RFC 8613, section 3.2) and is encoded as the `OSCORE_Input_Material` map of RFC 9203,
+//! section 3.2.1, e.g. to provision contexts to devices.  Algorithms are identified by their
+//! values in the COSE registry; text names are not supported.
+
+#[cfg(feature = "serde")]
+use core::fmt::{self, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::de::{MapAccess, Visitor};
+#[cfg(feature = "serde")]
+use serde::ser::SerializeMap as _;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+#[cfg(feature = "serde")]
+use crate::custom::{ByteStr, LabelKey};
+#[cfg(feature = "serde")]
+use crate::map::{self, MapFields, SeenFields};
+use crate::{Alg, Bytes};
+#[cfg(feature = "serde")]
+use crate::{BytesSeed, Error, Key, KeySeed, ParseOptions};
+
+/// The maximum length of the byte strings of [`OscoreInputMaterial`]
+pub const MAX_LEN: usize = 32;
+
+/// The default AEAD algorithm, AES-CCM-16-64-128
+pub const DEFAULT_AEAD_ALG: Alg = Alg::AesCcm16_64_128;
+
+/// The default HKDF algorithm, HKDF SHA-256
+pub const DEFAULT_HKDF_ALG: Alg = Alg::DirectHkdfSha256;
+
+/// The labels of the `OSCORE_Input_Material` map
+pub mod label {
+    /// `id`
+    pub const ID: i8 = 0;
+    /// `version`
+    pub const VERSION: i8 = 1;
+    /// `ms`
+    pub const MS: i8 = 2;
+    /// `hkdf`
+    pub const HKDF: i8 = 3;
+    /// `alg`
+    pub const ALG: i8 = 4;
+    /// `salt`
+    pub const SALT: i8 = 5;
+    /// `contextId`
+    pub const CONTEXT_ID: i8 = 6;
+}
+
+#[cfg(feature = "serde")]
+const LABELS: [i8; 7] = [
+    label::ID,
+    label::VERSION,
+    label::MS,
+    label::HKDF,
+    label::ALG,
+    label::SALT,
+    label::CONTEXT_ID,
+];
+
+#[cfg(feature = "serde")]
+const NAMES: [&str; 7] = ["id", "version", "ms", "hkdf", "alg", "salt", "contextId"];
+
+/// The input material of an OSCORE security context
+///
+/// Only the master secret is required; the defaults of absent parameters are applied by the
+/// accessors.  The sender and recipient IDs are not part of the input material.
+#[derive(Clone, Debug, Default, Eq, Hash, PartialEq)]
+pub struct OscoreInputMaterial {
+    /// The identifier of the input material (`id`)
+    pub id: Option<Bytes<MAX_LEN>>,
+    /// The OSCORE version (`version`)
+    pub version: Option<u64>,
+    /// The master secret (`ms`)
+    pub master_secret: Bytes<MAX_LEN>,
+    /// The HKDF algorithm (`hkdf`)
+    pub hkdf: Option<Alg>,
+    /// The AEAD algorithm (`alg`)
+    pub alg: Option<Alg>,
+    /// The master salt (`salt`)
+    pub master_salt: Option<Bytes<MAX_LEN>>,
+    /// The ID context (`contextId`)
+    pub id_context: Option<Bytes<MAX_LEN>>,
+}
+
+impl OscoreInputMaterial {
+    /// Returns the OSCORE version, 1 if it is not set.
+    pub fn version(&self) -> u64 {
+        self.version.unwrap_or(1)
+    }
+
+    /// Returns the HKDF algorithm, [`DEFAULT_HKDF_ALG`] if it is not set.
+    pub fn hkdf_alg(&self) -> Alg {
+        self.hkdf.unwrap_or(DEFAULT_HKDF_ALG)
+    }
+
+    /// Returns the AEAD algorithm, [`DEFAULT_AEAD_ALG`] if it is not set.
+    pub fn aead_alg(&self) -> Alg {
+        self.alg.unwrap_or(DEFAULT_AEAD_ALG)
+    }
+
+    /// Returns the master salt, which is empty if it is not set.
+    pub fn master_salt(&self) -> &[u8] {
+        self.master_salt.as_ref().map_or(&[], Bytes::as_slice)
+    }
+
+    /// Deserializes the input material using the given options.
+    ///
+    /// Only [`any_order`][ParseOptions::any_order],
+    /// [`deny_unknown_fields`][ParseOptions::deny_unknown_fields] and
+    /// [`human_readable`][ParseOptions::human_readable] apply.
+    #[cfg(feature = "serde")]
+    pub fn deserialize_with<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        options: ParseOptions,
+    ) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_map(MaterialVisitor {
+                material: OscoreInputMaterial::default(),
+                seen: SeenFields::new(options),
+                human_readable: options.human_readable,
+            })?
+            .map_err(Error::into_de_error)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for OscoreInputMaterial {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields = [
+            self.id.is_some(),
+            self.version.is_some(),
+            true,
+            self.hkdf.is_some(),
+            self.alg.is_some(),
+            self.master_salt.is_some(),
+            self.id_context.is_some(),
+        ];
+        let mut map = serializer.serialize_map(Some(fields.into_iter().filter(|f| *f).count()))?;
+        if let Some(id) = &self.id {
+            map.serialize_entry(&LabelKey(label::ID), &ByteStr(id))?;
+        }
+        if let Some(version) = &self.version {
+            map.serialize_entry(&LabelKey(label::VERSION), version)?;
+        }
+        map.serialize_entry(&LabelKey(label::MS), &ByteStr(&self.master_secret))?;
+        if let Some(hkdf) = &self.hkdf {
+            map.serialize_entry(&LabelKey(label::HKDF), hkdf)?;
+        }
+        if let Some(alg) = &self.alg {
+            map.serialize_entry(&LabelKey(label::ALG), alg)?;
+        }
+        if let Some(salt) = &self.master_salt {
+            map.serialize_entry(&LabelKey(label::SALT), &ByteStr(salt))?;
+        }
+        if let Some(id_context) = &self.id_context {
+            map.serialize_entry(&LabelKey(label::CONTEXT_ID), &ByteStr(id_context))?;
+        }
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for OscoreInputMaterial {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::deserialize_with(deserializer, ParseOptions::default())
+    }
+}
+
+#[cfg(feature = "serde")]
+struct MaterialVisitor {
+    material: OscoreInputMaterial,
+    seen: SeenFields,
+    human_readable: bool,
+}
+
+#[cfg(feature = "serde")]
+impl<'de> MapFields<'de> for MaterialVisitor {
+    type Field = usize;
+
+    fn key(&mut self, key: Key) -> Result<Option<usize>, Error> {
+        self.seen.check(map::find_field(&LABELS, &NAMES, key))
+    }
+
+    fn read_value<V: MapAccess<'de>>(
+        &mut self,
+        index: usize,
+        map: &mut V,
+    ) -> Result<Result<(), Error>, V::Error> {
+        let material = &mut self.material;
+        let Some(&label) = LABELS.get(index) else {
+            return Ok(Err(Error::InvalidKey));
+        };
+        match label {
+            label::VERSION => material.version = Some(map.next_value()?),
+            label::HKDF => material.hkdf = Some(map.next_value()?),
+            label::ALG => material.alg = Some(map.next_value()?),
+            _ => {
+                let bytes = match map.next_value_seed(BytesSeed::<MAX_LEN> {
+                    label,
+                    human_readable: self.human_readable,
+                })? {
+                    Ok(bytes) => bytes,
+                    Err(err) => return Ok(Err(err)),
+                };
+                match label {
+                    label::ID => material.id = Some(bytes),
+                    label::MS => material.master_secret = bytes,
+                    label::SALT => material.master_salt = Some(bytes),
+                    _ => material.id_context = Some(bytes),
+                }
+            }
+        }
+        Ok(Ok(()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for MaterialVisitor {
+    type Value = Result<OscoreInputMaterial, Error>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("OSCORE_Input_Material")
+    }
+
+    fn visit_map<V: MapAccess<'de>>(mut self, mut map: V) -> Result<Self::Value, V::Error> {
+        let seed = KeySeed {
+            text_labels: false,
+            human_readable: self.human_readable,
+        };
+        if let Err(err) = map::visit_entries(&mut self, &mut map, seed)? {
+            return Ok(Err(err));
+        }
+        // the labels are their indices in the canonical order
+        if !usize::try_from(label::MS).is_ok_and(|index| self.seen.contains(index)) {
+            return Ok(Err(Error::MissingField("ms")));
+        }
+        Ok(Ok(self.material))
+    }
+}
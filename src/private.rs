@@ -0,0 +1,286 @@
+//! Private keys that may omit the public key
+//!
+//! RFC 9053 allows EC2 and OKP private keys to contain only the curve and the private key `d`.
+//! [`PrivateKey`] accepts such keys as well as keys with the public parameters, and
+//! [`PrivateKey::public_key`] returns the public key if it is included or can be computed.
+
+use core::fmt::{self, Formatter};
+
+#[cfg(feature = "serde")]
+use serde::de::{MapAccess, Visitor};
+#[cfg(feature = "serde")]
+use serde::ser::SerializeMap as _;
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use zeroize::{Zeroize as _, ZeroizeOnDrop};
+
+#[cfg(feature = "serde")]
+use crate::custom::{ByteStr, LabelKey};
+#[cfg(any(feature = "curve25519-dalek", feature = "p256-curve"))]
+use crate::keypair;
+#[cfg(feature = "serde")]
+use crate::map::{self, MapFields, SeenFields};
+use crate::{bytes_from_array, Alg, Crv, Kty, ParseOptions, PublicKey, RawCoseKey};
+#[cfg(feature = "serde")]
+use crate::{label, BytesSeed, Error, Key, KeySeed};
+
+// The labels in canonical order
+#[cfg(feature = "serde")]
+const LABELS: [i8; 6] = [
+    label::KTY,
+    label::ALG,
+    label::ec2::CRV,
+    label::ec2::X,
+    label::ec2::Y,
+    label::ec2::D,
+];
+
+#[cfg(feature = "serde")]
+const NAMES: [&str; 6] = ["kty", "alg", "crv", "x", "y", "d"];
+
+/// A private key on P-256, Ed25519 or X25519
+///
+/// Only `kty`, `crv` and `d` are required.  The `Debug` output does not show `d`, and `d` is
+/// zeroized when the key is dropped.  As comparisons of `d` should be constant-time, the key does
+/// not implement `PartialEq`.
+#[derive(Clone)]
+pub struct PrivateKey {
+    /// The key type, EC2 or OKP
+    pub kty: Kty,
+    /// The algorithm, if the key is restricted to one
+    pub alg: Option<Alg>,
+    /// The curve
+    pub crv: Crv,
+    /// The x-coordinate of the public key, or the public key of OKP keys
+    pub x: Option<[u8; 32]>,
+    /// The y-coordinate of the public key of EC2 keys
+    pub y: Option<[u8; 32]>,
+    /// The private key
+    pub d: [u8; 32],
+}
+
+impl PrivateKey {
+    /// Returns the public key of this private key.
+    ///
+    /// If the key does not contain the public key, it is computed if the backend for the curve
+    /// is enabled, see [`keypair`][crate::keypair], and `None` is returned otherwise.  Keys are
+    /// converted with [`PublicKey::from_raw`], so `None` is also returned if the public key is
+    /// not supported by [`PublicKey`].
+    pub fn public_key(&self) -> Option<PublicKey> {
+        let (x, y) = match self.x {
+            Some(x) => (x, self.y),
+            None => self.compute_public_key()?,
+        };
+        let raw = RawCoseKey {
+            kty: Some(self.kty),
+            alg: self.alg,
+            key_ops: None,
+            crv: Some(self.crv),
+            x: Some(bytes_from_array(x)),
+            y: y.map(bytes_from_array),
+        };
+        PublicKey::from_raw(raw, ParseOptions::new()).ok()
+    }
+
+    fn compute_public_key(&self) -> Option<([u8; 32], Option<[u8; 32]>)> {
+        match (self.kty, self.crv) {
+            #[cfg(feature = "p256-curve")]
+            (Kty::Ec2, Crv::P256) => keypair::p256_public(&self.d).map(|(x, y)| (x, Some(y))),
+            #[cfg(all(feature = "curve25519-dalek", feature = "sha2"))]
+            (Kty::Okp, Crv::Ed25519) => Some((keypair::ed25519_public(&self.d), None)),
+            #[cfg(feature = "curve25519-dalek")]
+            (Kty::Okp, Crv::X25519) => Some((keypair::x25519_public(&self.d), None)),
+            _ => None,
+        }
+    }
+
+    /// Deserializes a key using the given options.
+    ///
+    /// Only [`any_order`][ParseOptions::any_order],
+    /// [`deny_unknown_fields`][ParseOptions::deny_unknown_fields] and
+    /// [`human_readable`][ParseOptions::human_readable] apply.
+    #[cfg(feature = "serde")]
+    pub fn deserialize_with<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        options: ParseOptions,
+    ) -> Result<Self, D::Error> {
+        deserializer
+            .deserialize_map(PrivateKeyVisitor {
+                fields: Fields::default(),
+                seen: SeenFields::new(options),
+                human_readable: options.human_readable,
+            })?
+            .map_err(Error::into_de_error)
+    }
+}
+
+impl Drop for PrivateKey {
+    fn drop(&mut self) {
+        self.d.zeroize();
+    }
+}
+
+impl ZeroizeOnDrop for PrivateKey {}
+
+impl fmt::Debug for PrivateKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        f.debug_struct("PrivateKey")
+            .field("kty", &self.kty)
+            .field("alg", &self.alg)
+            .field("crv", &self.crv)
+            .field("x", &self.x)
+            .field("y", &self.y)
+            .finish_non_exhaustive()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl Serialize for PrivateKey {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let fields = 3
+            + usize::from(self.alg.is_some())
+            + usize::from(self.x.is_some())
+            + usize::from(self.y.is_some());
+        let mut map = serializer.serialize_map(Some(fields))?;
+        map.serialize_entry(&LabelKey(label::KTY), &self.kty)?;
+        if let Some(alg) = &self.alg {
+            map.serialize_entry(&LabelKey(label::ALG), alg)?;
+        }
+        map.serialize_entry(&LabelKey(label::ec2::CRV), &self.crv)?;
+        if let Some(x) = &self.x {
+            map.serialize_entry(&LabelKey(label::ec2::X), &ByteStr(x))?;
+        }
+        if let Some(y) = &self.y {
+            map.serialize_entry(&LabelKey(label::ec2::Y), &ByteStr(y))?;
+        }
+        map.serialize_entry(&LabelKey(label::ec2::D), &ByteStr(&self.d))?;
+        map.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Deserialize<'de> for PrivateKey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        Self::deserialize_with(deserializer, ParseOptions::default())
+    }
+}
+
+#[cfg(feature = "serde")]
+#[derive(Default)]
+struct Fields {
+    kty: Option<Kty>,
+    alg: Option<Alg>,
+    crv: Option<Crv>,
+    params: [Option<[u8; 32]>; 3],
+}
+
+#[cfg(feature = "serde")]
+impl Fields {
+    fn finish(self) -> Result<PrivateKey, Error> {
+        let [x, y, d] = self.params;
+        let kty = self.kty.ok_or(Error::MissingField("kty"))?;
+        let crv = self.crv.ok_or(Error::MissingField("crv"))?;
+        let d = d.ok_or(Error::MissingField("d"))?;
+        match (kty, crv) {
+            (Kty::Ec2, Crv::P256) => {}
+            (Kty::Okp, Crv::Ed25519 | Crv::X25519) if y.is_none() => {}
+            (Kty::Okp, Crv::Ed25519 | Crv::X25519) => return Err(Error::UnknownField(-3)),
+            (Kty::Ec2 | Kty::Okp, _) => {
+                return Err(Error::UnsupportedValue {
+                    label: label::ec2::CRV,
+                    value: crv.to_i32().into(),
+                })
+            }
+            _ => {
+                return Err(Error::UnsupportedValue {
+                    label: label::KTY,
+                    value: kty.to_i32().into(),
+                })
+            }
+        }
+        Ok(PrivateKey {
+            kty,
+            alg: self.alg,
+            crv,
+            x,
+            y,
+            d,
+        })
+    }
+}
+
+#[cfg(feature = "serde")]
+struct PrivateKeyVisitor {
+    fields: Fields,
+    seen: SeenFields,
+    human_readable: bool,
+}
+
+#[cfg(feature = "serde")]
+impl<'de> MapFields<'de> for PrivateKeyVisitor {
+    type Field = usize;
+
+    fn key(&mut self, key: Key) -> Result<Option<usize>, Error> {
+        self.seen.check(map::find_field(&LABELS, &NAMES, key))
+    }
+
+    fn read_value<V: MapAccess<'de>>(
+        &mut self,
+        index: usize,
+        map: &mut V,
+    ) -> Result<Result<(), Error>, V::Error> {
+        let fields = &mut self.fields;
+        let Some(&label) = LABELS.get(index) else {
+            return Ok(Err(Error::InvalidKey));
+        };
+        match label {
+            label::KTY => fields.kty = Some(map.next_value()?),
+            label::ALG => fields.alg = Some(map.next_value()?),
+            label::ec2::CRV => fields.crv = Some(map.next_value()?),
+            _ => {
+                let bytes = match map.next_value_seed(BytesSeed::<32> {
+                    label,
+                    human_readable: self.human_readable,
+                })? {
+                    Ok(bytes) => bytes,
+                    Err(err) => return Ok(Err(err)),
+                };
+                let param = match bytes.as_slice().try_into() {
+                    Ok(param) => param,
+                    Err(_) => {
+                        return Ok(Err(Error::InvalidLength {
+                            label,
+                            expected: 32,
+                            actual: bytes.len(),
+                        }))
+                    }
+                };
+                if let Some(slot) = fields.params.get_mut(index.saturating_sub(3)) {
+                    *slot = Some(param);
+                }
+            }
+        }
+        Ok(Ok(()))
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> Visitor<'de> for PrivateKeyVisitor {
+    type Value = Result<PrivateKey, Error>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("PrivateKey")
+    }
+
+    fn visit_map<V: MapAccess<'de>>(mut self, mut map: V) -> Result<Self::Value, V::Error> {
+        // text labels are not supported for private keys
+        let seed = KeySeed {
+            text_labels: false,
+            human_readable: self.human_readable,
+        };
+        if let Err(err) = map::visit_entries(&mut self, &mut map, seed)? {
+            return Ok(Err(err));
+        }
+        Ok(self.fields.finish())
+    }
+}
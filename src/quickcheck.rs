@@ -0,0 +1,131 @@
+//! Implementations of [`Arbitrary`] for property tests
+//!
+//! As with the implementations for fuzzing, the generated keys have arbitrary key material, and
+//! [`RawCoseKey`] has arbitrary fields.
+
+use ::quickcheck::{Arbitrary, Gen};
+
+#[cfg(feature = "ecdh")]
+use crate::EcdhEsHkdf256PublicKey;
+#[cfg(feature = "ed25519")]
+use crate::Ed25519PublicKey;
+#[cfg(feature = "p256")]
+use crate::P256PublicKey;
+#[cfg(feature = "totp")]
+use crate::TotpPublicKey;
+#[cfg(feature = "x25519")]
+use crate::X25519PublicKey;
+use crate::{Bytes, KeyOpEntry, KeyOps, PublicKey, RawCoseKey, MAX_KEY_OPS, MAX_KEY_OP_LEN};
+
+#[cfg(feature = "p256")]
+impl Arbitrary for P256PublicKey {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self {
+            x: Arbitrary::arbitrary(g),
+            y: Arbitrary::arbitrary(g),
+        }
+    }
+}
+
+#[cfg(feature = "ecdh")]
+impl Arbitrary for EcdhEsHkdf256PublicKey {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self {
+            x: Arbitrary::arbitrary(g),
+            y: Arbitrary::arbitrary(g),
+        }
+    }
+}
+
+#[cfg(feature = "ed25519")]
+impl Arbitrary for Ed25519PublicKey {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self {
+            x: Arbitrary::arbitrary(g),
+        }
+    }
+}
+
+#[cfg(feature = "totp")]
+impl Arbitrary for TotpPublicKey {
+    fn arbitrary(_g: &mut Gen) -> Self {
+        Self {}
+    }
+}
+
+#[cfg(feature = "x25519")]
+impl Arbitrary for X25519PublicKey {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self {
+            pub_key: Arbitrary::arbitrary(g),
+        }
+    }
+}
+
+impl Arbitrary for PublicKey {
+    // at least one key type is enabled, see the `compile_error!` in the crate root
+    #[allow(clippy::expect_used)]
+    fn arbitrary(g: &mut Gen) -> Self {
+        let variants: &[fn(&mut Gen) -> Self] = &[
+            #[cfg(feature = "p256")]
+            |g| Self::P256Key(Arbitrary::arbitrary(g)),
+            #[cfg(feature = "ecdh")]
+            |g| Self::EcdhEsHkdf256Key(Arbitrary::arbitrary(g)),
+            #[cfg(feature = "ed25519")]
+            |g| Self::Ed25519Key(Arbitrary::arbitrary(g)),
+            #[cfg(feature = "totp")]
+            |g| Self::TotpKey(Arbitrary::arbitrary(g)),
+            #[cfg(feature = "x25519")]
+            |g| Self::X25519Key(Arbitrary::arbitrary(g)),
+        ];
+        g.choose(variants).expect("no key type enabled")(g)
+    }
+}
+
+impl Arbitrary for KeyOpEntry {
+    fn arbitrary(g: &mut Gen) -> Self {
+        if bool::arbitrary(g) {
+            return Self::Int(Arbitrary::arbitrary(g));
+        }
+        let mut text = heapless::String::new();
+        for _ in 0..usize::arbitrary(g) % (MAX_KEY_OP_LEN + 1) {
+            // stop at the first character that does not fit
+            if text.push(char::arbitrary(g)).is_err() {
+                break;
+            }
+        }
+        Self::Text(text)
+    }
+}
+
+impl Arbitrary for RawCoseKey {
+    fn arbitrary(g: &mut Gen) -> Self {
+        Self {
+            kty: Arbitrary::arbitrary(g),
+            alg: Arbitrary::arbitrary(g),
+            key_ops: arbitrary_key_ops(g),
+            crv: Arbitrary::arbitrary(g),
+            x: arbitrary_bytes(g),
+            y: arbitrary_bytes(g),
+        }
+    }
+}
+
+// A non-empty `key_ops` field, or `None`
+fn arbitrary_key_ops(g: &mut Gen) -> Option<KeyOps> {
+    if !bool::arbitrary(g) {
+        return None;
+    }
+    let mut key_ops = KeyOps::new();
+    for _ in 0..=usize::arbitrary(g) % MAX_KEY_OPS {
+        key_ops.push(Arbitrary::arbitrary(g)).ok()?;
+    }
+    Some(key_ops)
+}
+
+// A byte string of at most `N` bytes, or `None`
+fn arbitrary_bytes<const N: usize>(g: &mut Gen) -> Option<Bytes<N>> {
+    let bytes: Option<[u8; N]> = Arbitrary::arbitrary(g);
+    let len = usize::arbitrary(g) % (N + 1);
+    bytes.and_then(|bytes| Bytes::from_slice(bytes.get(..len)?).ok())
+}
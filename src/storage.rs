@@ -0,0 +1,102 @@
+//! A versioned format for storing keys
+//!
+//! Keys are stored as a CBOR array of the format version and the canonical COSE_Key.  Later
+//! versions may append elements to the array, which are ignored when decoding.  Keys with
+//! algorithms that are not supported by [`PublicKey`] can still be decoded as [`RawCoseKey`].
+
+use core::fmt::{self, Formatter};
+
+use serde::de::{DeserializeSeed, IgnoredAny, SeqAccess, Visitor};
+use serde::ser::{Serialize, SerializeTuple as _, Serializer};
+
+use crate::cbor::{exact, ser, Deserializer};
+use crate::{Bytes, Error, KeyFields, ParseOptions, PublicKey, RawCoseKey};
+
+/// The version of the storage format written by this crate
+pub const STORAGE_VERSION: u64 = 1;
+
+struct Stored<'a>(KeyFields<'a>);
+
+impl Serialize for Stored<'_> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut tuple = serializer.serialize_tuple(2)?;
+        tuple.serialize_element(&STORAGE_VERSION)?;
+        tuple.serialize_element(&self.0)?;
+        tuple.end()
+    }
+}
+
+struct KeySeed(ParseOptions);
+
+impl<'de> DeserializeSeed<'de> for KeySeed {
+    type Value = Result<RawCoseKey, Error>;
+
+    fn deserialize<D: serde::Deserializer<'de>>(
+        self,
+        deserializer: D,
+    ) -> Result<Self::Value, D::Error> {
+        RawCoseKey::try_deserialize_with(deserializer, self.0)
+    }
+}
+
+struct StoredVisitor;
+
+impl<'de> Visitor<'de> for StoredVisitor {
+    type Value = Result<RawCoseKey, Error>;
+
+    fn expecting(&self, formatter: &mut Formatter) -> fmt::Result {
+        formatter.write_str("a stored key")
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let result = match seq.next_element::<u64>()? {
+            Some(STORAGE_VERSION) => match seq.next_element_seed(KeySeed(ParseOptions::new()))? {
+                Some(result) => result,
+                None => Err(Error::InvalidKey),
+            },
+            Some(version) => Err(Error::UnsupportedVersion(version)),
+            None => Err(Error::InvalidKey),
+        };
+        // elements added by later versions
+        while seq.next_element::<IgnoredAny>()?.is_some() {}
+        Ok(result)
+    }
+}
+
+impl RawCoseKey {
+    /// Serializes the key in the versioned storage format.
+    ///
+    /// Unlike [`to_bytes`][Self::to_bytes], the encoding is not a COSE_Key, but it can be decoded
+    /// by later versions of this crate.  Returns [`Error::BufferTooSmall`] if the key does not
+    /// fit into `N` bytes.
+    pub fn to_stored<const N: usize>(&self) -> Result<Bytes<N>, Error> {
+        ser::to_bytes(&Stored(self.fields()))
+    }
+
+    /// Decodes a key in the storage format written by [`to_stored`][Self::to_stored].
+    ///
+    /// Returns [`Error::UnsupportedVersion`] if the key was stored in a format version that is
+    /// not known to this crate.
+    pub fn from_stored(data: &[u8]) -> Result<Self, Error> {
+        let mut deserializer = Deserializer::new(data, ParseOptions::new());
+        let raw = serde::Deserializer::deserialize_seq(&mut deserializer, StoredVisitor)
+            .map_err(|err| err.0)??;
+        exact((raw, deserializer.offset(data)), data)
+    }
+}
+
+impl PublicKey {
+    /// Serializes the key in the versioned storage format, see [`RawCoseKey::to_stored`].
+    pub fn to_stored<const N: usize>(&self) -> Result<Bytes<N>, Error> {
+        ser::to_bytes(&Stored(self.fields()))
+    }
+
+    /// Decodes a key in the versioned storage format, see [`RawCoseKey::from_stored`].
+    ///
+    /// If the key is valid, but not supported by this version of the crate, the error of
+    /// [`PublicKey::from_raw`] is returned.  Such keys can be decoded with
+    /// [`RawCoseKey::from_stored`].
+    pub fn from_stored(data: &[u8]) -> Result<Self, Error> {
+        Self::from_raw(RawCoseKey::from_stored(data)?, ParseOptions::new())
+    }
+}
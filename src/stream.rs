@@ -0,0 +1,604 @@
+//! Incremental parsing of COSE_Keys and COSE_KeySets
+
+#![deny(clippy::arithmetic_side_effects)]
+
+use heapless::Vec;
+
+use crate::cbor::{self, Argument, Deserializer, BREAK, MAX_DEPTH};
+use crate::map::MapFields as _;
+use crate::{label, Bytes, Error, Key, KeyOp, KeyOpEntry, Label, ParseOptions, PublicKey};
+use crate::{RawCoseKey, RawCoseKeyBuilder, MAX_KEY_OP_LEN};
+
+// The longest text label that is recognized, i.e. the decimal representation of `i64::MIN`.  This
+// is also enough for the text entries of `key_ops`.
+const MAX_TEXT_LABEL: usize = 20;
+
+/// An incremental decoder for COSE_Keys
+///
+/// The decoder can be fed with fragments of the encoded key as they are received, e.g. from
+/// CTAPHID packets.  Only the fields known to this crate are buffered, so the full encoding does
+/// not have to be kept in memory.
+///
+/// After the decoder returned a key or an error, it must not be fed again.
+pub struct StreamDecoder {
+    options: ParseOptions,
+    builder: Option<RawCoseKeyBuilder>,
+    head: Vec<u8, 9>,
+    payload: Payload,
+    // remaining items of the open arrays and maps, `None` for indefinite lengths.  Maps count
+    // keys and values separately.  The COSE_Key is at index 0.
+    stack: Vec<Option<u64>, MAX_DEPTH>,
+    entry: Entry,
+    // whether invalid keys are skipped to their end instead of stopping the decoder, see
+    // `KeySetDecoder`
+    recover: bool,
+    // the first error of the key if it is skipped
+    error: Option<Error>,
+}
+
+// The decoded key, or the error if it was skipped
+type Decoded = Result<RawCoseKey, Error>;
+
+// The remaining bytes of the byte or text string that is being read
+enum Payload {
+    None,
+    Skip(u64),
+    Bytes {
+        label: Label,
+        bytes: Bytes<32>,
+        remaining: usize,
+    },
+    // a text label or a text entry of `key_ops`
+    Text {
+        text: Vec<u8, MAX_TEXT_LABEL>,
+        remaining: usize,
+    },
+}
+
+// The next item in the COSE_Key map
+#[derive(Clone, Copy)]
+enum Entry {
+    Key,
+    // the label of the field, or `None` if the value is skipped
+    Value(Option<Label>),
+}
+
+impl StreamDecoder {
+    /// Creates a decoder using the given options.
+    pub fn new(options: ParseOptions) -> Self {
+        Self {
+            options,
+            builder: Some(RawCoseKeyBuilder::new(options)),
+            head: Vec::new(),
+            payload: Payload::None,
+            stack: Vec::new(),
+            entry: Entry::Key,
+            recover: false,
+            error: None,
+        }
+    }
+
+    // Creates a decoder that skips invalid keys to their end and returns their error with their
+    // length.  Only malformed CBOR stops the decoder.
+    fn recovering(options: ParseOptions) -> Self {
+        Self {
+            recover: true,
+            ..Self::new(options)
+        }
+    }
+
+    /// Feeds the next fragment of the encoded key into the decoder.
+    ///
+    /// Returns `None` if more data is required.  Once the key is complete, it is returned
+    /// together with the number of bytes of `data` that belong to the key.
+    pub fn feed(&mut self, data: &[u8]) -> Result<Option<(PublicKey, usize)>, Error> {
+        let options = self.options;
+        self.feed_raw(data)?
+            .map(|(raw, len)| Ok((PublicKey::from_raw(raw, options)?, len)))
+            .transpose()
+    }
+
+    // Like `feed`, but returns the key without checking that it is supported.
+    pub(crate) fn feed_raw(&mut self, data: &[u8]) -> Result<Option<(RawCoseKey, usize)>, Error> {
+        self.feed_decoded(data)?
+            .map(|(decoded, len)| decoded.map(|raw| (raw, len)))
+            .transpose()
+    }
+
+    // Like `feed_raw`, but returns the error of an invalid key with its length if the decoder is
+    // recovering.
+    fn feed_decoded(&mut self, data: &[u8]) -> Result<Option<(Decoded, usize)>, Error> {
+        if self.builder.is_none() {
+            return Err(Error::InvalidKey);
+        }
+        let result = self.try_feed(data);
+        if !matches!(result, Ok(None)) {
+            self.builder = None;
+        }
+        result
+    }
+
+    fn try_feed(&mut self, data: &[u8]) -> Result<Option<(Decoded, usize)>, Error> {
+        let mut rest = data;
+        while let Some((&byte, tail)) = rest.split_first() {
+            let raw = match &mut self.payload {
+                Payload::None => {
+                    rest = tail;
+                    self.head.push(byte).map_err(|_| Error::InvalidKey)?;
+                    let initial = self.head.first().copied().unwrap_or(byte);
+                    if self.head.len() < cbor::head_len(initial) {
+                        continue;
+                    }
+                    let mut deserializer = Deserializer::new(&self.head, self.options);
+                    let (major, info, argument) = deserializer.head()?;
+                    self.head.clear();
+                    self.item(major, info, argument)?
+                }
+                Payload::Skip(remaining) => {
+                    let (chunk, tail) =
+                        split(rest, usize::try_from(*remaining).unwrap_or(usize::MAX));
+                    rest = tail;
+                    *remaining = remaining.saturating_sub(chunk.len() as u64);
+                    if *remaining > 0 {
+                        continue;
+                    }
+                    self.payload = Payload::None;
+                    self.item_done()?
+                }
+                Payload::Bytes {
+                    bytes, remaining, ..
+                } => {
+                    let (chunk, tail) = split(rest, *remaining);
+                    rest = tail;
+                    bytes
+                        .extend_from_slice(chunk)
+                        .map_err(|()| Error::InvalidKey)?;
+                    *remaining = remaining.saturating_sub(chunk.len());
+                    if *remaining > 0 {
+                        continue;
+                    }
+                    self.bytes_done()?
+                }
+                Payload::Text { text, remaining } => {
+                    let (chunk, tail) = split(rest, *remaining);
+                    rest = tail;
+                    text.extend_from_slice(chunk)
+                        .map_err(|()| Error::InvalidKey)?;
+                    *remaining = remaining.saturating_sub(chunk.len());
+                    if *remaining > 0 {
+                        continue;
+                    }
+                    self.text_done()?
+                }
+            };
+            if let Some(raw) = raw {
+                return Ok(Some((raw, data.len().saturating_sub(rest.len()))));
+            }
+        }
+        Ok(None)
+    }
+
+    // The nesting depth of the next data item.  The COSE_Key is at depth 0, the keys and values
+    // of its fields at depth 1.
+    fn depth(&self) -> usize {
+        self.stack.len()
+    }
+
+    fn builder(&mut self) -> Result<&mut RawCoseKeyBuilder, Error> {
+        self.builder.as_mut().ok_or(Error::InvalidKey)
+    }
+
+    // Processes the head of a data item.  Returns the key if it is complete.
+    fn item(&mut self, major: u8, info: u8, argument: Argument) -> Result<Option<Decoded>, Error> {
+        if let (6, Argument::Value(tag)) = (major, argument) {
+            if let Err(err) = cbor::check_tag(self.options.tags, self.depth(), tag) {
+                self.fail(err)?;
+            }
+            return Ok(None);
+        }
+        if let (2 | 3, Argument::Value(len)) = (major, argument) {
+            if let Err(err) = cbor::check_string_len(self.options.max_string_len, len) {
+                self.fail(err)?;
+            }
+        }
+        if self.depth() == 0 {
+            return match (major, argument) {
+                (5, argument) => self.open(entries(argument)?),
+                _ => Err(Error::InvalidKey),
+            };
+        }
+        if (major, info) == (7, BREAK & 0x1f) {
+            return self.close();
+        }
+        if self.error.is_some() || (self.depth() > 1 && !self.in_key_ops()) {
+            return self.skip(major, argument);
+        }
+        if self.depth() > 1 {
+            return self.key_op(major, argument);
+        }
+
+        match self.entry {
+            Entry::Key => match (major, argument) {
+                (0 | 1, argument) => {
+                    let key = Key::Label(cbor::int(major, argument)?);
+                    self.key(key)
+                }
+                (3, Argument::Value(len)) if self.options.text_labels => {
+                    match usize::try_from(len) {
+                        Ok(len) if len <= MAX_TEXT_LABEL => {
+                            self.payload = Payload::Text {
+                                text: Vec::new(),
+                                remaining: len,
+                            };
+                            if len == 0 {
+                                return self.text_done();
+                            }
+                            Ok(None)
+                        }
+                        // too long for a known label, so the key is unknown
+                        _ => {
+                            if let Err(err) = self.builder()?.key(Key::UnknownText) {
+                                self.fail(err)?;
+                            }
+                            self.skip(major, argument)
+                        }
+                    }
+                }
+                _ => self.reject(Error::InvalidKey, major, argument),
+            },
+            Entry::Value(Some(label @ (Label::Kty | Label::Alg | Label::Crv))) => {
+                let result = match major {
+                    0 | 1 => cbor::int(major, argument)
+                        .and_then(|value| self.builder()?.int(label, value)),
+                    _ => return self.reject(Error::InvalidKey, major, argument),
+                };
+                if let Err(err) = result {
+                    self.fail(err)?;
+                }
+                self.item_done()
+            }
+            Entry::Value(Some(Label::KeyOps)) => match (major, argument) {
+                (4, argument) => {
+                    self.builder()?.start_key_ops();
+                    self.open(match argument {
+                        Argument::Value(len) => Some(len),
+                        Argument::Indefinite => None,
+                    })
+                }
+                _ => self.reject(Error::InvalidKey, major, argument),
+            },
+            Entry::Value(Some(label @ (Label::X | Label::Y))) => {
+                let len = match (major, argument) {
+                    (2, Argument::Value(len)) => len,
+                    _ => return self.reject(Error::InvalidKey, major, argument),
+                };
+                // reject long byte strings before reading them
+                let len = match usize::try_from(len).ok().filter(|len| *len <= 32) {
+                    Some(len) => len,
+                    None => {
+                        let err = Error::InvalidLength {
+                            label: label as i8,
+                            expected: 32,
+                            actual: usize::try_from(len).unwrap_or(usize::MAX),
+                        };
+                        return self.reject(err, major, argument);
+                    }
+                };
+                self.payload = Payload::Bytes {
+                    label,
+                    bytes: Bytes::new(),
+                    remaining: len,
+                };
+                if len == 0 {
+                    return self.bytes_done();
+                }
+                Ok(None)
+            }
+            Entry::Value(None) => self.skip(major, argument),
+        }
+    }
+
+    // Returns whether the next data item is an entry of `key_ops`.
+    fn in_key_ops(&self) -> bool {
+        self.depth() == 2 && matches!(self.entry, Entry::Value(Some(Label::KeyOps)))
+    }
+
+    // Processes the head of an entry of `key_ops`.
+    fn key_op(&mut self, major: u8, argument: Argument) -> Result<Option<Decoded>, Error> {
+        match (major, argument) {
+            (0 | 1, argument) => {
+                let result = cbor::int(major, argument).and_then(|value| {
+                    let op = KeyOp::try_from(value).map_err(|_| Error::UnsupportedValue {
+                        label: label::KEY_OPS,
+                        value,
+                    })?;
+                    self.builder()?.key_op(KeyOpEntry::Int(op))
+                });
+                if let Err(err) = result {
+                    self.fail(err)?;
+                }
+                self.item_done()
+            }
+            (3, Argument::Value(len)) => match usize::try_from(len) {
+                Ok(len) if len <= MAX_KEY_OP_LEN => {
+                    self.payload = Payload::Text {
+                        text: Vec::new(),
+                        remaining: len,
+                    };
+                    if len == 0 {
+                        return self.text_done();
+                    }
+                    Ok(None)
+                }
+                _ => self.reject(Error::KeyOpsTooLong, major, argument),
+            },
+            _ => self.reject(Error::InvalidKey, major, argument),
+        }
+    }
+
+    fn key(&mut self, key: Key) -> Result<Option<Decoded>, Error> {
+        let label = match self.builder()?.key(key) {
+            Ok(label) => label,
+            Err(err) => {
+                self.fail(err)?;
+                None
+            }
+        };
+        self.item_done()?;
+        self.entry = Entry::Value(label);
+        Ok(None)
+    }
+
+    fn bytes_done(&mut self) -> Result<Option<Decoded>, Error> {
+        if let Payload::Bytes { label, bytes, .. } =
+            core::mem::replace(&mut self.payload, Payload::None)
+        {
+            if let Err(err) = self.builder()?.bytes(label, bytes) {
+                self.fail(err)?;
+            }
+        }
+        self.item_done()
+    }
+
+    fn text_done(&mut self) -> Result<Option<Decoded>, Error> {
+        if let Payload::Text { text, .. } = core::mem::replace(&mut self.payload, Payload::None) {
+            let Ok(text) = core::str::from_utf8(&text) else {
+                self.fail(Error::InvalidKey)?;
+                return self.item_done();
+            };
+            if self.depth() > 1 {
+                let result = KeyOpEntry::text(text)
+                    .ok_or(Error::KeyOpsTooLong)
+                    .and_then(|entry| self.builder()?.key_op(entry));
+                if let Err(err) = result {
+                    self.fail(err)?;
+                }
+                return self.item_done();
+            }
+            return self.key(Key::from_text(text));
+        }
+        Ok(None)
+    }
+
+    // Returns the error if the decoder is not recovering, and records it as the error of the key
+    // otherwise.
+    fn fail(&mut self, err: Error) -> Result<(), Error> {
+        if !self.recover {
+            return Err(err);
+        }
+        self.error.get_or_insert(err);
+        Ok(())
+    }
+
+    // Like `fail`, but also skips the data item.
+    fn reject(
+        &mut self,
+        err: Error,
+        major: u8,
+        argument: Argument,
+    ) -> Result<Option<Decoded>, Error> {
+        self.fail(err)?;
+        self.skip(major, argument)
+    }
+
+    // Skips a data item that is not needed for the key.
+    fn skip(&mut self, major: u8, argument: Argument) -> Result<Option<Decoded>, Error> {
+        match (major, argument) {
+            (0 | 1 | 7, Argument::Value(_)) => self.item_done(),
+            (2 | 3, Argument::Value(len)) => {
+                if len == 0 {
+                    return self.item_done();
+                }
+                self.payload = Payload::Skip(len);
+                Ok(None)
+            }
+            (4, argument) => self.open(match argument {
+                Argument::Value(len) => Some(len),
+                Argument::Indefinite => None,
+            }),
+            (5, argument) => self.open(entries(argument)?),
+            // indefinite-length strings
+            _ => Err(Error::InvalidKey),
+        }
+    }
+
+    fn open(&mut self, remaining: Option<u64>) -> Result<Option<Decoded>, Error> {
+        if remaining == Some(0) {
+            // an empty container is complete immediately
+            return if self.depth() == 0 {
+                self.finish()
+            } else {
+                self.item_done()
+            };
+        }
+        // nested too deeply
+        self.stack.push(remaining).map_err(|_| Error::InvalidKey)?;
+        Ok(None)
+    }
+
+    fn close(&mut self) -> Result<Option<Decoded>, Error> {
+        let is_indefinite = matches!(self.stack.last(), Some(None));
+        let is_value = self.depth() == 1 && matches!(self.entry, Entry::Value(_));
+        if !is_indefinite || is_value {
+            return Err(Error::InvalidKey);
+        }
+        self.stack.pop();
+        if self.depth() == 0 {
+            self.finish()
+        } else {
+            self.item_done()
+        }
+    }
+
+    // Marks the current item as complete, closing the containers that are complete.
+    fn item_done(&mut self) -> Result<Option<Decoded>, Error> {
+        loop {
+            if self.depth() == 1 {
+                self.entry = match self.entry {
+                    Entry::Key => Entry::Value(None),
+                    Entry::Value(_) => Entry::Key,
+                };
+            }
+            match self.stack.last_mut() {
+                Some(Some(remaining)) => {
+                    *remaining = remaining.saturating_sub(1);
+                    if *remaining > 0 {
+                        return Ok(None);
+                    }
+                }
+                Some(None) => return Ok(None),
+                None => return Err(Error::InvalidKey),
+            }
+            self.stack.pop();
+            if self.depth() == 0 {
+                return self.finish();
+            }
+        }
+    }
+
+    fn finish(&mut self) -> Result<Option<Decoded>, Error> {
+        let builder = self.builder.take().ok_or(Error::InvalidKey)?;
+        Ok(Some(match self.error.take() {
+            Some(err) => Err(err),
+            None => builder.finish(),
+        }))
+    }
+}
+
+/// An incremental decoder for COSE_KeySets
+///
+/// Like [`StreamDecoder`], the decoder can be fed with fragments of the encoded key set.  It
+/// returns the keys one at a time as soon as they are complete, so only the key that is being
+/// decoded is buffered and key sets larger than the available memory can be processed.
+///
+/// After the decoder returned an error, it must not be fed again.
+pub struct KeySetDecoder {
+    options: ParseOptions,
+    head: Vec<u8, 9>,
+    // remaining keys, `None` for indefinite lengths.  `None` until the array head is read.
+    remaining: Option<Option<u64>>,
+    key: StreamDecoder,
+    // whether the current key has been fed data
+    in_key: bool,
+    finished: bool,
+}
+
+impl KeySetDecoder {
+    /// Creates a decoder using the given options for the keys.
+    pub fn new(options: ParseOptions) -> Self {
+        Self {
+            options,
+            head: Vec::new(),
+            remaining: None,
+            key: StreamDecoder::recovering(options),
+            in_key: false,
+            finished: false,
+        }
+    }
+
+    /// Feeds the next fragment of the encoded key set into the decoder.
+    ///
+    /// Returns the next key together with the number of bytes of `data` that were consumed.  The
+    /// remaining bytes must be fed again to obtain the following keys.  Returns `None` if all of
+    /// `data` was consumed without completing a key, i.e. if more data is required or if the key
+    /// set is [finished][Self::is_finished].
+    ///
+    /// Keys that are invalid or not supported by [`PublicKey`], e.g. RSA keys or keys with an
+    /// unknown algorithm, are returned as their error, and decoding continues with the next key.
+    /// Only errors of the key set itself, e.g. malformed CBOR, are returned as the outer error.
+    ///
+    /// Data after the end of the key set is reported as [`Error::TrailingData`] with the number
+    /// of bytes of `data` that belong to the key set.
+    #[allow(clippy::type_complexity)]
+    pub fn feed(
+        &mut self,
+        data: &[u8],
+    ) -> Result<Option<(Result<PublicKey, Error>, usize)>, Error> {
+        let mut rest = data;
+        loop {
+            let consumed = data.len().saturating_sub(rest.len());
+            if self.finished {
+                if rest.is_empty() {
+                    return Ok(None);
+                }
+                return Err(Error::TrailingData { consumed });
+            }
+            let Some((&byte, tail)) = rest.split_first() else {
+                return Ok(None);
+            };
+            let Some(remaining) = self.remaining else {
+                rest = tail;
+                self.head.push(byte).map_err(|_| Error::InvalidKey)?;
+                let initial = self.head.first().copied().unwrap_or(byte);
+                if self.head.len() < cbor::head_len(initial) {
+                    continue;
+                }
+                let mut deserializer = Deserializer::new(&self.head, self.options);
+                let remaining = match deserializer.head()? {
+                    (4, _, Argument::Value(len)) => Some(len),
+                    (4, _, Argument::Indefinite) => None,
+                    _ => return Err(Error::InvalidKey),
+                };
+                self.head.clear();
+                self.remaining = Some(remaining);
+                self.finished = remaining == Some(0);
+                continue;
+            };
+            if remaining.is_none() && !self.in_key && byte == BREAK {
+                rest = tail;
+                self.finished = true;
+                continue;
+            }
+            self.in_key = true;
+            let Some((decoded, len)) = self.key.feed_decoded(rest)? else {
+                return Ok(None);
+            };
+            let key = decoded.and_then(|raw| PublicKey::from_raw(raw, self.options));
+            self.key = StreamDecoder::recovering(self.options);
+            self.in_key = false;
+            if let Some(Some(remaining)) = &mut self.remaining {
+                *remaining = remaining.saturating_sub(1);
+                self.finished = *remaining == 0;
+            }
+            return Ok(Some((key, consumed.saturating_add(len))));
+        }
+    }
+
+    /// Returns whether the end of the key set has been read.
+    pub fn is_finished(&self) -> bool {
+        self.finished
+    }
+}
+
+// Splits off the first `n` bytes of `data`, or all of them if `data` is shorter.
+fn split(data: &[u8], n: usize) -> (&[u8], &[u8]) {
+    data.split_at_checked(n).unwrap_or((data, &[]))
+}
+
+// The number of keys and values of a map
+fn entries(argument: Argument) -> Result<Option<u64>, Error> {
+    match argument {
+        Argument::Value(len) => len.checked_mul(2).map(Some).ok_or(Error::InvalidKey),
+        Argument::Indefinite => Ok(None),
+    }
+}
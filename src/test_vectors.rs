@@ -0,0 +1,110 @@
+//! Example keys from the specifications
+//!
+//! The keys are given as typed constants together with their canonical CTAP2 encoding, which
+//! includes the algorithm even if the example in the specification does not.
+
+#[cfg(feature = "ed25519")]
+use crate::Ed25519PublicKey;
+#[cfg(feature = "p256")]
+use crate::P256PublicKey;
+
+/// The P-256 key of `meriadoc.brandybuck@buckland.example` from RFC 9052, appendix C.7.1
+#[cfg(feature = "p256")]
+pub const RFC9052_MERIADOC: P256PublicKey = P256PublicKey {
+    x: [
+        0x65, 0xed, 0xa5, 0xa1, 0x25, 0x77, 0xc2, 0xba, 0xe8, 0x29, 0x43, 0x7f, 0xe3, 0x38, 0x70,
+        0x1a, 0x10, 0xaa, 0xa3, 0x75, 0xe1, 0xbb, 0x5b, 0x5d, 0xe1, 0x08, 0xde, 0x43, 0x9c, 0x08,
+        0x55, 0x1d,
+    ],
+    y: [
+        0x1e, 0x52, 0xed, 0x75, 0x70, 0x11, 0x63, 0xf7, 0xf9, 0xe4, 0x0d, 0xdf, 0x9f, 0x34, 0x1b,
+        0x3d, 0xc9, 0xba, 0x86, 0x0a, 0xf7, 0xe0, 0xca, 0x7c, 0xa7, 0xe9, 0xee, 0xcd, 0x00, 0x84,
+        0xd1, 0x9c,
+    ],
+};
+
+/// The canonical encoding of [`RFC9052_MERIADOC`]
+#[cfg(feature = "p256")]
+pub const RFC9052_MERIADOC_ENCODED: [u8; 77] = [
+    0xa5, 0x01, 0x02, 0x03, 0x26, 0x20, 0x01, 0x21, 0x58, 0x20, 0x65, 0xed, 0xa5, 0xa1, 0x25, 0x77,
+    0xc2, 0xba, 0xe8, 0x29, 0x43, 0x7f, 0xe3, 0x38, 0x70, 0x1a, 0x10, 0xaa, 0xa3, 0x75, 0xe1, 0xbb,
+    0x5b, 0x5d, 0xe1, 0x08, 0xde, 0x43, 0x9c, 0x08, 0x55, 0x1d, 0x22, 0x58, 0x20, 0x1e, 0x52, 0xed,
+    0x75, 0x70, 0x11, 0x63, 0xf7, 0xf9, 0xe4, 0x0d, 0xdf, 0x9f, 0x34, 0x1b, 0x3d, 0xc9, 0xba, 0x86,
+    0x0a, 0xf7, 0xe0, 0xca, 0x7c, 0xa7, 0xe9, 0xee, 0xcd, 0x00, 0x84, 0xd1, 0x9c,
+];
+
+/// The example EC2 credential public key from the WebAuthn specification
+#[cfg(feature = "p256")]
+pub const WEBAUTHN_ES256: P256PublicKey = RFC9052_MERIADOC;
+
+/// The canonical encoding of [`WEBAUTHN_ES256`]
+#[cfg(feature = "p256")]
+pub const WEBAUTHN_ES256_ENCODED: [u8; 77] = RFC9052_MERIADOC_ENCODED;
+
+/// The P-256 key with the key ID `11` from RFC 9052, appendix C.7.1
+#[cfg(feature = "p256")]
+pub const RFC9052_KID_11: P256PublicKey = P256PublicKey {
+    x: [
+        0xba, 0xc5, 0xb1, 0x1c, 0xad, 0x8f, 0x99, 0xf9, 0xc7, 0x2b, 0x05, 0xcf, 0x4b, 0x9e, 0x26,
+        0xd2, 0x44, 0xdc, 0x18, 0x9f, 0x74, 0x52, 0x28, 0x25, 0x5a, 0x21, 0x9a, 0x86, 0xd6, 0xa0,
+        0x9e, 0xff,
+    ],
+    y: [
+        0x20, 0x13, 0x8b, 0xf8, 0x2d, 0xc1, 0xb6, 0xd5, 0x62, 0xbe, 0x0f, 0xa5, 0x4a, 0xb7, 0x80,
+        0x4a, 0x3a, 0x64, 0xb6, 0xd7, 0x2c, 0xcf, 0xed, 0x6b, 0x6f, 0xb6, 0xed, 0x28, 0xbb, 0xfc,
+        0x11, 0x7e,
+    ],
+};
+
+/// The canonical encoding of [`RFC9052_KID_11`]
+#[cfg(feature = "p256")]
+pub const RFC9052_KID_11_ENCODED: [u8; 77] = [
+    0xa5, 0x01, 0x02, 0x03, 0x26, 0x20, 0x01, 0x21, 0x58, 0x20, 0xba, 0xc5, 0xb1, 0x1c, 0xad, 0x8f,
+    0x99, 0xf9, 0xc7, 0x2b, 0x05, 0xcf, 0x4b, 0x9e, 0x26, 0xd2, 0x44, 0xdc, 0x18, 0x9f, 0x74, 0x52,
+    0x28, 0x25, 0x5a, 0x21, 0x9a, 0x86, 0xd6, 0xa0, 0x9e, 0xff, 0x22, 0x58, 0x20, 0x20, 0x13, 0x8b,
+    0xf8, 0x2d, 0xc1, 0xb6, 0xd5, 0x62, 0xbe, 0x0f, 0xa5, 0x4a, 0xb7, 0x80, 0x4a, 0x3a, 0x64, 0xb6,
+    0xd7, 0x2c, 0xcf, 0xed, 0x6b, 0x6f, 0xb6, 0xed, 0x28, 0xbb, 0xfc, 0x11, 0x7e,
+];
+
+/// The P-256 key of `peregrin.took@tuckborough.example` from RFC 9052, appendix C.7.1
+#[cfg(feature = "p256")]
+pub const RFC9052_PEREGRIN: P256PublicKey = P256PublicKey {
+    x: [
+        0x98, 0xf5, 0x0a, 0x4f, 0xf6, 0xc0, 0x58, 0x61, 0xc8, 0x86, 0x0d, 0x13, 0xa6, 0x38, 0xea,
+        0x56, 0xc3, 0xf5, 0xad, 0x75, 0x90, 0xbb, 0xfb, 0xf0, 0x54, 0xe1, 0xc7, 0xb4, 0xd9, 0x1d,
+        0x62, 0x80,
+    ],
+    y: [
+        0xf0, 0x14, 0x00, 0xb0, 0x89, 0x86, 0x78, 0x04, 0xb8, 0xe9, 0xfc, 0x96, 0xc3, 0x93, 0x21,
+        0x61, 0xf1, 0x93, 0x4f, 0x42, 0x23, 0x06, 0x91, 0x70, 0xd9, 0x24, 0xb7, 0xe0, 0x3b, 0xf8,
+        0x22, 0xbb,
+    ],
+};
+
+/// The canonical encoding of [`RFC9052_PEREGRIN`]
+#[cfg(feature = "p256")]
+pub const RFC9052_PEREGRIN_ENCODED: [u8; 77] = [
+    0xa5, 0x01, 0x02, 0x03, 0x26, 0x20, 0x01, 0x21, 0x58, 0x20, 0x98, 0xf5, 0x0a, 0x4f, 0xf6, 0xc0,
+    0x58, 0x61, 0xc8, 0x86, 0x0d, 0x13, 0xa6, 0x38, 0xea, 0x56, 0xc3, 0xf5, 0xad, 0x75, 0x90, 0xbb,
+    0xfb, 0xf0, 0x54, 0xe1, 0xc7, 0xb4, 0xd9, 0x1d, 0x62, 0x80, 0x22, 0x58, 0x20, 0xf0, 0x14, 0x00,
+    0xb0, 0x89, 0x86, 0x78, 0x04, 0xb8, 0xe9, 0xfc, 0x96, 0xc3, 0x93, 0x21, 0x61, 0xf1, 0x93, 0x4f,
+    0x42, 0x23, 0x06, 0x91, 0x70, 0xd9, 0x24, 0xb7, 0xe0, 0x3b, 0xf8, 0x22, 0xbb,
+];
+
+/// The Ed25519 public key of TEST 1 from RFC 8032, section 7.1
+#[cfg(feature = "ed25519")]
+pub const RFC8032_TEST_1: Ed25519PublicKey = Ed25519PublicKey {
+    x: [
+        0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1, 0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64, 0x07,
+        0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6, 0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68, 0xf7, 0x07,
+        0x51, 0x1a,
+    ],
+};
+
+/// The canonical encoding of [`RFC8032_TEST_1`]
+#[cfg(feature = "ed25519")]
+pub const RFC8032_TEST_1_ENCODED: [u8; 42] = [
+    0xa4, 0x01, 0x01, 0x03, 0x27, 0x20, 0x06, 0x21, 0x58, 0x20, 0xd7, 0x5a, 0x98, 0x01, 0x82, 0xb1,
+    0x0a, 0xb7, 0xd5, 0x4b, 0xfe, 0xd3, 0xc9, 0x64, 0x07, 0x3a, 0x0e, 0xe1, 0x72, 0xf3, 0xda, 0xa6,
+    0x23, 0x25, 0xaf, 0x02, 0x1a, 0x68, 0xf7, 0x07, 0x51, 0x1a,
+];
@@ -0,0 +1,45 @@
+#![cfg(feature = "arbitrary")]
+
+use arbitrary::{Arbitrary, Unstructured};
+use cosey::Alg;
+#[cfg(feature = "serde")]
+use cosey::{PublicKey, RawCoseKey};
+
+#[cfg(feature = "serde")]
+quickcheck::quickcheck! {
+    fn public_key_roundtrip(data: Vec<u8>) -> bool {
+        let mut u = Unstructured::new(&data);
+        let Ok(key) = PublicKey::arbitrary(&mut u) else {
+            return true;
+        };
+        let encoded = serde_cbor::to_vec(&key).unwrap();
+        serde_cbor::from_slice::<PublicKey>(&encoded).unwrap() == key
+    }
+
+    fn raw_key_roundtrip(data: Vec<u8>) -> bool {
+        let mut u = Unstructured::new(&data);
+        let Ok(key) = RawCoseKey::arbitrary(&mut u) else {
+            return true;
+        };
+        let encoded = serde_cbor::to_vec(&key).unwrap();
+        serde_cbor::from_slice::<RawCoseKey>(&encoded).unwrap() == key
+    }
+}
+
+#[test]
+fn variants() {
+    #[cfg(feature = "p256")]
+    assert_eq!(
+        cosey::PublicKey::arbitrary(&mut Unstructured::new(&[0; 64])).unwrap(),
+        cosey::PublicKey::P256Key(cosey::P256PublicKey {
+            x: [0; 32],
+            y: [0; 32]
+        })
+    );
+
+    // the first byte selects between listed and other values
+    let mut u = Unstructured::new(&[0x01, 0x00]);
+    assert_eq!(Alg::arbitrary(&mut u).unwrap(), Alg::Rs1);
+    let mut u = Unstructured::new(&[0x00, 0x12, 0x34, 0x56, 0x78]);
+    assert!(matches!(Alg::arbitrary(&mut u).unwrap(), Alg::Other(_)));
+}
@@ -0,0 +1,521 @@
+#![cfg(all(
+    feature = "cbor",
+    feature = "p256",
+    feature = "ed25519",
+    feature = "serde"
+))]
+
+use core::mem::MaybeUninit;
+
+use cosey::{
+    Alg, Error, KeyOp, KeyOpEntry, KeySetDecoder, Kty, P256PublicKey, ParseOptions, PublicKey,
+    RawCoseKey, StreamDecoder,
+};
+
+const P256_KEY: &str = "a5010203262001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+// ED25519_KEY with key_ops: ["verify", 2]
+const ED25519_KEY_OPS: &str =
+    "a501010327048266766572696679022006215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+const ED25519_KEY: &str =
+    "a4010103272006215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+
+#[test]
+fn peek() {
+    let key = hex::decode(P256_KEY).unwrap();
+    assert_eq!(cosey::peek_kty(&key), Ok(Kty::Ec2));
+    assert_eq!(cosey::peek_alg(&key), Ok(Some(Alg::Es256)));
+
+    let key = hex::decode(ED25519_KEY).unwrap();
+    assert_eq!(cosey::peek_kty(&key), Ok(Kty::Okp));
+    assert_eq!(cosey::peek_alg(&key), Ok(Some(Alg::EdDsa)));
+
+    // the key material is not read
+    let key = hex::decode("a40102032620015820ff").unwrap();
+    assert_eq!(cosey::peek_alg(&key), Ok(Some(Alg::Es256)));
+    // without alg
+    let key = hex::decode("a3010220015820ff").unwrap();
+    assert_eq!(cosey::peek_kty(&key), Ok(Kty::Ec2));
+    assert_eq!(cosey::peek_alg(&key), Ok(None));
+    // unknown fields are skipped
+    let key = hex::decode("a4004100010218638102032620").unwrap();
+    assert_eq!(cosey::peek_alg(&key), Ok(Some(Alg::Es256)));
+    // algorithms that are not supported by the key types
+    let key = hex::decode("a20103033824").unwrap();
+    assert_eq!(cosey::peek_kty(&key), Ok(Kty::Rsa));
+    assert_eq!(cosey::peek_alg(&key), Ok(Some(Alg::Ps256)));
+    let key = hex::decode("a201020339012b").unwrap();
+    assert_eq!(cosey::peek_alg(&key), Ok(Some(Alg::Other(-300))));
+}
+
+#[test]
+fn peek_errors() {
+    let peek = |key: &str| cosey::peek_alg(&hex::decode(key).unwrap());
+
+    assert_eq!(peek(""), Err(Error::Truncated));
+    assert_eq!(peek("a20102"), Err(Error::Truncated));
+    assert_eq!(peek("80"), Err(Error::InvalidKey));
+    assert_eq!(peek("a0"), Err(Error::MissingField("kty")));
+    assert_eq!(peek("a203260102"), Err(Error::NonCanonicalOrder));
+    // values that do not fit into an i32
+    assert_eq!(
+        peek("a2011b0000000100000000"),
+        Err(Error::UnsupportedValue {
+            label: 1,
+            value: 0x1_0000_0000
+        })
+    );
+    assert_eq!(
+        peek("a20102033b0000000100000000"),
+        Err(Error::UnsupportedValue {
+            label: 3,
+            value: -0x1_0000_0001
+        })
+    );
+}
+
+#[test]
+fn from_slice_exact() {
+    let key = P256PublicKey::from_coordinates([0xff; 32], [0xff; 32]);
+    let mut data = hex::decode(P256_KEY).unwrap();
+    let len = data.len();
+    let options = ParseOptions::new();
+
+    assert_eq!(PublicKey::from_slice_exact(&data, options), Ok(key.into()));
+    assert_eq!(RawCoseKey::from_slice_exact(&data, options), Ok(key.into()));
+    assert_eq!(
+        PublicKey::take_from_slice(&data, options),
+        Ok((key.into(), len))
+    );
+
+    data.extend_from_slice(&[0xa0, 0x00]);
+    assert_eq!(
+        PublicKey::from_slice_exact(&data, options),
+        Err(Error::TrailingData { consumed: len })
+    );
+    assert_eq!(
+        RawCoseKey::from_slice_exact(&data, options),
+        Err(Error::TrailingData { consumed: len })
+    );
+    assert_eq!(
+        PublicKey::take_from_slice(&data, options),
+        Ok((key.into(), len))
+    );
+    assert_eq!(
+        RawCoseKey::take_from_slice(&data, options),
+        Ok((key.into(), len))
+    );
+}
+
+#[test]
+fn require_alg() {
+    // kty: EC2, crv: P-256, x, y
+    let data = hex::decode("a401022001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap();
+    assert!(PublicKey::from_slice_exact(&data, ParseOptions::new()).is_ok());
+    assert_eq!(
+        PublicKey::from_slice_exact(&data, ParseOptions::new().require_alg(true)),
+        Err(Error::MissingField("alg"))
+    );
+}
+
+#[test]
+fn key_ops() {
+    let data = hex::decode(ED25519_KEY_OPS).unwrap();
+    let options = ParseOptions::new().deny_unknown_fields(true);
+    let raw = RawCoseKey::from_slice_exact(&data, options).unwrap();
+    let expected = [
+        KeyOpEntry::text("verify").unwrap(),
+        KeyOpEntry::Int(KeyOp::Verify),
+    ];
+    assert_eq!(raw.key_ops.as_deref(), Some(&expected[..]));
+    assert_eq!(raw.to_bytes::<128>().unwrap().as_slice(), data);
+    assert_eq!(raw.encoded_len(), data.len());
+    assert_eq!(
+        PublicKey::from_slice_exact(&data, options),
+        PublicKey::from_slice(&hex::decode(ED25519_KEY).unwrap())
+    );
+    // the key is verify-only, which permits signatures
+    assert!(PublicKey::from_slice_exact(&data, options.enforce_key_ops(true)).is_ok());
+
+    // kty: OKP, key_ops with the given entries
+    let key = |key_ops: &str| hex::decode(format!("a2010104{key_ops}")).unwrap();
+    let from_slice = |key_ops: &str| RawCoseKey::from_slice_exact(&key(key_ops), options);
+    let many = format!("8b{}", "01".repeat(11));
+    let long = format!("8171{}", "61".repeat(17));
+    let errors = [
+        ("80", Error::InvalidKey),
+        (many.as_str(), Error::KeyOpsTooLong),
+        (long.as_str(), Error::KeyOpsTooLong),
+        (
+            "811b0000010000000000",
+            Error::UnsupportedValue {
+                label: 4,
+                value: 1 << 40,
+            },
+        ),
+        ("8140", Error::InvalidKey),
+        ("818101", Error::InvalidKey),
+        ("01", Error::InvalidKey),
+    ];
+    for (key_ops, error) in errors {
+        assert_eq!(from_slice(key_ops), Err(error), "{key_ops}");
+        let mut decoder = StreamDecoder::new(options);
+        assert_eq!(decoder.feed(&key(key_ops)), Err(error), "{key_ops}");
+    }
+}
+
+#[test]
+fn stream_decoder() {
+    let options = ParseOptions::new().text_labels(true);
+    let keys = [
+        P256_KEY,
+        ED25519_KEY,
+        // indefinite map with unknown nested fields
+        "bf010103272006215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff389f9fa10142ffffffff",
+        // text labels
+        "a4636b74790163616c6727636372760661785820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        ED25519_KEY_OPS,
+    ];
+    for key in keys {
+        let mut data = hex::decode(key).unwrap();
+        let expected = PublicKey::take_from_slice(&data, options);
+        assert!(expected.is_ok(), "{key}");
+        data.extend_from_slice(&[0xa0, 0x00]);
+        for chunk_size in [1, 3, 7, 64] {
+            let mut decoder = StreamDecoder::new(options);
+            let mut result = Ok(None);
+            let mut offset = 0;
+            for chunk in data.chunks(chunk_size) {
+                result = decoder.feed(chunk);
+                if !matches!(result, Ok(None)) {
+                    break;
+                }
+                offset += chunk.len();
+            }
+            let result = result.map(|key| key.map(|(key, len)| (key, offset + len)));
+            assert_eq!(result.transpose().unwrap(), expected, "{key} {chunk_size}");
+        }
+    }
+}
+
+#[test]
+fn stream_decoder_errors() {
+    let feed = |key: &str| StreamDecoder::new(ParseOptions::new()).feed(&hex::decode(key).unwrap());
+
+    assert_eq!(feed(""), Ok(None));
+    assert_eq!(feed("a50102032620"), Ok(None));
+    assert_eq!(feed("80"), Err(Error::InvalidKey));
+    assert_eq!(feed("a0").map(|_| ()), Err(Error::MissingField("kty")));
+    // long byte strings are rejected before they are read
+    assert_eq!(
+        feed("a501020326200121590100"),
+        Err(Error::InvalidLength {
+            label: -2,
+            expected: 32,
+            actual: 256
+        })
+    );
+
+    let mut decoder = StreamDecoder::new(ParseOptions::new());
+    assert_eq!(decoder.feed(&[0x80]), Err(Error::InvalidKey));
+    assert_eq!(decoder.feed(&[0xa0]), Err(Error::InvalidKey));
+}
+
+// Decodes a key set fed in chunks of the given size.
+#[allow(clippy::type_complexity)]
+fn decode_key_set(
+    data: &str,
+    chunk_size: usize,
+) -> Result<(Vec<Result<PublicKey, Error>>, bool), Error> {
+    let data = hex::decode(data).unwrap();
+    let mut decoder = KeySetDecoder::new(ParseOptions::new());
+    let mut keys = Vec::new();
+    for mut chunk in data.chunks(chunk_size) {
+        while let Some((key, len)) = decoder.feed(chunk)? {
+            keys.push(key);
+            chunk = &chunk[len..];
+        }
+    }
+    Ok((keys, decoder.is_finished()))
+}
+
+#[test]
+fn key_set_decoder() {
+    let keys: Vec<_> = [P256_KEY, ED25519_KEY]
+        .into_iter()
+        .map(|key| Ok(PublicKey::from_slice(&hex::decode(key).unwrap()).unwrap()))
+        .collect();
+    for chunk_size in [1, 3, 7, 64, 256] {
+        let sets = [
+            format!("82{P256_KEY}{ED25519_KEY}"),
+            format!("9f{P256_KEY}{ED25519_KEY}ff"),
+        ];
+        for set in sets {
+            assert_eq!(
+                decode_key_set(&set, chunk_size),
+                Ok((keys.clone(), true)),
+                "{set} {chunk_size}"
+            );
+        }
+        assert_eq!(decode_key_set("80", chunk_size), Ok((vec![], true)));
+        assert_eq!(decode_key_set("9fff", chunk_size), Ok((vec![], true)));
+        // incomplete
+        assert_eq!(
+            decode_key_set(&format!("82{P256_KEY}"), chunk_size),
+            Ok((keys[..1].to_vec(), false))
+        );
+    }
+}
+
+#[test]
+fn key_set_decoder_unsupported_keys() {
+    // kty: RSA, alg: RS256, n (256 bytes), e
+    let rsa = format!("a4010303390100205901 00{} 2143010001", "c3".repeat(256)).replace(' ', "");
+    // kty: EC2, alg: -300, crv: P-256, x, y
+    let unknown_alg = P256_KEY.replacen("0326", "0339012b", 1);
+    // kty: EC2, alg: ES256, crv: P-256, x with 33 bytes
+    let long_x =
+        "a4010203262001215821ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+    // followed by an empty key
+    let data = format!("86{P256_KEY}{rsa}{unknown_alg}{long_x}a0{ED25519_KEY}");
+
+    let p256 = PublicKey::from_slice(&hex::decode(P256_KEY).unwrap()).unwrap();
+    let ed25519 = PublicKey::from_slice(&hex::decode(ED25519_KEY).unwrap()).unwrap();
+    for chunk_size in [1, 3, 7, 64, 512] {
+        let (keys, finished) = decode_key_set(&data, chunk_size).unwrap();
+        assert!(finished);
+        assert_eq!(
+            keys,
+            [
+                Ok(p256.clone()),
+                Err(Error::InvalidKey),
+                Err(Error::UnsupportedValue {
+                    label: 3,
+                    value: -300
+                }),
+                Err(Error::InvalidLength {
+                    label: -2,
+                    expected: 32,
+                    actual: 33
+                }),
+                Err(Error::MissingField("kty")),
+                Ok(ed25519.clone()),
+            ],
+            "{chunk_size}"
+        );
+    }
+
+    // malformed CBOR still stops the decoder
+    assert_eq!(
+        decode_key_set(&format!("82{P256_KEY}a101"), 1),
+        Ok((vec![Ok(p256)], false))
+    );
+    assert_eq!(
+        decode_key_set(&format!("82{P256_KEY}a1015f"), 1),
+        Err(Error::InvalidKey)
+    );
+}
+
+#[test]
+fn key_set_decoder_errors() {
+    let feed = |set: &str| KeySetDecoder::new(ParseOptions::new()).feed(&hex::decode(set).unwrap());
+
+    assert_eq!(feed(""), Ok(None));
+    assert_eq!(feed(P256_KEY), Err(Error::InvalidKey));
+    assert_eq!(feed("81a0"), Ok(Some((Err(Error::MissingField("kty")), 2))));
+    assert_eq!(feed("8000"), Err(Error::TrailingData { consumed: 1 }));
+    // a break is only allowed between the keys of an indefinite-length array
+    assert_eq!(feed("81ff"), Err(Error::InvalidKey));
+
+    let mut decoder = KeySetDecoder::new(ParseOptions::new());
+    let data = hex::decode(format!("81{ED25519_KEY}00")).unwrap();
+    let (_, len) = decoder.feed(&data).unwrap().unwrap();
+    assert_eq!(len, data.len() - 1);
+    assert_eq!(
+        decoder.feed(&data[len..]),
+        Err(Error::TrailingData { consumed: 0 })
+    );
+}
+
+#[test]
+fn max_string_len() {
+    let options = ParseOptions::new().max_string_len(Some(32));
+    let key = hex::decode(P256_KEY).unwrap();
+    assert!(PublicKey::from_slice_exact(&key, options).is_ok());
+
+    // unknown field with a long byte string, without the string itself
+    let key = hex::decode("a6010203262001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff185a5a00100000").unwrap();
+    let error = Error::StringTooLong {
+        max: 32,
+        actual: 0x100000,
+    };
+    assert_eq!(PublicKey::take_from_slice(&key, options), Err(error));
+    assert_eq!(StreamDecoder::new(options).feed(&key), Err(error));
+    assert_eq!(StreamDecoder::new(ParseOptions::new()).feed(&key), Ok(None));
+}
+
+#[test]
+fn to_bytes() {
+    for key in [P256_KEY, ED25519_KEY] {
+        let data = hex::decode(key).unwrap();
+        let key = PublicKey::from_slice(&data).unwrap();
+        let bytes = key.to_bytes::<128>().unwrap();
+        assert_eq!(bytes.as_slice(), data);
+        assert_eq!(bytes.as_slice(), serde_cbor::to_vec(&key).unwrap());
+
+        let raw = RawCoseKey::from(key.clone());
+        assert_eq!(raw.to_bytes::<128>().unwrap().as_slice(), data);
+
+        assert_eq!(key.to_bytes::<32>(), Err(Error::BufferTooSmall), "{key:?}");
+    }
+
+    // fields with values that do not fit into one byte
+    let mut raw = RawCoseKey::default();
+    raw.kty = Some(Kty::Other(1000));
+    raw.alg = Some(Alg::Rs256);
+    let bytes = raw.to_bytes::<16>().unwrap();
+    assert_eq!(bytes.as_slice(), serde_cbor::to_vec(&raw).unwrap());
+    assert_eq!(
+        RawCoseKey::from_slice_exact(&bytes, ParseOptions::new()),
+        Ok(raw)
+    );
+
+    // RS1 from legacy TPM attestation, encoded as 0x39fffe
+    let mut raw = RawCoseKey::default();
+    raw.kty = Some(Kty::Rsa);
+    raw.alg = Some(Alg::Rs1);
+    let bytes = raw.to_bytes::<16>().unwrap();
+    assert_eq!(hex::encode(&bytes), "a201030339fffe");
+    assert_eq!(
+        RawCoseKey::from_slice_exact(&bytes, ParseOptions::new()),
+        Ok(raw.clone())
+    );
+    // RSA keys are not supported, but the error reports the full value
+    assert_eq!(
+        PublicKey::from_raw(raw, ParseOptions::new()),
+        Err(Error::UnsupportedValue {
+            label: 3,
+            value: -65535
+        })
+    );
+}
+
+#[test]
+fn heapless_vec() {
+    let data = hex::decode(P256_KEY).unwrap();
+    let key = PublicKey::from_slice(&data).unwrap();
+    let vec = key.to_heapless_vec::<128>().unwrap();
+    assert_eq!(vec.as_slice(), data);
+    assert_eq!(key.to_heapless_vec::<32>(), Err(Error::BufferTooSmall));
+
+    let mut output = heapless::Vec::<u8, 200>::new();
+    output.push(0x82).unwrap();
+    assert_eq!(key.append_to(&mut output), Ok(data.len()));
+    let raw = RawCoseKey::from(key.clone());
+    assert_eq!(raw.append_to(&mut output), Ok(data.len()));
+    assert_eq!(output.as_slice(), [&[0x82], &data[..], &data[..]].concat());
+
+    let mut output = heapless::Vec::<u8, 100>::new();
+    output.push(0x82).unwrap();
+    assert_eq!(key.append_to(&mut output), Ok(data.len()));
+    assert_eq!(key.append_to(&mut output), Err(Error::BufferTooSmall));
+    assert_eq!(output.len(), 1 + data.len());
+}
+
+#[test]
+fn stored() {
+    for key in [P256_KEY, ED25519_KEY] {
+        let data = hex::decode(key).unwrap();
+        let key = PublicKey::from_slice(&data).unwrap();
+        let stored = key.to_stored::<128>().unwrap();
+        assert_eq!(stored.as_slice(), [&[0x82, 0x01], &data[..]].concat());
+        assert_eq!(PublicKey::from_stored(&stored), Ok(key.clone()));
+        assert_eq!(
+            RawCoseKey::from_stored(&stored),
+            Ok(RawCoseKey::from(key.clone()))
+        );
+
+        // elements appended by later versions are ignored
+        let extended = [&[0x83, 0x01], &data[..], &[0x63, b'n', b'e', b'w']].concat();
+        assert_eq!(PublicKey::from_stored(&extended), Ok(key));
+
+        let future = [&[0x82, 0x02], &data[..]].concat();
+        assert_eq!(
+            PublicKey::from_stored(&future),
+            Err(Error::UnsupportedVersion(2))
+        );
+        assert_eq!(PublicKey::from_stored(&data), Err(Error::InvalidKey));
+        assert_eq!(
+            PublicKey::from_stored(&[0x81, 0x01]),
+            Err(Error::InvalidKey)
+        );
+        assert_eq!(
+            PublicKey::from_stored(&[&stored[..], &[0x00]].concat()),
+            Err(Error::TrailingData {
+                consumed: stored.len()
+            })
+        );
+    }
+
+    // keys that are not supported by `PublicKey` can be read as `RawCoseKey`
+    let mut raw = RawCoseKey::default();
+    raw.kty = Some(Kty::Ec2);
+    raw.alg = Some(Alg::Es384);
+    let stored = raw.to_stored::<16>().unwrap();
+    assert_eq!(RawCoseKey::from_stored(&stored), Ok(raw));
+    assert!(matches!(
+        PublicKey::from_stored(&stored),
+        Err(Error::UnsupportedValue { .. })
+    ));
+}
+
+#[test]
+fn codec() {
+    let options = ParseOptions::new().text_labels(true);
+    let keys = [
+        P256_KEY,
+        ED25519_KEY,
+        // indefinite map with unknown nested fields
+        "bf010103272006215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff389f9fa10142ffffffff",
+        // text labels
+        "a4636b74790163616c6727636372760661785820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        // unsupported algorithm
+        "a20102033824",
+        // errors
+        "a50102032620",
+        "80",
+        "a0",
+        "a201020102",
+        "a4010203262001215821ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+    ];
+    for key in keys {
+        let mut data = hex::decode(key).unwrap();
+        data.push(0x00);
+        let raw = RawCoseKey::take_from_slice(&data, options);
+        assert_eq!(RawCoseKey::decode(&data, options), raw, "{key}");
+        assert_eq!(
+            PublicKey::decode(&data, options),
+            PublicKey::take_from_slice(&data, options),
+            "{key}"
+        );
+
+        if let Ok((raw, _)) = raw {
+            let mut output = [0; 128];
+            let len = raw.encode(&mut output).unwrap();
+            assert_eq!(&output[..len], raw.to_bytes::<128>().unwrap().as_slice());
+            assert_eq!(
+                raw.encode(&mut output[..len - 1]),
+                Err(Error::BufferTooSmall)
+            );
+            let mut uninit = [MaybeUninit::uninit(); 128];
+            assert_eq!(raw.encode_uninit(&mut uninit).unwrap(), &output[..len]);
+            assert_eq!(
+                raw.encode_uninit(&mut uninit[..len - 1]),
+                Err(Error::BufferTooSmall)
+            );
+            if let Ok(key) = PublicKey::try_from(raw) {
+                assert_eq!(key.encode(&mut output), Ok(len));
+                assert_eq!(key.encode_uninit(&mut uninit).unwrap(), &output[..len]);
+            }
+        }
+    }
+}
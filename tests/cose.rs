@@ -1,31 +1,95 @@
+#![cfg(all(
+    feature = "ecdh",
+    feature = "ed25519",
+    feature = "p256",
+    feature = "totp",
+    feature = "x25519",
+    feature = "serde"
+))]
+
 use core::fmt::Debug;
 
 use cbor_smol::{cbor_deserialize, cbor_serialize_bytes};
 use ciborium::Value;
-use cosey::{EcdhEsHkdf256PublicKey, Ed25519PublicKey, P256PublicKey};
+use cosey::{
+    iana, Alg, CoseKeyType, Crv, Ec2Variant, EcdhEsHkdf256PublicKey, EcdhEsHkdf256PublicKeyRef,
+    Ed25519PublicKey, Ed25519PublicKeyRef, Error, KeyOp, KeyOpEntry, KeyUse, Kty, P256PublicKey,
+    P256PublicKeyRef, ParseOptions, Profile, PublicKey, RawCoseKey, TagPolicy, TotpPublicKey,
+    X25519PublicKey, MAX_KEY_OPS, MAX_KEY_OP_LEN,
+};
 use heapless_bytes::Bytes;
 use itertools::Itertools as _;
-use quickcheck::{Arbitrary, Gen};
-use serde::{de::DeserializeOwned, Serialize};
+use serde::{de::DeserializeOwned, Deserializer, Serialize};
 
-#[derive(Clone, Debug)]
-struct Input(Bytes<32>);
+trait DeserializeWith: Sized {
+    fn deserialize_with<'de, D: Deserializer<'de>>(
+        deserializer: D,
+        options: ParseOptions,
+    ) -> Result<Self, D::Error>;
+}
 
-impl Arbitrary for Input {
-    fn arbitrary(g: &mut Gen) -> Self {
-        let mut data = vec![0; 32];
-        data.fill_with(|| u8::arbitrary(g));
-        Self(Bytes::from_slice(&data).unwrap())
-    }
+macro_rules! impl_deserialize_with {
+    ($($ty:ty),*) => {
+        $(
+            impl DeserializeWith for $ty {
+                fn deserialize_with<'de, D: Deserializer<'de>>(
+                    deserializer: D,
+                    options: ParseOptions,
+                ) -> Result<Self, D::Error> {
+                    <$ty>::deserialize_with(deserializer, options)
+                }
+            }
+        )*
+    };
+}
+
+impl_deserialize_with!(
+    P256PublicKey,
+    EcdhEsHkdf256PublicKey,
+    Ed25519PublicKey,
+    PublicKey
+);
+
+fn deserialize_map_with<T: DeserializeWith>(
+    map: Vec<(Value, Value)>,
+    options: ParseOptions,
+) -> (Result<T, cbor_smol::Error>, Vec<u8>) {
+    let map = Value::Map(map);
+    let mut serialized: Vec<u8> = Default::default();
+    ciborium::into_writer(&map, &mut serialized).unwrap();
+    let mut deserializer = cbor_smol::de::Deserializer::from_bytes(&serialized);
+    (T::deserialize_with(&mut deserializer, options), serialized)
 }
 
-fn deserialize_map<T: DeserializeOwned>(
+fn deserialize_map<T: DeserializeOwned + Debug + PartialEq>(
     map: Vec<(Value, Value)>,
 ) -> (Result<T, cbor_smol::Error>, Vec<u8>) {
     let map = Value::Map(map);
     let mut serialized: Vec<u8> = Default::default();
     ciborium::into_writer(&map, &mut serialized).unwrap();
-    (cbor_deserialize(&serialized), serialized)
+    let deserialized = cbor_deserialize(&serialized);
+    assert_compatible_de(&serialized, &deserialized);
+    (deserialized, serialized)
+}
+
+// ciborium and serde_cbor should produce the same result as cbor-smol
+fn assert_compatible_de<T: DeserializeOwned + Debug + PartialEq>(
+    serialized: &[u8],
+    deserialized: &Result<T, cbor_smol::Error>,
+) {
+    let deserialized = deserialized.as_ref().ok();
+    let ciborium: Option<T> = ciborium::from_reader(serialized).ok();
+    assert_eq!(deserialized, ciborium.as_ref(), "ciborium");
+    let serde_cbor: Option<T> = serde_cbor::from_slice(serialized).ok();
+    assert_eq!(deserialized, serde_cbor.as_ref(), "serde_cbor");
+}
+
+fn assert_compatible_ser<T: Serialize>(serialized: &[u8], data: &T) {
+    let mut ciborium: Vec<u8> = Default::default();
+    ciborium::into_writer(data, &mut ciborium).unwrap();
+    assert_eq!(serialized, ciborium, "ciborium");
+    let serde_cbor = serde_cbor::to_vec(data).unwrap();
+    assert_eq!(serialized, serde_cbor, "serde_cbor");
 }
 
 fn print_input_output<T: Debug + PartialEq>(
@@ -43,16 +107,19 @@ fn print_input_output<T: Debug + PartialEq>(
     }
 }
 
-fn test_serde<T: Serialize + DeserializeOwned + PartialEq>(data: T) -> bool {
+fn test_serde<T: Serialize + DeserializeOwned + Debug + PartialEq>(data: T) -> bool {
     let serialized: Bytes<1024> = cbor_serialize_bytes(&data).unwrap();
-    let deserialized: T = cbor_deserialize(&serialized).unwrap();
-    data == deserialized
+    assert_compatible_ser(&serialized, &data);
+    let deserialized = cbor_deserialize(&serialized);
+    assert_compatible_de(&serialized, &deserialized);
+    Ok(data) == deserialized
 }
 
 fn test_de<T: DeserializeOwned + Debug + PartialEq>(s: &str, data: T) {
     let serialized = hex::decode(s).unwrap();
-    let deserialized: T = cbor_deserialize(&serialized).unwrap();
-    assert_eq!(data, deserialized);
+    let deserialized = cbor_deserialize(&serialized);
+    assert_compatible_de(&serialized, &deserialized);
+    assert_eq!(Ok(data), deserialized);
 }
 
 fn test_de_alg<T: Serialize + DeserializeOwned + Debug + PartialEq>(
@@ -129,41 +196,89 @@ fn test_de_order<T: Serialize + DeserializeOwned + Debug + PartialEq>(data: T) -
         }
     }
 
-    let mut fields = canonical_fields;
-    fields.push((Value::Integer(42.into()), Value::Text("foobar".to_owned())));
-    fields.push((Value::Integer(24.into()), Value::Text("foobar".to_owned())));
-    let (deserialized, serialized) = deserialize_map::<T>(fields);
+    let unknown_fields = [
+        (Value::Integer(2.into()), Value::Bytes(vec![0xff; 16])),
+        (Value::Integer(42.into()), Value::Text("foobar".to_owned())),
+        (Value::Integer(24.into()), Value::Text("foobar".to_owned())),
+        (Value::Integer(1000.into()), Value::Array(vec![Value::Null])),
+        (Value::Integer((-65536).into()), Value::Map(vec![])),
+    ];
+    for i in 0..=canonical_fields.len() {
+        let mut fields = canonical_fields.clone();
+        for (j, unknown_field) in unknown_fields.iter().enumerate() {
+            fields.insert(i + j, unknown_field.clone());
+        }
+        let (deserialized, serialized) = deserialize_map::<T>(fields);
 
-    // injecting an unsupported field should not change the result
-    let is_success = Ok(&data) == deserialized.as_ref();
+        // injecting unsupported fields in any position should not change the result
+        let is_success = Ok(&data) == deserialized.as_ref();
 
-    if !is_success {
-        println!("Expected correct deserialization with unsupported fields");
-        print_input_output(&data, &serialized, &deserialized);
+        if !is_success {
+            println!("Expected correct deserialization with unsupported fields");
+            print_input_output(&data, &serialized, &deserialized);
+            return false;
+        }
     }
 
-    is_success
+    true
+}
+
+fn test_de_any_order<T: Serialize + DeserializeWith + Debug + PartialEq>(data: T) -> bool {
+    let options = ParseOptions::new().any_order(true);
+    let serialized_value = Value::serialized(&data).unwrap();
+    let canonical_fields = serialized_value.into_map().unwrap();
+    let unknown_field = (Value::Integer(42.into()), Value::Text("foobar".to_owned()));
+
+    for fields in canonical_fields
+        .iter()
+        .cloned()
+        .permutations(canonical_fields.len())
+    {
+        // all orders should be accepted, also with unsupported fields in any position
+        for i in 0..=fields.len() {
+            let mut fields = fields.clone();
+            fields.insert(i, unknown_field.clone());
+            let (deserialized, serialized) = deserialize_map_with::<T>(fields, options);
+            if Ok(&data) != deserialized.as_ref() {
+                println!("Expected correct deserialization for any order");
+                print_input_output(&data, &serialized, &deserialized);
+                return false;
+            }
+        }
+
+        // duplicates should be rejected
+        let mut duplicated = fields.clone();
+        duplicated.push(fields[0].clone());
+        let (deserialized, serialized) = deserialize_map_with::<T>(duplicated, options);
+        if deserialized.is_ok() {
+            println!("Expected error for duplicate field");
+            print_input_output(&data, &serialized, &deserialized);
+            return false;
+        }
+    }
+
+    true
 }
 
 #[test]
 fn de_p256() {
-    let x = Bytes::from_slice(&[0xff; 32]).unwrap();
-    let y = Bytes::from_slice(&[0xff; 32]).unwrap();
+    let x = [0xff; 32];
+    let y = [0xff; 32];
     let key = P256PublicKey { x, y };
     test_de("a5010203262001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", key);
 }
 
 #[test]
 fn de_ecdh() {
-    let x = Bytes::from_slice(&[0xff; 32]).unwrap();
-    let y = Bytes::from_slice(&[0xff; 32]).unwrap();
+    let x = [0xff; 32];
+    let y = [0xff; 32];
     let key = EcdhEsHkdf256PublicKey { x, y };
     test_de("a501020338182001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff", key);
 }
 
 #[test]
 fn de_ed25519() {
-    let x = Bytes::from_slice(&[0xff; 32]).unwrap();
+    let x = [0xff; 32];
     let key = Ed25519PublicKey { x };
     test_de(
         "a4010103272006215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
@@ -171,64 +286,1329 @@ fn de_ed25519() {
     );
 }
 
-quickcheck::quickcheck! {
-    fn serde_p256(x: Input, y: Input) -> bool {
-        test_serde(P256PublicKey {
-            x: x.0,
-            y: y.0,
-        })
+#[test]
+fn de_x25519() {
+    let key = X25519PublicKey::from([0xff; 32]);
+    test_de(
+        "a401010338182004215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        key,
+    );
+    assert_eq!(
+        hex::encode(serde_cbor::to_vec(&key).unwrap()),
+        "a401010338182004215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+    );
+}
+
+#[test]
+fn de_public_key() {
+    test_de(
+        "a5010203262001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        PublicKey::P256Key(P256PublicKey::from_coordinates([0xff; 32], [0xff; 32])),
+    );
+    test_de(
+        "a501020338182001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        PublicKey::EcdhEsHkdf256Key(EcdhEsHkdf256PublicKey::from_coordinates(
+            [0xff; 32], [0xff; 32],
+        )),
+    );
+    test_de(
+        "a4010103272006215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        PublicKey::Ed25519Key(Ed25519PublicKey::from([0xff; 32])),
+    );
+    test_de("a201040328", PublicKey::TotpKey(TotpPublicKey {}));
+    test_de(
+        "a401010338182004215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        PublicKey::X25519Key(X25519PublicKey::from([0xff; 32])),
+    );
+    // without alg, OKP keys are selected by the curve
+    test_de(
+        "a301012004215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        PublicKey::X25519Key(X25519PublicKey::from([0xff; 32])),
+    );
+    test_de(
+        "a301012006215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        PublicKey::Ed25519Key(Ed25519PublicKey::from([0xff; 32])),
+    );
+    // without alg, EC2 keys on P-256 are assumed to be signature keys
+    test_de(
+        "a401022001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+        PublicKey::P256Key(P256PublicKey::from_coordinates([0xff; 32], [0xff; 32])),
+    );
+}
+
+#[test]
+fn de_public_key_without_alg() {
+    let serialized = hex::decode("a401022001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap();
+    let deserialize = |variant| {
+        let options = ParseOptions::new().ec2_without_alg(variant);
+        let mut deserializer = cbor_smol::de::Deserializer::from_bytes(&serialized);
+        PublicKey::deserialize_with(&mut deserializer, options)
+    };
+    assert_eq!(
+        deserialize(Ec2Variant::P256),
+        Ok(PublicKey::P256Key(P256PublicKey::from_coordinates(
+            [0xff; 32], [0xff; 32]
+        )))
+    );
+    assert_eq!(
+        deserialize(Ec2Variant::EcdhEsHkdf256),
+        Ok(PublicKey::EcdhEsHkdf256Key(
+            EcdhEsHkdf256PublicKey::from_coordinates([0xff; 32], [0xff; 32])
+        ))
+    );
+    assert!(deserialize(Ec2Variant::Reject).is_err());
+}
+
+#[test]
+fn de_require_alg() {
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let mut fields = Value::serialized(&key).unwrap().into_map().unwrap();
+    let options = ParseOptions::new().require_alg(true);
+
+    let (deserialized, _) = deserialize_map_with::<P256PublicKey>(fields.clone(), options);
+    assert_eq!(deserialized.unwrap(), key);
+
+    fields.remove(1);
+    let (deserialized, _) = deserialize_map_with::<P256PublicKey>(fields.clone(), options);
+    assert!(deserialized.is_err());
+    let (deserialized, _) = deserialize_map_with::<PublicKey>(fields.clone(), options);
+    assert!(deserialized.is_err());
+    assert_eq!(
+        deserialize_error(fields, options),
+        "label 3: missing field `alg`"
+    );
+}
+
+#[test]
+fn registry_values() {
+    assert_eq!(Kty::from_i32(2), Kty::Ec2);
+    assert_eq!(Kty::from_i32(7), Kty::Other(7));
+    assert_eq!(Alg::try_from(-35_i64), Ok(Alg::Es384));
+    assert_eq!(Alg::from(-1000), Alg::Other(-1000));
+    assert!(Alg::try_from(i64::from(i32::MAX) + 1).is_err());
+    assert_eq!(i32::from(Crv::BrainpoolP256r1), 256);
+    assert_eq!(Crv::Other(300).to_i32(), 300);
+
+    // values that do not fit into an i16
+    assert_eq!(Alg::from_i32(-65535), Alg::Rs1);
+    assert_eq!(Alg::Rs1.to_i32(), iana::alg::RS1);
+    assert_eq!(Alg::Rs1.hash(), Some(Alg::Sha1));
+    assert_eq!(Alg::Rs1.key_use(), KeyUse::Signature);
+    // RFC 9459
+    assert_eq!(Alg::from_i32(-65534), Alg::A128Ctr);
+    assert_eq!(Alg::from_i32(-65529), Alg::A256Cbc);
+    assert_eq!(Alg::A192Cbc.key_size(), Some(24));
+    assert_eq!(Alg::from_i32(-260), Alg::WalnutDsa);
+    assert_eq!(Alg::WalnutDsa.key_types(), &[Kty::WalnutDsa]);
+    // RFC 9864
+    assert_eq!(Alg::from_i32(-19), Alg::Ed25519);
+    assert_eq!(Alg::from_i32(-53), Alg::Ed448);
+    assert_eq!(Alg::from_i32(-51), Alg::Esp384);
+    assert_eq!(Alg::from_i32(-52), Alg::Esp512);
+    assert_eq!(Alg::Esp512.hash(), Some(Alg::Sha512));
+    assert_eq!(Alg::Ed25519.key_types(), &[Kty::Okp]);
+
+    // -9 is registered as ESP256 and used for TOTP keys
+    assert_eq!(Alg::from_i32(-9), Alg::Esp256);
+    assert_eq!(Alg::Totp, Alg::Esp256);
+    assert_eq!(Alg::Totp.name(), Some("ESP256"));
+    assert_eq!(Alg::Esp256.key_use(), KeyUse::Signature);
+    assert_eq!(iana::alg::TOTP, iana::alg::ESP256);
+
+    for alg in [
+        Alg::Es256,
+        Alg::Rs512,
+        Alg::Rs1,
+        Alg::A128Gcm,
+        Alg::Other(-1000),
+    ] {
+        let serialized = serde_cbor::to_vec(&alg).unwrap();
+        assert_eq!(serialized, serde_cbor::to_vec(&alg.to_i32()).unwrap());
+        assert_eq!(serde_cbor::from_slice::<Alg>(&serialized).unwrap(), alg);
     }
+}
 
-    fn serde_ecdh(x: Input, y: Input) -> bool {
-        test_serde(EcdhEsHkdf256PublicKey {
-            x: x.0,
-            y: y.0,
-        })
+#[test]
+fn registry_names() {
+    assert_eq!(Alg::Es256.name(), Some("ES256"));
+    assert_eq!(Alg::from_name("ES256"), Some(Alg::Es256));
+    assert_eq!(
+        Alg::from_name("ECDH-ES + HKDF-256"),
+        Some(Alg::EcdhEsHkdf256)
+    );
+    assert_eq!(Alg::from_name("es256"), None);
+    assert_eq!(Alg::Other(-1000).name(), None);
+    assert_eq!(Kty::from_name("OKP"), Some(Kty::Okp));
+    assert_eq!(Crv::from_name("P-256"), Some(Crv::P256));
+    assert_eq!(Crv::from_name(""), None);
+
+    for alg in [Alg::Es256, Alg::EdDsa, Alg::Rs512, Alg::A128Gcm, Alg::Totp] {
+        assert_eq!(Alg::from_name(alg.name().unwrap()), Some(alg));
     }
+}
 
-    fn serde_ed25519(x: Input) -> bool {
-        test_serde(Ed25519PublicKey {
-            x: x.0,
-        })
+#[test]
+fn registry_metadata() {
+    assert_eq!(Alg::EcdhEsHkdf256.name(), Some("ECDH-ES + HKDF-256"));
+    assert_eq!(Crv::P256.name(), Some("P-256"));
+    assert_eq!(Kty::Other(100).name(), None);
+
+    assert_eq!(Alg::Es256.key_types(), &[Kty::Ec2]);
+    assert_eq!(Alg::EcdhEsHkdf256.key_types(), &[Kty::Ec2, Kty::Okp]);
+    assert_eq!(Alg::Sha256.key_types(), &[]);
+    assert_eq!(Crv::Ed25519.key_type(), Some(Kty::Okp));
+    assert_eq!(Crv::Other(100).key_type(), None);
+
+    assert_eq!(Crv::P256.coordinate_size(), Some(32));
+    assert_eq!(Crv::P521.coordinate_size(), Some(66));
+    assert_eq!(Crv::Ed448.coordinate_size(), Some(57));
+    assert_eq!(Crv::Other(100).coordinate_size(), None);
+    assert_eq!(Alg::Es384.hash(), Some(Alg::Sha384));
+    assert_eq!(Alg::EcdhEsHkdf256.hash(), Some(Alg::Sha256));
+    assert_eq!(Alg::EdDsa.hash(), None);
+    assert_eq!(Alg::A192Gcm.key_size(), Some(24));
+    assert_eq!(Alg::Hs256.key_size(), None);
+    assert_eq!(Alg::Es256.key_size(), None);
+
+    assert_eq!(iana::alg::ES256, Alg::Es256.to_i32());
+    assert_eq!(iana::kty::HSS_LMS, Kty::HssLms.to_i32());
+    assert_eq!(iana::crv::BRAINPOOL_P256R1, Crv::BrainpoolP256r1.to_i32());
+}
+
+#[test]
+fn key_use() {
+    assert_eq!(Alg::Ps256.key_use(), KeyUse::Signature);
+    assert_eq!(Alg::HssLms.key_use(), KeyUse::Signature);
+    assert_eq!(Alg::EcdhSsA128Kw.key_use(), KeyUse::KeyAgreement);
+    assert_eq!(Alg::Hs256.key_use(), KeyUse::Mac);
+    assert_eq!(Alg::A128Gcm.key_use(), KeyUse::Other);
+    assert_eq!(Alg::Other(-1000).key_use(), KeyUse::Other);
+
+    let key = PublicKey::from(P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]));
+    assert_eq!(key.alg(), Alg::Es256);
+    assert_eq!(key.key_use(), KeyUse::Signature);
+    let key = PublicKey::from(EcdhEsHkdf256PublicKey::from_coordinates(
+        [0x01; 32], [0x02; 32],
+    ));
+    assert_eq!(key.key_use(), KeyUse::KeyAgreement);
+    assert_eq!(
+        PublicKey::from(Ed25519PublicKey::from([0x01; 32])).key_use(),
+        KeyUse::Signature
+    );
+    assert_eq!(PublicKey::from(TotpPublicKey {}).key_use(), KeyUse::Mac);
+}
+
+#[test]
+fn same_key() {
+    let p256 = PublicKey::from(P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]));
+    let ecdh = PublicKey::from(EcdhEsHkdf256PublicKey::from_coordinates(
+        [0x01; 32], [0x02; 32],
+    ));
+    let other = PublicKey::from(P256PublicKey::from_coordinates([0x01; 32], [0x03; 32]));
+    let ed25519 = PublicKey::from(Ed25519PublicKey::from([0x01; 32]));
+    assert!(p256.same_key(&p256));
+    assert!(p256.same_key(&ecdh));
+    assert!(ecdh.same_key(&p256));
+    assert_ne!(p256, ecdh);
+    assert!(!p256.same_key(&other));
+    assert!(!p256.same_key(&ed25519));
+    assert!(!ed25519.same_key(&PublicKey::from(TotpPublicKey {})));
+}
+
+#[test]
+fn supports_alg() {
+    let p256 = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let key = PublicKey::from(p256);
+    assert!(key.supports_alg(iana::alg::ES256));
+    assert!(!key.supports_alg(iana::alg::ECDH_ES_HKDF256));
+    assert!(!key.supports_alg(iana::alg::ED_DSA));
+
+    let key = PublicKey::from(EcdhEsHkdf256PublicKey::from_coordinates(p256.x, p256.y));
+    assert!(key.supports_alg(iana::alg::ECDH_ES_HKDF256));
+    assert!(!key.supports_alg(iana::alg::ES256));
+
+    assert!(Ed25519PublicKey::supports_alg(iana::alg::ED_DSA));
+    assert!(!Ed25519PublicKey::supports_alg(iana::alg::ES256));
+    assert!(TotpPublicKey::supports_alg(iana::alg::TOTP));
+}
+
+#[test]
+fn labels() {
+    use cosey::label;
+
+    assert_eq!(Error::MissingField("kty").label(), Some(label::KTY));
+    assert_eq!(Error::MissingField("alg").label(), Some(label::ALG));
+    assert_eq!(Error::MissingField("crv").label(), Some(label::ec2::CRV));
+    assert_eq!(Error::MissingField("x").label(), Some(label::okp::X));
+    assert_eq!(Error::MissingField("y").label(), Some(label::ec2::Y));
+
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let fields = Value::serialized(&key).unwrap().into_map().unwrap();
+    let labels: Vec<_> = fields
+        .iter()
+        .map(|(label, _)| i8::try_from(label.as_integer().unwrap()).unwrap())
+        .collect();
+    assert_eq!(
+        labels,
+        [
+            label::KTY,
+            label::ALG,
+            label::ec2::CRV,
+            label::ec2::X,
+            label::ec2::Y
+        ]
+    );
+}
+
+#[test]
+fn max_encoded_len() {
+    fn len<T: Serialize>(key: T) -> usize {
+        serde_cbor::to_vec(&key).unwrap().len()
     }
 
-    fn de_order_p256(x: Input, y: Input) -> bool {
-        test_de_order(P256PublicKey {
-            x: x.0,
-            y: y.0,
-        })
+    let p256 = P256PublicKey::from_coordinates([0xff; 32], [0xff; 32]);
+    let ecdh = EcdhEsHkdf256PublicKey::from_coordinates([0xff; 32], [0xff; 32]);
+    let ed25519 = Ed25519PublicKey::from([0xff; 32]);
+    assert_eq!(P256PublicKey::MAX_ENCODED_LEN, len(p256));
+    assert_eq!(EcdhEsHkdf256PublicKey::MAX_ENCODED_LEN, len(ecdh));
+    assert_eq!(Ed25519PublicKey::MAX_ENCODED_LEN, len(ed25519));
+    assert_eq!(TotpPublicKey::MAX_ENCODED_LEN, len(TotpPublicKey {}));
+    assert_eq!(PublicKey::MAX_ENCODED_LEN, len(ecdh));
+
+    let mut raw = RawCoseKey::from(p256);
+    raw.kty = Some(Kty::Other(i32::MIN));
+    raw.alg = Some(Alg::Other(i32::MIN));
+    raw.crv = Some(Crv::Other(i32::MIN));
+    let op = KeyOpEntry::text(&"a".repeat(MAX_KEY_OP_LEN)).unwrap();
+    raw.key_ops = Some(core::iter::repeat(op).take(MAX_KEY_OPS).collect());
+    assert_eq!(RawCoseKey::MAX_ENCODED_LEN, len(&raw));
+}
+
+#[test]
+fn encoded_len() {
+    fn len<T: Serialize>(key: &T) -> usize {
+        serde_cbor::to_vec(key).unwrap().len()
+    }
+
+    let p256 = P256PublicKey::from_coordinates([0x00; 32], [0xff; 32]);
+    let ed25519 = Ed25519PublicKey::from([0x00; 32]);
+    assert_eq!(p256.encoded_len(), len(&p256));
+    assert_eq!(ed25519.encoded_len(), len(&ed25519));
+    let key = PublicKey::from(ed25519);
+    assert_eq!(key.encoded_len(), len(&key));
+
+    for n in [0, 1, 23, 24, 31, 32] {
+        let bytes = Bytes::<32>::from_slice(&[0xff; 32][..n]).unwrap();
+        let mut raw = RawCoseKey::default();
+        assert_eq!(raw.encoded_len(), len(&raw));
+        raw.alg = Some(Alg::Other(-1000));
+        raw.y = Some(bytes);
+        assert_eq!(raw.encoded_len(), len(&raw));
+    }
+    assert_eq!(TotpPublicKey {}.encoded_len(), len(&TotpPublicKey {}));
+}
+
+#[test]
+fn cose_key_type() {
+    fn check<K: CoseKeyType + Serialize>(key: &K) {
+        let Value::Map(fields) = Value::serialized(key).unwrap() else {
+            panic!("not a map");
+        };
+        let int = |value: &Value| i32::try_from(value.as_integer().unwrap()).unwrap();
+        assert_eq!(int(&fields[0].1), K::KTY.to_i32());
+        assert_eq!(int(&fields[1].1), K::ALG.to_i32());
+        let params = match K::CRV {
+            Some(crv) => {
+                assert_eq!(int(&fields[2].1), crv.to_i32());
+                &fields[3..]
+            }
+            None => &fields[2..],
+        };
+        assert_eq!(params.len(), K::PARAMS.len());
+        for ((label, value), (expected_label, len)) in params.iter().zip(K::PARAMS) {
+            assert_eq!(int(label), i32::from(*expected_label));
+            assert_eq!(value.as_bytes().unwrap().len(), *len);
+        }
+        assert_eq!(
+            cbor_serialize_bytes::<_, 256>(key).unwrap().len(),
+            K::MAX_ENCODED_LEN
+        );
+    }
+
+    check(&P256PublicKey::from_coordinates([0xff; 32], [0xff; 32]));
+    check(&EcdhEsHkdf256PublicKey::from_coordinates(
+        [0xff; 32], [0xff; 32],
+    ));
+    check(&Ed25519PublicKey::from([0xff; 32]));
+    check(&TotpPublicKey {});
+}
+
+#[test]
+fn hash() {
+    use std::collections::HashSet;
+
+    let p256 = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let ed25519 = Ed25519PublicKey::from([0x01; 32]);
+    let keys: HashSet<PublicKey> = [
+        PublicKey::from(p256),
+        PublicKey::from(ed25519),
+        PublicKey::from(p256),
+        PublicKey::from(EcdhEsHkdf256PublicKey::from_coordinates(
+            [0x01; 32], [0x02; 32],
+        )),
+        PublicKey::from(ed25519),
+    ]
+    .into_iter()
+    .collect();
+    assert_eq!(keys.len(), 3);
+    assert!(keys.contains(&PublicKey::from(p256)));
+
+    let raw: HashSet<RawCoseKey> = [RawCoseKey::from(p256), RawCoseKey::from(p256)]
+        .into_iter()
+        .collect();
+    assert_eq!(raw.len(), 1);
+}
+
+#[test]
+fn ord() {
+    fn check<T: Ord + Serialize + Debug>(keys: &[T]) {
+        for (a, b) in keys.iter().cartesian_product(keys) {
+            let encoded_a = serde_cbor::to_vec(a).unwrap();
+            let encoded_b = serde_cbor::to_vec(b).unwrap();
+            assert_eq!(a.cmp(b), encoded_a.cmp(&encoded_b), "{a:?} {b:?}");
+        }
+    }
+
+    let mut raw_keys = Vec::new();
+    for (kty, alg, crv, x, y) in itertools::iproduct!(
+        [
+            None,
+            Some(Kty::Okp),
+            Some(Kty::Other(-30)),
+            Some(Kty::Other(1000))
+        ],
+        [None, Some(Alg::Es256), Some(Alg::Other(20))],
+        [None, Some(Crv::P256), Some(Crv::Other(i32::MIN))],
+        [None, Some(0), Some(1), Some(32)],
+        [None, Some(24)]
+    ) {
+        let mut raw = RawCoseKey::default();
+        raw.kty = kty;
+        raw.alg = alg;
+        raw.crv = crv;
+        raw.x = x.map(|len| Bytes::from_slice(&[0x80; 32][..len]).unwrap());
+        raw.y = y.map(|len| Bytes::from_slice(&[0x00; 32][..len]).unwrap());
+        raw_keys.push(raw);
     }
+    check(&raw_keys);
+
+    let ed25519: Vec<_> = [[0x00; 32], [0x01; 32], [0xff; 32]]
+        .into_iter()
+        .map(Ed25519PublicKey::from)
+        .collect();
+    check(&ed25519);
+
+    let mut keys: Vec<_> = ed25519.into_iter().map(PublicKey::from).collect();
+    keys.push(P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]).into());
+    keys.push(P256PublicKey::from_coordinates([0x02; 32], [0x01; 32]).into());
+    keys.push(EcdhEsHkdf256PublicKey::from_coordinates([0x01; 32], [0x02; 32]).into());
+    keys.push(TotpPublicKey {}.into());
+    check(&keys);
+}
+
+#[test]
+fn coordinates() {
+    let x = [0x01; 32];
+    let y = [0x02; 32];
+    let key = P256PublicKey::from_coordinates(x, y);
+    assert_eq!(key.x, x);
+    assert_eq!(key.y, y);
+    assert_eq!(key.into_coordinates(), (x, y));
+
+    let key = EcdhEsHkdf256PublicKey::from_coordinates(x, y);
+    assert_eq!(key.into_coordinates(), (x, y));
+
+    let key = Ed25519PublicKey::from(x);
+    assert_eq!(key.x, x);
+}
+
+#[test]
+fn try_from_slice() {
+    let key = Ed25519PublicKey::try_from([0x01; 32].as_slice()).unwrap();
+    assert_eq!(key.x.as_slice(), &[0x01; 32]);
+    let key = X25519PublicKey::try_from([0x01; 32].as_slice()).unwrap();
+    assert_eq!(key.pub_key.as_slice(), &[0x01; 32]);
+
+    assert_eq!(
+        Ed25519PublicKey::try_from([0x01; 31].as_slice()),
+        Err(Error::InvalidLength {
+            label: -2,
+            expected: 32,
+            actual: 31
+        })
+    );
+    assert_eq!(
+        X25519PublicKey::try_from([0x01; 33].as_slice()),
+        Err(Error::InvalidLength {
+            label: -2,
+            expected: 32,
+            actual: 33
+        })
+    );
+}
+
+#[test]
+fn try_from_public_key() {
+    let p256 = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let ed25519 = Ed25519PublicKey::from([0x01; 32]);
+
+    let key = PublicKey::from(p256);
+    assert_eq!(P256PublicKey::try_from(key.clone()), Ok(p256));
+    assert_eq!(Ed25519PublicKey::try_from(key.clone()), Err(key.clone()));
+    assert_eq!(EcdhEsHkdf256PublicKey::try_from(key.clone()), Err(key));
+
+    let key = PublicKey::from(ed25519);
+    assert_eq!(Ed25519PublicKey::try_from(key.clone()), Ok(ed25519));
+    assert_eq!(TotpPublicKey::try_from(key.clone()), Err(key));
+}
+
+#[test]
+fn public_key_accessors() {
+    let p256 = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let key = PublicKey::from(p256);
+    assert_eq!(key.as_p256(), Some(&p256));
+    assert_eq!(key.as_ecdh_es_hkdf256(), None);
+    assert_eq!(key.as_ed25519(), None);
+    assert_eq!(key.as_totp(), None);
+
+    let ed25519 = Ed25519PublicKey::from([0x01; 32]);
+    let key = PublicKey::from(ed25519);
+    assert_eq!(key.as_p256(), None);
+    assert_eq!(key.as_ed25519(), Some(&ed25519));
+    assert_eq!(key.as_x25519(), None);
+
+    let x25519 = X25519PublicKey::from([0x01; 32]);
+    let key = PublicKey::from(x25519);
+    assert_eq!(key.as_ed25519(), None);
+    assert_eq!(key.as_x25519(), Some(&x25519));
+    assert_eq!(X25519PublicKey::try_from(key), Ok(x25519));
+}
 
-    fn de_order_ecdh(x: Input, y: Input) -> bool {
-        test_de_order(EcdhEsHkdf256PublicKey {
-            x: x.0,
-            y: y.0,
+#[test]
+fn key_sizes() {
+    let buffer = [0x01; cosey::P256_COORDINATE_SIZE];
+    let key = P256PublicKey::from_coordinates(buffer, buffer);
+    assert_eq!(
+        <P256PublicKey as CoseKeyType>::PARAMS,
+        &[(-2, key.x.len()), (-3, key.y.len())]
+    );
+    let key = Ed25519PublicKey::from([0x01; cosey::ED25519_PUBLIC_KEY_SIZE]);
+    assert_eq!(
+        <Ed25519PublicKey as CoseKeyType>::PARAMS,
+        &[(-2, key.x.len())]
+    );
+    let key = X25519PublicKey::from([0x01; cosey::X25519_PUBLIC_KEY_SIZE]);
+    assert_eq!(key.pub_key.len(), 32);
+}
+
+#[test]
+fn checked_constructors() {
+    let key = P256PublicKey::new(&[0x01; 32], &[0x02; 32]).unwrap();
+    assert_eq!(key, P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]));
+    let key = EcdhEsHkdf256PublicKey::new(&[0x01; 32], &[0x02; 32]).unwrap();
+    assert_eq!(
+        key,
+        EcdhEsHkdf256PublicKey::from_coordinates([0x01; 32], [0x02; 32])
+    );
+    assert_eq!(
+        Ed25519PublicKey::new(&[0x01; 32]),
+        Ok(Ed25519PublicKey::from([0x01; 32]))
+    );
+    assert_eq!(
+        X25519PublicKey::new(&[0x01; 32]),
+        Ok(X25519PublicKey::from([0x01; 32]))
+    );
+
+    assert_eq!(
+        P256PublicKey::new(&[0x01; 31], &[0x02; 32]),
+        Err(Error::InvalidLength {
+            label: -2,
+            expected: 32,
+            actual: 31
         })
+    );
+    assert_eq!(
+        EcdhEsHkdf256PublicKey::new(&[0x01; 32], &[0x02; 33]),
+        Err(Error::InvalidLength {
+            label: -3,
+            expected: 32,
+            actual: 33
+        })
+    );
+    assert_eq!(
+        Ed25519PublicKey::new(&[]),
+        Err(Error::InvalidLength {
+            label: -2,
+            expected: 32,
+            actual: 0
+        })
+    );
+}
+
+#[cfg(feature = "curve25519-dalek")]
+#[test]
+fn ed25519_to_x25519() {
+    // base points of edwards25519 and curve25519
+    let mut y = [0x66; 32];
+    y[0] = 0x58;
+    let mut u = [0x00; 32];
+    u[0] = 0x09;
+    let key = Ed25519PublicKey::from(y);
+    assert_eq!(key.to_x25519(), Some(X25519PublicKey::from(u)));
+
+    // not on the curve
+    let mut y = [0x00; 32];
+    y[0] = 0x02;
+    let key = Ed25519PublicKey::from(y);
+    assert_eq!(key.to_x25519(), None);
+}
+
+#[cfg(feature = "curve25519-dalek")]
+#[test]
+fn ed25519_valid_point() {
+    let mut y = [0x66; 32];
+    y[0] = 0x58;
+    assert!(Ed25519PublicKey::from(y).is_valid_point());
+    // the identity is on the curve
+    let mut identity = [0x00; 32];
+    identity[0] = 0x01;
+    assert!(Ed25519PublicKey::from(identity).is_valid_point());
+
+    // not on the curve
+    let mut y = [0x00; 32];
+    y[0] = 0x02;
+    assert!(!Ed25519PublicKey::from(y).is_valid_point());
+    // p + 1 is a non-canonical encoding of the identity
+    let mut y = [0xff; 32];
+    y[0] = 0xee;
+    y[31] = 0x7f;
+    assert!(!Ed25519PublicKey::from(y).is_valid_point());
+}
+
+#[test]
+fn small_order() {
+    // base points of edwards25519 and curve25519
+    let mut y = [0x66; 32];
+    y[0] = 0x58;
+    assert!(!Ed25519PublicKey::from(y).is_small_order());
+    let mut u = [0x00; 32];
+    u[0] = 0x09;
+    assert!(!X25519PublicKey::from(u).is_small_order());
+
+    // the identity with both signs and its non-canonical encoding
+    let mut identity = [0x00; 32];
+    identity[0] = 0x01;
+    assert!(Ed25519PublicKey::from(identity).is_small_order());
+    identity[31] = 0x80;
+    assert!(Ed25519PublicKey::from(identity).is_small_order());
+    let mut p_plus_one = [0xff; 32];
+    p_plus_one[0] = 0xee;
+    assert!(Ed25519PublicKey::from(p_plus_one).is_small_order());
+    assert!(X25519PublicKey::from([0x00; 32]).is_small_order());
+
+    let serialized = serde_cbor::to_vec(&Ed25519PublicKey::from(identity)).unwrap();
+    let key: Ed25519PublicKey = serde_cbor::from_slice(&serialized).unwrap();
+    assert!(key.is_small_order());
+    let mut deserializer = serde_cbor::Deserializer::from_slice(&serialized);
+    let options = ParseOptions::new().reject_small_order(true);
+    let error = Ed25519PublicKey::deserialize_with(&mut deserializer, options).unwrap_err();
+    assert_eq!(error.to_string(), "label -2: point of small order");
+}
+
+#[test]
+fn coordinate_range() {
+    let mut prime = [0xff; 32];
+    prime[4..7].fill(0x00);
+    prime[7] = 0x01;
+    prime[8..20].fill(0x00);
+    let mut below = prime;
+    below[31] = 0xfe;
+
+    assert!(P256PublicKey::from_coordinates(below, below).coordinates_in_range());
+    assert!(!P256PublicKey::from_coordinates(below, prime).coordinates_in_range());
+    assert!(!EcdhEsHkdf256PublicKey::from_coordinates([0xff; 32], below).coordinates_in_range());
+
+    let options = ParseOptions::new().check_coordinate_range(true);
+    let fields = |key| Value::serialized(&key).unwrap().into_map().unwrap();
+    let key = P256PublicKey::from_coordinates(below, below);
+    assert_eq!(
+        deserialize_map_with::<P256PublicKey>(fields(key), options)
+            .0
+            .unwrap(),
+        key
+    );
+    let key = P256PublicKey::from_coordinates(below, prime);
+    assert_eq!(
+        deserialize_map_with::<P256PublicKey>(fields(key), ParseOptions::new())
+            .0
+            .unwrap(),
+        key
+    );
+    assert_eq!(
+        deserialize_error(fields(key), options),
+        "label -3: coordinate out of range"
+    );
+}
+
+#[cfg(feature = "curve25519-dalek")]
+#[test]
+fn small_order_torsion() {
+    for point in curve25519_dalek::constants::EIGHT_TORSION {
+        let key = Ed25519PublicKey::from(point.compress().to_bytes());
+        assert!(key.is_small_order());
+        let key = X25519PublicKey::from(point.to_montgomery().to_bytes());
+        assert!(key.is_small_order());
+    }
+}
+
+fn deserialize_error(fields: Vec<(Value, Value)>, options: ParseOptions) -> String {
+    let mut serialized: Vec<u8> = Default::default();
+    ciborium::into_writer(&Value::Map(fields), &mut serialized).unwrap();
+    let mut deserializer = serde_cbor::Deserializer::from_slice(&serialized);
+    P256PublicKey::deserialize_with(&mut deserializer, options)
+        .unwrap_err()
+        .to_string()
+}
+
+#[test]
+fn de_duplicates() {
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let fields = Value::serialized(&key).unwrap().into_map().unwrap();
+
+    for options in [ParseOptions::new(), ParseOptions::new().any_order(true)] {
+        for i in 0..fields.len() {
+            let mut duplicated = fields.clone();
+            duplicated.insert(i + 1, fields[i].clone());
+            let error = deserialize_error(duplicated, options);
+            assert!(error.contains("duplicate field"), "{error}");
+
+            let mut duplicated = fields.clone();
+            duplicated.push(fields[i].clone());
+            let error = deserialize_error(duplicated, options);
+            assert!(error.contains("duplicate field"), "{error}");
+        }
+    }
+
+    let mut reordered = fields.clone();
+    reordered.swap(0, 1);
+    let error = deserialize_error(reordered, ParseOptions::new());
+    assert!(error.contains("wrong order"), "{error}");
+}
+
+#[test]
+fn error_display() {
+    let error = Error::WrongAlg {
+        expected: Alg::Es256,
+        actual: Alg::EdDsa,
+    };
+    assert_eq!(error.to_string(), "label 3: expected alg -7, got -8");
+
+    assert_eq!(error.code(), 17);
+    assert_eq!(Error::Truncated.code(), 1);
+    assert_eq!(Error::SmallOrder { label: -2 }.code(), 21);
+    assert_eq!(Error::DisallowedAlg(Alg::EdDsa).code(), 22);
+    assert_eq!(Error::CoordinateOutOfRange { label: -2 }.code(), 23);
+    assert_eq!(Error::KeyOpsTooLong.code(), 24);
+    assert_eq!(Error::KeyOpNotPermitted(KeyOp::Sign).code(), 25);
+    assert_eq!(
+        Error::KeyOpNotPermitted(KeyOp::Other(-1)).to_string(),
+        "label 4: key operation -1 is not permitted"
+    );
+    let error = Error::InvalidBase64Url { label: -2 };
+    assert_eq!(error.code(), 26);
+    assert_eq!(error.to_string(), "label -2: invalid base64url");
+
+    let error = Error::UnsupportedValue {
+        label: -1,
+        value: i64::MIN,
+    };
+    assert_eq!(
+        error.to_string(),
+        "label -1: unsupported crv -9223372036854775808"
+    );
+    let error = Error::UnexpectedTag(u64::MAX);
+    assert_eq!(error.to_string(), "unexpected tag 18446744073709551615");
+    assert_eq!(
+        Error::TrailingData { consumed: 0 }.to_string(),
+        "trailing data after 0 bytes"
+    );
+    assert_eq!(Alg::Other(-65536).to_string(), "-65536");
+
+    let error: Box<dyn std::error::Error> = Box::new(Error::Truncated);
+    assert_eq!(error.to_string(), "unexpected end of input");
+    assert!(error.source().is_none());
+}
+
+#[test]
+fn display() {
+    assert_eq!(Kty::Ec2.to_string(), "EC2");
+    assert_eq!(Crv::P256.to_string(), "P-256");
+    assert_eq!(Alg::EdDsa.to_string(), "EdDSA");
+    assert_eq!(Alg::from_i32(-1000).to_string(), "-1000");
+
+    let mut x = [0x00; 32];
+    x[..2].copy_from_slice(&[0xab, 0x12]);
+    let key = P256PublicKey::from_coordinates(x, [0xcd; 32]);
+    assert_eq!(key.to_string(), "EC2/P-256/ES256 x=ab12… y=cdcd…");
+    assert_eq!(PublicKey::from(key).to_string(), key.to_string());
+    assert_eq!(
+        format!("{key:#}"),
+        format!(
+            "EC2/P-256/ES256 x=ab12{} y={}",
+            "00".repeat(30),
+            "cd".repeat(32)
+        )
+    );
+
+    let key = EcdhEsHkdf256PublicKey::from_coordinates(x, [0xcd; 32]);
+    assert_eq!(
+        key.to_string(),
+        "EC2/P-256/ECDH-ES + HKDF-256 x=ab12… y=cdcd…"
+    );
+    let key = Ed25519PublicKey::from(x);
+    assert_eq!(
+        PublicKey::from(key).to_string(),
+        "OKP/Ed25519/EdDSA x=ab12…"
+    );
+    assert_eq!(
+        PublicKey::from(TotpPublicKey {}).to_string(),
+        "Symmetric/TOTP"
+    );
+}
+
+#[test]
+fn borrowed() {
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let serialized = serde_cbor::to_vec(&key).unwrap();
+    let borrowed: P256PublicKeyRef<'_> = serde_cbor::from_slice(&serialized).unwrap();
+    assert_eq!(borrowed, P256PublicKeyRef::from(&key));
+    assert_eq!(P256PublicKey::from(borrowed.clone()), key);
+    assert_eq!(serde_cbor::to_vec(&borrowed).unwrap(), serialized);
+    assert_eq!(borrowed.to_string(), key.to_string());
+
+    let key = EcdhEsHkdf256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let serialized = serde_cbor::to_vec(&key).unwrap();
+    let borrowed: EcdhEsHkdf256PublicKeyRef<'_> = serde_cbor::from_slice(&serialized).unwrap();
+    assert_eq!(EcdhEsHkdf256PublicKey::from(borrowed), key);
+    assert!(serde_cbor::from_slice::<P256PublicKeyRef<'_>>(&serialized).is_err());
+
+    let key = Ed25519PublicKey::from([0x03; 32]);
+    let serialized = serde_cbor::to_vec(&key).unwrap();
+    let borrowed: Ed25519PublicKeyRef<'_> = serde_cbor::from_slice(&serialized).unwrap();
+    assert_eq!(borrowed, Ed25519PublicKeyRef::from(&key));
+    assert_eq!(Ed25519PublicKey::from(borrowed), key);
+}
+
+#[test]
+fn de_error_context() {
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let fields = Value::serialized(&key).unwrap().into_map().unwrap();
+    let with_field = |i: usize, value: Value| {
+        let mut fields = fields.clone();
+        fields[i].1 = value;
+        deserialize_error(fields, ParseOptions::new())
+    };
+
+    assert_eq!(
+        with_field(2, Value::Integer(6.into())),
+        "label -1: expected crv 1, got 6"
+    );
+    assert_eq!(
+        with_field(2, Value::Integer(2.into())),
+        "label -1: expected crv 1, got 2"
+    );
+    assert_eq!(
+        with_field(2, Value::Integer(0x1_0000_0000_i64.into())),
+        "label -1: unsupported crv 4294967296"
+    );
+    assert_eq!(
+        with_field(1, Value::Integer((-8).into())),
+        "label 3: expected alg -7, got -8"
+    );
+    assert_eq!(
+        with_field(0, Value::Integer(1.into())),
+        "label 1: expected kty 2, got 1"
+    );
+    // coordinates are only read as byte strings, never element by element
+    let array = Value::Array(vec![Value::Integer(1.into()); 32]);
+    assert_eq!(
+        with_field(3, array),
+        "invalid type: sequence, expected a byte string with at most 32 bytes"
+    );
+
+    let mut missing = fields.clone();
+    missing.remove(4);
+    assert_eq!(
+        deserialize_error(missing, ParseOptions::new()),
+        "label -3: missing field `y`"
+    );
+}
+
+#[test]
+fn de_invalid_length() {
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let fields = Value::serialized(&key).unwrap().into_map().unwrap();
+    for (i, name) in [(3, "x"), (4, "y")] {
+        for len in [0, 31, 33, 64] {
+            let mut fields = fields.clone();
+            fields[i].1 = Value::Bytes(vec![0x01; len]);
+            let label = -(i as i8 - 1);
+            assert_eq!(
+                deserialize_error(fields, ParseOptions::new()),
+                format!("label {label}: expected 32 bytes for {name}, got {len}")
+            );
+        }
     }
 
-    fn de_order_ed25519(x: Input) -> bool {
-        test_de_order(Ed25519PublicKey {
-            x: x.0,
+    let key = Ed25519PublicKey::from([0x01; 32]);
+    let mut fields = Value::serialized(&key).unwrap().into_map().unwrap();
+    fields[3].1 = Value::Bytes(vec![0x01; 16]);
+    let (deserialized, _) = deserialize_map_with::<Ed25519PublicKey>(fields, ParseOptions::new());
+    assert!(deserialized.is_err());
+}
+
+#[test]
+fn raw_cose_key() {
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let serialized = cbor_serialize_bytes::<_, 128>(&key).unwrap();
+    let raw: RawCoseKey = cbor_deserialize(&serialized).unwrap();
+    assert_eq!(raw.kty, Some(Kty::Ec2));
+    assert_eq!(raw.alg, Some(Alg::Es256));
+    assert_eq!(raw.crv, Some(Crv::P256));
+    assert_eq!(raw, RawCoseKey::from(key));
+    assert_eq!(raw, RawCoseKey::from(PublicKey::from(key)));
+    assert_eq!(cbor_serialize_bytes::<_, 128>(&raw).unwrap(), serialized);
+    assert_eq!(P256PublicKey::try_from(raw.clone()), Ok(key));
+    assert_eq!(PublicKey::try_from(raw.clone()), Ok(key.into()));
+    assert_eq!(
+        Ed25519PublicKey::try_from(raw.clone()),
+        Err(Error::WrongKty {
+            expected: Kty::Okp,
+            actual: Kty::Ec2
+        })
+    );
+
+    let mut raw = raw;
+    raw.crv = Some(Crv::Ed25519);
+    raw.y = None;
+    let serialized = cbor_serialize_bytes::<_, 128>(&raw).unwrap();
+    let deserialized: RawCoseKey = cbor_deserialize(&serialized).unwrap();
+    assert_eq!(deserialized, raw);
+    assert_eq!(
+        PublicKey::try_from(raw),
+        Err(Error::WrongCrv {
+            expected: Crv::P256,
+            actual: Crv::Ed25519
         })
+    );
+}
+
+#[test]
+fn de_short_coordinates() {
+    let mut x = [0x01; 32];
+    x[0] = 0x00;
+    let mut y = [0x02; 32];
+    y[..2].fill(0x00);
+    let key = P256PublicKey::from_coordinates(x, y);
+    let mut fields = Value::serialized(&key).unwrap().into_map().unwrap();
+    fields[3].1 = Value::Bytes(x[1..].to_vec());
+    fields[4].1 = Value::Bytes(y[2..].to_vec());
+
+    let (deserialized, _) =
+        deserialize_map_with::<P256PublicKey>(fields.clone(), ParseOptions::new());
+    assert!(deserialized.is_err());
+    let options = ParseOptions::new().pad_short_coordinates(true);
+    let (deserialized, _) = deserialize_map_with::<P256PublicKey>(fields.clone(), options);
+    assert_eq!(deserialized.unwrap(), key);
+    let (deserialized, _) = deserialize_map_with::<PublicKey>(fields, options);
+    assert_eq!(deserialized.unwrap(), key.into());
+
+    // OKP keys are not padded
+    let key = Ed25519PublicKey::from([0x01; 32]);
+    let mut fields = Value::serialized(&key).unwrap().into_map().unwrap();
+    fields[3].1 = Value::Bytes(vec![0x01; 31]);
+    let (deserialized, _) = deserialize_map_with::<Ed25519PublicKey>(fields, options);
+    assert!(deserialized.is_err());
+}
+
+#[test]
+fn de_profiles() {
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let fields = Value::serialized(&key).unwrap().into_map().unwrap();
+    let mut reordered = fields.clone();
+    reordered.swap(0, 1);
+    let mut unknown = fields.clone();
+    unknown.push((Value::Integer(2.into()), Value::Bytes(vec![0xff; 16])));
+
+    let mut without_alg = fields.clone();
+    without_alg.remove(1);
+
+    let strict = ParseOptions::from_profile(Profile::Ctap2Canonical);
+    let lenient = ParseOptions::from(Profile::Rfc9052Lenient);
+    assert_eq!(
+        strict,
+        ParseOptions::new()
+            .require_alg(true)
+            .deny_unknown_fields(true)
+    );
+    assert_eq!(
+        lenient,
+        ParseOptions::new()
+            .any_order(true)
+            .allow_non_minimal(true)
+            .tags(TagPolicy::Accept)
+    );
+
+    let deserialize = |fields: &[(Value, Value)], options| {
+        deserialize_map_with::<P256PublicKey>(fields.to_vec(), options).0
+    };
+    for options in [strict, lenient] {
+        assert_eq!(deserialize(&fields, options).unwrap(), key);
+    }
+    assert!(deserialize(&reordered, strict).is_err());
+    assert_eq!(deserialize(&reordered, lenient).unwrap(), key);
+
+    // the profiles differ in the handling of unknown fields and of a missing `alg`
+    assert_eq!(
+        deserialize_error(unknown.clone(), strict),
+        "unknown field with label 2"
+    );
+    assert_eq!(deserialize(&unknown, lenient).unwrap(), key);
+    assert!(
+        deserialize_map_with::<PublicKey>(without_alg.clone(), strict)
+            .0
+            .is_err()
+    );
+    assert_eq!(
+        deserialize_map_with::<PublicKey>(without_alg, lenient)
+            .0
+            .unwrap(),
+        PublicKey::P256Key(key)
+    );
+}
+
+#[test]
+fn de_key_ops() {
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let mut fields = Value::serialized(&key).unwrap().into_map().unwrap();
+    let key_ops = Value::Array(vec![
+        Value::Text("verify".to_owned()),
+        Value::Integer(2.into()),
+    ]);
+    fields.insert(2, (Value::Integer(4.into()), key_ops));
+
+    // key_ops requires a self-describing deserializer, so serde_cbor is used instead of cbor-smol
+    fn deserialize<T: DeserializeWith>(
+        fields: Vec<(Value, Value)>,
+        options: ParseOptions,
+    ) -> Result<T, serde_cbor::Error> {
+        let mut serialized: Vec<u8> = Default::default();
+        ciborium::into_writer(&Value::Map(fields), &mut serialized).unwrap();
+        T::deserialize_with(
+            &mut serde_cbor::Deserializer::from_slice(&serialized),
+            options,
+        )
+    }
+
+    // the key types check key_ops but do not keep it
+    for options in [
+        ParseOptions::new(),
+        ParseOptions::new().deny_unknown_fields(true),
+    ] {
+        assert_eq!(
+            deserialize::<P256PublicKey>(fields.clone(), options).unwrap(),
+            key
+        );
+    }
+
+    // RawCoseKey keeps the text and integer entries in their order
+    let mut serialized: Vec<u8> = Default::default();
+    ciborium::into_writer(&Value::Map(fields.clone()), &mut serialized).unwrap();
+    let raw: RawCoseKey = ciborium::from_reader(&serialized[..]).unwrap();
+    assert_eq!(
+        serde_cbor::from_slice::<RawCoseKey>(&serialized).unwrap(),
+        raw
+    );
+    let expected = [
+        KeyOpEntry::text("verify").unwrap(),
+        KeyOpEntry::Int(KeyOp::Verify),
+    ];
+    assert_eq!(raw.key_ops.as_deref(), Some(&expected[..]));
+    assert!(expected.iter().all(|op| op.op() == Some(KeyOp::Verify)));
+    assert_eq!(raw.encoded_len(), serialized.len());
+    assert_compatible_ser(&serialized, &raw);
+    assert_eq!(
+        cbor_serialize_bytes::<_, 256>(&raw).unwrap().as_slice(),
+        serialized
+    );
+
+    let errors = [
+        (Value::Array(vec![]), "invalid COSE_Key"),
+        (
+            Value::Array(vec![Value::Integer(1.into()); MAX_KEY_OPS + 1]),
+            "label 4: too many or too long key operations",
+        ),
+        (
+            Value::Array(vec![Value::Text("a".repeat(MAX_KEY_OP_LEN + 1))]),
+            "label 4: too many or too long key operations",
+        ),
+        (
+            Value::Array(vec![Value::Integer((1i64 << 40).into())]),
+            "label 4: unsupported key_ops 1099511627776",
+        ),
+        (Value::Array(vec![Value::Bytes(vec![])]), ""),
+        (Value::Integer(1.into()), ""),
+    ];
+    for (value, error) in errors {
+        let mut fields = fields.clone();
+        fields[2].1 = value;
+        let message = deserialize_error(fields.clone(), ParseOptions::new());
+        if !error.is_empty() {
+            assert_eq!(message, error);
+        }
+        assert!(deserialize::<PublicKey>(fields, ParseOptions::new()).is_err());
+    }
+
+    let mut duplicate = fields.clone();
+    duplicate.insert(3, duplicate[2].clone());
+    assert_eq!(
+        deserialize_error(duplicate, ParseOptions::new().any_order(true)),
+        "label 4: duplicate field `key_ops`"
+    );
+
+    let mut after_crv = fields.clone();
+    let entry = after_crv.remove(2);
+    after_crv.insert(3, entry);
+    assert_eq!(
+        deserialize_error(after_crv.clone(), ParseOptions::new()),
+        "public key data in wrong order"
+    );
+    let options = ParseOptions::new().any_order(true);
+    assert_eq!(
+        deserialize::<P256PublicKey>(after_crv, options).unwrap(),
+        key
+    );
+}
+
+#[test]
+fn enforce_key_ops() {
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let mut raw = RawCoseKey::from(key);
+    raw.alg = None;
+    assert!(raw.permits(KeyOp::DeriveKey));
+    assert_eq!(raw.assert_permits_use(KeyUse::KeyAgreement), Ok(()));
+
+    // a verify-only key
+    raw.key_ops = Some([KeyOp::Verify.into()].into_iter().collect());
+    assert!(raw.permits(KeyOp::Verify));
+    assert!(!raw.permits(KeyOp::DeriveKey));
+    assert_eq!(raw.assert_permits(KeyOp::Verify), Ok(()));
+    assert_eq!(
+        raw.assert_permits(KeyOp::Sign),
+        Err(Error::KeyOpNotPermitted(KeyOp::Sign))
+    );
+    assert_eq!(raw.assert_permits_use(KeyUse::Signature), Ok(()));
+    assert_eq!(
+        raw.assert_permits_use(KeyUse::KeyAgreement),
+        Err(Error::KeyOpNotPermitted(KeyOp::DeriveKey))
+    );
+    assert_eq!(raw.assert_permits_use(KeyUse::Other), Ok(()));
+
+    let options = ParseOptions::new().enforce_key_ops(true);
+    let ecdh = ParseOptions::new().ec2_without_alg(Ec2Variant::EcdhEsHkdf256);
+    assert_eq!(
+        PublicKey::from_raw(raw.clone(), options),
+        Ok(PublicKey::P256Key(key))
+    );
+    assert_eq!(
+        PublicKey::from_raw(raw.clone(), ecdh.enforce_key_ops(true)),
+        Err(Error::KeyOpNotPermitted(KeyOp::DeriveKey))
+    );
+    assert!(PublicKey::from_raw(raw.clone(), ecdh).is_ok());
+
+    // text entries are matched by name, including the JWK names
+    raw.key_ops = Some(
+        [KeyOpEntry::text("deriveBits").unwrap()]
+            .into_iter()
+            .collect(),
+    );
+    assert_eq!(raw.assert_permits_use(KeyUse::KeyAgreement), Ok(()));
+    assert!(PublicKey::from_raw(raw.clone(), ecdh.enforce_key_ops(true)).is_ok());
+    assert_eq!(
+        PublicKey::from_raw(raw.clone(), options),
+        Err(Error::KeyOpNotPermitted(KeyOp::Verify))
+    );
+
+    // TOTP keys are MAC keys
+    let mut totp = RawCoseKey::from(TotpPublicKey {});
+    totp.key_ops = Some([KeyOp::MacCreate.into()].into_iter().collect());
+    assert!(PublicKey::from_raw(totp.clone(), options).is_ok());
+    totp.key_ops = Some([KeyOp::Verify.into()].into_iter().collect());
+    assert_eq!(
+        PublicKey::from_raw(totp, options),
+        Err(Error::KeyOpNotPermitted(KeyOp::MacVerify))
+    );
+
+    // the key types enforce key_ops when they are deserialized with the options
+    raw.alg = Some(Alg::EcdhEsHkdf256);
+    raw.key_ops = Some([KeyOp::Verify.into()].into_iter().collect());
+    let serialized = serde_cbor::to_vec(&raw).unwrap();
+    let deserialize = |options| {
+        let mut deserializer = serde_cbor::Deserializer::from_slice(&serialized);
+        EcdhEsHkdf256PublicKey::deserialize_with(&mut deserializer, options)
+            .map_err(|err| err.to_string())
+    };
+    assert!(deserialize(ParseOptions::new()).is_ok());
+    assert_eq!(
+        deserialize(options).unwrap_err(),
+        "label 4: key operation derive key is not permitted"
+    );
+}
+
+#[test]
+fn de_allowed_algs() {
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let fields = Value::serialized(&key).unwrap().into_map().unwrap();
+    let mut without_alg = fields.clone();
+    without_alg.remove(1);
+
+    let approved = ParseOptions::new().allowed_algs(Some(&[Alg::Es256, Alg::Es384]));
+    let deserialize = |fields: &[(Value, Value)], options| {
+        deserialize_map_with::<PublicKey>(fields.to_vec(), options).0
+    };
+    assert_eq!(deserialize(&fields, approved).unwrap(), key.into());
+    assert_eq!(deserialize(&without_alg, approved).unwrap(), key.into());
+    assert!(deserialize(&without_alg, approved.require_alg(true)).is_err());
+
+    let es384_only = ParseOptions::new().allowed_algs(Some(&[Alg::Es384]));
+    assert!(deserialize(&fields, es384_only).is_err());
+    assert_eq!(
+        deserialize_error(fields, es384_only),
+        "label 3: alg -7 is not allowed"
+    );
+}
+
+#[test]
+fn de_text_labels() {
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let fields = Value::serialized(&key).unwrap().into_map().unwrap();
+    let text = |label: &str| Value::Text(label.to_owned());
+    let labels = [
+        [text("1"), text("3"), text("-1"), text("-2"), text("-3")],
+        [text("kty"), text("alg"), text("crv"), text("x"), text("y")],
+    ];
+
+    for labels in labels {
+        let mut fields: Vec<_> = labels
+            .into_iter()
+            .zip(fields.iter().map(|(_, v)| v.clone()))
+            .collect();
+        fields.push((text("foo"), Value::Null));
+        let mut serialized: Vec<u8> = Default::default();
+        ciborium::into_writer(&Value::Map(fields), &mut serialized).unwrap();
+
+        let options = ParseOptions::new().text_labels(true);
+        let mut deserializer = serde_cbor::Deserializer::from_slice(&serialized);
+        let deserialized = P256PublicKey::deserialize_with(&mut deserializer, options).unwrap();
+        assert_eq!(deserialized, key);
+
+        let mut deserializer = serde_cbor::Deserializer::from_slice(&serialized);
+        let options = options.deny_unknown_fields(true);
+        let error = P256PublicKey::deserialize_with(&mut deserializer, options).unwrap_err();
+        assert_eq!(error.to_string(), "unknown field with text label");
+
+        let mut deserializer = serde_cbor::Deserializer::from_slice(&serialized);
+        assert!(P256PublicKey::deserialize_with(&mut deserializer, ParseOptions::new()).is_err());
+    }
+}
+
+#[test]
+fn de_indefinite_length() {
+    // a5 replaced with bf, terminated with ff
+    let serialized = hex::decode("bf010203262001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap();
+    let key = P256PublicKey::from_coordinates([0xff; 32], [0xff; 32]);
+
+    for options in [ParseOptions::new(), ParseOptions::new().any_order(true)] {
+        let mut deserializer = serde_cbor::Deserializer::from_slice(&serialized);
+        let deserialized = P256PublicKey::deserialize_with(&mut deserializer, options).unwrap();
+        assert_eq!(deserialized, key);
+    }
+    let deserialized: P256PublicKey = ciborium::from_reader(serialized.as_slice()).unwrap();
+    assert_eq!(deserialized, key);
+}
+
+quickcheck::quickcheck! {
+    fn serde_p256(key: P256PublicKey) -> bool {
+        test_serde(key)
+    }
+
+    fn serde_ecdh(key: EcdhEsHkdf256PublicKey) -> bool {
+        test_serde(key)
+    }
+
+    fn serde_ed25519(key: Ed25519PublicKey) -> bool {
+        test_serde(key)
+    }
+
+    fn serde_public_key_p256(key: P256PublicKey) -> bool {
+        test_serde(PublicKey::P256Key(key))
+    }
+
+    fn serde_public_key_ecdh(key: EcdhEsHkdf256PublicKey) -> bool {
+        test_serde(PublicKey::EcdhEsHkdf256Key(key))
+    }
+
+    fn serde_public_key_ed25519(key: Ed25519PublicKey) -> bool {
+        test_serde(PublicKey::Ed25519Key(key))
+    }
+
+    fn serde_public_key(key: PublicKey) -> bool {
+        test_serde(key)
+    }
+
+    fn de_order_p256(key: P256PublicKey) -> bool {
+        test_de_order(key)
+    }
+
+    fn de_order_ecdh(key: EcdhEsHkdf256PublicKey) -> bool {
+        test_de_order(key)
+    }
+
+    fn de_order_ed25519(key: Ed25519PublicKey) -> bool {
+        test_de_order(key)
+    }
+
+    fn de_any_order_p256(key: P256PublicKey) -> bool {
+        test_de_any_order(key)
+    }
+
+    fn de_any_order_ecdh(key: EcdhEsHkdf256PublicKey) -> bool {
+        test_de_any_order(key)
+    }
+
+    fn de_any_order_ed25519(key: Ed25519PublicKey) -> bool {
+        test_de_any_order(key)
     }
 
-    fn de_alg_p256(x: Input, y: Input, alg: Option<i8>) -> bool {
-        test_de_alg(P256PublicKey {
-            x: x.0,
-            y: y.0,
-        }, alg)
+    fn de_alg_p256(key: P256PublicKey, alg: Option<i8>) -> bool {
+        test_de_alg(key, alg)
     }
 
-    fn de_alg_ecdh(x: Input, y: Input, alg: Option<i8>) -> bool {
-        test_de_alg(EcdhEsHkdf256PublicKey {
-            x: x.0,
-            y: y.0,
-        }, alg)
+    fn de_alg_ecdh(key: EcdhEsHkdf256PublicKey, alg: Option<i8>) -> bool {
+        test_de_alg(key, alg)
     }
 
-    fn de_alg_ed25519(x: Input, alg: Option<i8>) -> bool {
-        test_de_alg(Ed25519PublicKey {
-            x: x.0,
-        }, alg)
+    fn de_alg_ed25519(key: Ed25519PublicKey, alg: Option<i8>) -> bool {
+        test_de_alg(key, alg)
     }
 }
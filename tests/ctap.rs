@@ -0,0 +1,241 @@
+use cosey::{ctap, Alg};
+#[cfg(all(feature = "cbor", feature = "p256", feature = "serde"))]
+use cosey::{Crv, Error, P256PublicKey, ParseOptions, Profile, PublicKey, TagPolicy};
+#[cfg(all(feature = "cbor", feature = "p256", feature = "serde"))]
+#[cfg(all(feature = "cbor", feature = "p256", feature = "serde"))]
+const P256_KEY: &str = "a5010203262001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+
+#[cfg(all(feature = "cbor", feature = "p256", feature = "serde"))]
+fn auth_data(flags: u8, credential_id: &[u8], key: &[u8], extensions: &[u8]) -> Vec<u8> {
+    let mut data = vec![0xaa; 32];
+    data.push(flags);
+    data.extend_from_slice(&[0, 0, 0, 1]);
+    data.extend_from_slice(&[0xbb; 16]);
+    data.extend_from_slice(&u16::try_from(credential_id.len()).unwrap().to_be_bytes());
+    data.extend_from_slice(credential_id);
+    data.extend_from_slice(key);
+    data.extend_from_slice(extensions);
+    data
+}
+
+#[cfg(all(feature = "cbor", feature = "p256", feature = "serde"))]
+#[test]
+fn credential_public_key() {
+    let key = hex::decode(P256_KEY).unwrap();
+    let extensions = hex::decode("a16b6372656450726f7465637402").unwrap();
+    let data = auth_data(0xc5, &[0xcc; 16], &key, &extensions);
+
+    let (parsed, range) = ctap::credential_public_key(&data).unwrap();
+    assert_eq!(
+        parsed,
+        PublicKey::P256Key(P256PublicKey {
+            x: [0xff; 32],
+            y: [0xff; 32],
+        })
+    );
+    assert_eq!(range, 71..71 + key.len());
+    assert_eq!(&data[range.end..], extensions);
+}
+
+#[cfg(all(feature = "cbor", feature = "p256", feature = "serde"))]
+#[test]
+fn credential_public_key_errors() {
+    let key = hex::decode(P256_KEY).unwrap();
+
+    let data = auth_data(0x01, &[0xcc; 16], &key, &[]);
+    assert_eq!(
+        ctap::credential_public_key(&data),
+        Err(Error::MissingAttestedCredentialData)
+    );
+
+    let data = auth_data(0x41, &[0xcc; 16], &key, &[]);
+    assert_eq!(
+        ctap::credential_public_key(&data[..40]),
+        Err(Error::Truncated)
+    );
+    assert_eq!(
+        ctap::credential_public_key(&data[..data.len() - 1]),
+        Err(Error::Truncated)
+    );
+
+    let data = auth_data(0x41, &[0xcc; 16], &[0x80], &[]);
+    assert_eq!(ctap::credential_public_key(&data), Err(Error::InvalidKey));
+}
+
+#[cfg(all(feature = "cbor", feature = "p256", feature = "serde"))]
+#[test]
+fn credential_public_key_invalid() {
+    fn parse(key: &str) -> Result<PublicKey, Error> {
+        let data = auth_data(0x41, &[0xcc; 16], &hex::decode(key).unwrap(), &[]);
+        ctap::credential_public_key(&data).map(|(key, _)| key)
+    }
+
+    assert_eq!(parse("a0"), Err(Error::MissingField("kty")));
+    // kty: EC2, alg: ES256, crv: P-256, x
+    assert_eq!(
+        parse(
+            "a4010203262001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"
+        ),
+        Err(Error::MissingField("y"))
+    );
+    // kty: EC2, alg: ES256, crv: Ed25519
+    assert_eq!(
+        parse("a3010203262006"),
+        Err(Error::WrongCrv {
+            expected: Crv::P256,
+            actual: Crv::Ed25519,
+        })
+    );
+    // kty: OKP, alg: ES256, crv: Ed25519
+    #[cfg(feature = "ed25519")]
+    assert_eq!(
+        parse("a3010103262006"),
+        Err(Error::WrongAlg {
+            expected: Alg::EdDsa,
+            actual: Alg::Es256,
+        })
+    );
+    // kty: EC2, alg: ES384
+    assert_eq!(
+        parse("a20102033822"),
+        Err(Error::UnsupportedValue {
+            label: 3,
+            value: -35
+        })
+    );
+    // kty: EC2, alg: ES256, crv: P-256, x (33 bytes)
+    assert_eq!(
+        parse("a4010203262001215821ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff"),
+        Err(Error::InvalidLength {
+            label: -2,
+            expected: 32,
+            actual: 33
+        })
+    );
+    // alg: ES256, kty: EC2
+    assert_eq!(parse("a203260102"), Err(Error::NonCanonicalOrder));
+    // kty: EC2, kty: EC2
+    assert_eq!(parse("a201020102"), Err(Error::DuplicateField("kty")));
+}
+
+#[cfg(all(feature = "cbor", feature = "p256", feature = "serde"))]
+#[test]
+fn credential_public_key_non_minimal() {
+    // P256_KEY with the label of kty encoded as 0x1801 and the length of x as 0x590020
+    let key = hex::decode("a5180102032620012159002000000000000000000000000000000000000000000000000000000000000000ff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff").unwrap();
+    let data = auth_data(0x41, &[0xcc; 16], &key, &[]);
+
+    assert_eq!(ctap::credential_public_key(&data), Err(Error::NonMinimal));
+    let options = ParseOptions::new().allow_non_minimal(true);
+    for options in [options, Profile::Rfc9052Lenient.into()] {
+        let mut x = [0; 32];
+        x[31] = 0xff;
+        let expected = P256PublicKey::from_coordinates(x, [0xff; 32]);
+        assert_eq!(
+            ctap::credential_public_key_with(&data, options),
+            Ok((expected.into(), 71..data.len()))
+        );
+    }
+}
+
+#[cfg(all(feature = "cbor", feature = "p256", feature = "serde"))]
+#[test]
+fn credential_public_key_tagged() {
+    let key = P256PublicKey::from_coordinates([0xff; 32], [0xff; 32]);
+    // P256_KEY with tag 1234
+    let tagged = hex::decode(format!("d904d2{P256_KEY}")).unwrap();
+    let data = auth_data(0x41, &[0xcc; 16], &tagged, &[]);
+    let parse = |options| ctap::credential_public_key_with(&data, options);
+
+    assert_eq!(parse(ParseOptions::new()), Err(Error::UnexpectedTag(1234)));
+    assert_eq!(
+        parse(ParseOptions::new().tags(TagPolicy::Only(24))),
+        Err(Error::UnexpectedTag(1234))
+    );
+    for options in [
+        ParseOptions::new().tags(TagPolicy::Accept),
+        ParseOptions::new().tags(TagPolicy::Only(1234)),
+        Profile::Rfc9052Lenient.into(),
+    ] {
+        assert_eq!(parse(options), Ok((key.into(), 71..data.len())));
+    }
+
+    // P256_KEY with tag 1234 on the value of crv
+    let tagged = P256_KEY.replacen("2001", "20d904d201", 1);
+    let data = auth_data(0x41, &[0xcc; 16], &hex::decode(tagged).unwrap(), &[]);
+    assert_eq!(
+        ctap::credential_public_key(&data),
+        Err(Error::UnexpectedTag(1234))
+    );
+    let options = ParseOptions::new().tags(TagPolicy::Only(24));
+    assert_eq!(
+        ctap::credential_public_key_with(&data, options),
+        Ok((key.into(), 71..data.len()))
+    );
+}
+
+#[cfg(all(feature = "cbor", feature = "p256", feature = "serde"))]
+#[test]
+fn credential_public_key_truncated() {
+    let keys = [
+        P256_KEY.to_owned(),
+        // indefinite-length map with an unknown field containing an array and a text string
+        P256_KEY.replacen("a5", "bf", 1) + "18639f63666f6fff" + "ff",
+    ];
+    for key in keys {
+        let key = hex::decode(key).unwrap();
+        let data = auth_data(0x41, &[0xcc; 16], &key, &[]);
+        assert!(ctap::credential_public_key_with(&data, Profile::Rfc9052Lenient.into()).is_ok());
+        for len in 0..data.len() {
+            assert_eq!(
+                ctap::credential_public_key_with(&data[..len], Profile::Rfc9052Lenient.into()),
+                Err(Error::Truncated),
+                "{len}"
+            );
+        }
+    }
+}
+
+#[test]
+fn select_alg() {
+    // ECDH-ES + HKDF-256 is not a signature algorithm
+    assert_eq!(ctap::select_alg([-25, -257]), None);
+    assert_eq!(ctap::select_alg([]), None);
+}
+
+#[cfg(all(feature = "p256", feature = "ed25519"))]
+#[test]
+fn select_alg_preference() {
+    assert_eq!(ctap::CREDENTIAL_ALGS, [Alg::Es256, Alg::EdDsa]);
+    // RS256, ES256, EdDSA
+    assert_eq!(ctap::select_alg([-257, -7, -8]), Some(Alg::Es256));
+    assert_eq!(ctap::select_alg([-8, -7]), Some(Alg::EdDsa));
+}
+
+#[cfg(feature = "p256")]
+#[test]
+fn select_alg_p256() {
+    assert!(ctap::CREDENTIAL_ALGS.contains(&Alg::Es256));
+    assert_eq!(ctap::select_alg([-257, -7]), Some(Alg::Es256));
+}
+
+#[cfg(not(feature = "p256"))]
+#[test]
+fn select_alg_without_p256() {
+    assert!(!ctap::CREDENTIAL_ALGS.contains(&Alg::Es256));
+    assert_eq!(ctap::select_alg([-7]), None);
+}
+
+#[cfg(feature = "ed25519")]
+#[test]
+fn select_alg_ed25519() {
+    assert!(ctap::CREDENTIAL_ALGS.contains(&Alg::EdDsa));
+    assert_eq!(ctap::select_alg([-257, -8]), Some(Alg::EdDsa));
+}
+
+#[cfg(not(feature = "ed25519"))]
+#[test]
+fn select_alg_without_ed25519() {
+    assert!(!ctap::CREDENTIAL_ALGS.contains(&Alg::EdDsa));
+    assert_eq!(ctap::select_alg([-8]), None);
+}
@@ -0,0 +1,268 @@
+#![cfg(feature = "serde")]
+
+use ciborium::Value;
+use cosey::{define_cose_key, Alg, CoseKeyType, Crv, Kty, ParseOptions};
+use serde::{
+    de::{DeserializeOwned, DeserializeSeed as _},
+    Serialize,
+};
+
+define_cose_key! {
+    /// A key with a curve and two parameters
+    pub struct CurveKey {
+        kty: Kty::Ec2,
+        alg: Alg::Es384,
+        crv: Some(Crv::P384),
+        pub x: [u8; 48] = -2,
+        pub y: [u8; 48] = -3,
+    }
+}
+
+define_cose_key! {
+    struct LargeKey {
+        kty: Kty::Other(7),
+        alg: Alg::Other(-48),
+        crv: None,
+        pub_key: [u8; 1312] = -1,
+    }
+}
+
+define_cose_key! {
+    struct LargeKeyRef<'a> {
+        kty: Kty::Other(7),
+        alg: Alg::Other(-48),
+        crv: None,
+        pub_key: &'a [u8; 1312] = -1,
+    }
+}
+
+fn curve_key() -> CurveKey {
+    CurveKey {
+        x: [0x01; 48],
+        y: [0x02; 48],
+    }
+}
+
+fn round_trip<T: Serialize + DeserializeOwned + core::fmt::Debug + PartialEq>(key: &T) -> Vec<u8> {
+    let serialized = serde_cbor::to_vec(key).unwrap();
+    let mut ciborium_serialized = Vec::new();
+    ciborium::into_writer(key, &mut ciborium_serialized).unwrap();
+    assert_eq!(serialized, ciborium_serialized);
+
+    let deserialized: T = serde_cbor::from_slice(&serialized).unwrap();
+    assert_eq!(&deserialized, key);
+    let deserialized: T = ciborium::from_reader(serialized.as_slice()).unwrap();
+    assert_eq!(&deserialized, key);
+    serialized
+}
+
+fn deserialize_error(fields: Vec<(Value, Value)>, options: ParseOptions) -> String {
+    let mut serialized = Vec::new();
+    ciborium::into_writer(&Value::Map(fields), &mut serialized).unwrap();
+    let mut deserializer = serde_cbor::Deserializer::from_slice(&serialized);
+    CurveKey::deserialize_with(&mut deserializer, options)
+        .unwrap_err()
+        .to_string()
+}
+
+#[test]
+fn define_serde() {
+    let key = curve_key();
+    let serialized = round_trip(&key);
+    assert_eq!(serialized.len(), key.encoded_len());
+    assert_eq!(serialized.len(), CurveKey::MAX_ENCODED_LEN);
+    assert_eq!(
+        &serialized[..8],
+        &[0xa5, 0x01, 0x02, 0x03, 0x38, 0x22, 0x20, 0x02]
+    );
+    assert_eq!(CurveKey::KTY, Kty::Ec2);
+    assert_eq!(CurveKey::ALG, Alg::Es384);
+    assert_eq!(CurveKey::CRV, Some(Crv::P384));
+    assert_eq!(<CurveKey as CoseKeyType>::PARAMS, &[(-2, 48), (-3, 48)]);
+    assert_eq!(
+        <CurveKey as CoseKeyType>::MAX_ENCODED_LEN,
+        CurveKey::MAX_ENCODED_LEN
+    );
+
+    let key = LargeKey {
+        pub_key: [0x03; 1312],
+    };
+    let serialized = round_trip(&key);
+    assert_eq!(serialized.len(), key.encoded_len());
+    assert_eq!(serialized.len(), LargeKey::MAX_ENCODED_LEN);
+    assert_eq!(&serialized[..6], &[0xa3, 0x01, 0x07, 0x03, 0x38, 0x2f]);
+}
+
+#[test]
+fn define_options() {
+    let fields = Value::serialized(&curve_key()).unwrap().into_map().unwrap();
+
+    let mut without_alg = fields.clone();
+    without_alg.remove(1);
+    let mut serialized = Vec::new();
+    ciborium::into_writer(&Value::Map(without_alg.clone()), &mut serialized).unwrap();
+    let key: CurveKey = serde_cbor::from_slice(&serialized).unwrap();
+    assert_eq!(key, curve_key());
+    assert_eq!(
+        deserialize_error(without_alg, ParseOptions::new().require_alg(true)),
+        "label 3: missing field `alg`"
+    );
+    assert_eq!(
+        deserialize_error(fields.clone(), ParseOptions::new().allowed_algs(Some(&[]))),
+        "label 3: alg -35 is not allowed"
+    );
+
+    let mut unknown = fields.clone();
+    unknown.push((Value::Integer((-4).into()), Value::Bytes(vec![0x04; 48])));
+    let mut serialized = Vec::new();
+    ciborium::into_writer(&Value::Map(unknown.clone()), &mut serialized).unwrap();
+    let key: CurveKey = serde_cbor::from_slice(&serialized).unwrap();
+    assert_eq!(key, curve_key());
+    assert_eq!(
+        deserialize_error(unknown, ParseOptions::new().deny_unknown_fields(true)),
+        "unknown field with label -4"
+    );
+
+    let mut reordered = fields.clone();
+    reordered.swap(3, 4);
+    assert_eq!(
+        deserialize_error(reordered.clone(), ParseOptions::new()),
+        "public key data in wrong order"
+    );
+    let mut serialized = Vec::new();
+    ciborium::into_writer(&Value::Map(reordered), &mut serialized).unwrap();
+    let mut deserializer = serde_cbor::Deserializer::from_slice(&serialized);
+    let key =
+        CurveKey::deserialize_with(&mut deserializer, ParseOptions::new().any_order(true)).unwrap();
+    assert_eq!(key, curve_key());
+}
+
+#[test]
+fn define_errors() {
+    let fields = Value::serialized(&curve_key()).unwrap().into_map().unwrap();
+    let with_field = |i: usize, value: Value| {
+        let mut fields = fields.clone();
+        fields[i].1 = value;
+        deserialize_error(fields, ParseOptions::new())
+    };
+
+    assert_eq!(
+        with_field(1, Value::Integer((-7).into())),
+        "label 3: expected alg -35, got -7"
+    );
+    assert_eq!(
+        with_field(2, Value::Integer(1.into())),
+        "label -1: expected crv 2, got 1"
+    );
+    assert_eq!(
+        with_field(3, Value::Bytes(vec![0x01; 32])),
+        "label -2: expected 48 bytes for x, got 32"
+    );
+
+    let mut missing = fields.clone();
+    missing.remove(4);
+    assert_eq!(
+        deserialize_error(missing, ParseOptions::new()),
+        "label -3: missing field `y`"
+    );
+
+    let mut duplicated = fields.clone();
+    duplicated.insert(4, fields[3].clone());
+    assert_eq!(
+        deserialize_error(duplicated, ParseOptions::new()),
+        "label -2: duplicate field `x`"
+    );
+}
+
+#[test]
+fn define_ord() {
+    let keys: Vec<_> = [[0x00; 48], [0x01; 48], [0xff; 48]]
+        .into_iter()
+        .map(|x| CurveKey { x, y: [0x01; 48] })
+        .collect();
+    for a in &keys {
+        for b in &keys {
+            let encoded_a = serde_cbor::to_vec(a).unwrap();
+            let encoded_b = serde_cbor::to_vec(b).unwrap();
+            assert_eq!(a.cmp(b), encoded_a.cmp(&encoded_b));
+        }
+    }
+}
+
+#[test]
+fn define_debug() {
+    let mut pub_key = [0xab; 1312];
+    pub_key[0] = 0x01;
+    let key = LargeKey { pub_key };
+    assert_eq!(
+        format!("{key:?}"),
+        "LargeKey { pub_key: <1312 bytes: 01ababababababab...> }"
+    );
+
+    let key = CurveKey {
+        x: [0x01; 48],
+        y: [0x02; 48],
+    };
+    assert_eq!(
+        format!("{key:?}"),
+        format!("CurveKey {{ x: {:?}, y: {:?} }}", [0x01; 48], [0x02; 48])
+    );
+}
+
+#[test]
+fn define_display() {
+    assert_eq!(curve_key().to_string(), "EC2/P-384/ES384 x=0101… y=0202…");
+    let key = LargeKey {
+        pub_key: [0x03; 1312],
+    };
+    assert_eq!(key.to_string(), "7/-48 pub_key=0303…");
+}
+
+#[test]
+fn define_borrowed() {
+    let key = LargeKey {
+        pub_key: [0x03; 1312],
+    };
+    let serialized = serde_cbor::to_vec(&key).unwrap();
+    let borrowed: LargeKeyRef<'_> = serde_cbor::from_slice(&serialized).unwrap();
+    assert_eq!(borrowed.pub_key, &key.pub_key);
+    assert_eq!(borrowed.encoded_len(), LargeKeyRef::MAX_ENCODED_LEN);
+    assert_eq!(serde_cbor::to_vec(&borrowed).unwrap(), serialized);
+    assert!(std::ptr::eq(
+        borrowed.pub_key.as_ptr(),
+        serialized[serialized.len() - 1312..].as_ptr()
+    ));
+
+    let mut deserializer = serde_cbor::Deserializer::from_reader(serialized.as_slice());
+    let error = LargeKeyRef::deserialize_with(&mut deserializer, ParseOptions::new()).unwrap_err();
+    assert_eq!(
+        error.to_string(),
+        "label -1: byte string cannot be borrowed from the input"
+    );
+}
+
+#[test]
+fn define_in_place() {
+    let key = LargeKey {
+        pub_key: [0x03; 1312],
+    };
+    let serialized = serde_cbor::to_vec(&key).unwrap();
+
+    let mut place = Box::new(LargeKey {
+        pub_key: [0x00; 1312],
+    });
+    let mut deserializer = serde_cbor::Deserializer::from_slice(&serialized);
+    LargeKey::deserialize_in_place_with(&mut deserializer, &mut place, ParseOptions::new())
+        .unwrap();
+    assert_eq!(*place, key);
+
+    let mut place = curve_key();
+    place.x = [0x00; 48];
+    let serialized = serde_cbor::to_vec(&curve_key()).unwrap();
+    let mut deserializer = serde_cbor::Deserializer::from_slice(&serialized);
+    (&mut place).deserialize(&mut deserializer).unwrap();
+    assert_eq!(place, curve_key());
+
+    let mut deserializer = serde_cbor::Deserializer::from_slice(&serialized[..20]);
+    assert!((&mut place).deserialize(&mut deserializer).is_err());
+}
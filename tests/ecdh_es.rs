@@ -0,0 +1,82 @@
+#![cfg(all(feature = "ecdh", feature = "p256-curve", feature = "rand_core"))]
+
+use cosey::ecdh_es::{agree_ephemeral, EphemeralKey};
+use cosey::{EcdhEsHkdf256PublicKey, Error};
+use rand_core::{CryptoRng, RngCore};
+
+// Not random, but sufficient to get distinct keys
+struct CounterRng(u8);
+
+impl RngCore for CounterRng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            self.0 = self.0.wrapping_add(1);
+            *byte = self.0;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for CounterRng {}
+
+fn generator() -> EcdhEsHkdf256PublicKey {
+    let x = "6b17d1f2e12c4247f8bce6e563a440f277037d812deb33a0f4a13945d898c296";
+    let y = "4fe342e2fe1a7f9b8ee7eb4a7c0f9e162bce33576b315ececbb6406837bf51f5";
+    EcdhEsHkdf256PublicKey {
+        x: hex::decode(x).unwrap().try_into().unwrap(),
+        y: hex::decode(y).unwrap().try_into().unwrap(),
+    }
+}
+
+#[test]
+fn agree() {
+    let mut rng = CounterRng(0);
+    let alice = EphemeralKey::generate(&mut rng);
+    let bob = EphemeralKey::generate(&mut rng);
+    assert_ne!(alice.public_key(), bob.public_key());
+
+    let secret = alice.agree(&bob.public_key()).unwrap();
+    assert_eq!(
+        secret.as_bytes(),
+        bob.agree(&alice.public_key()).unwrap().as_bytes()
+    );
+
+    // the shared secret with the generator is the x-coordinate of the public key
+    assert_eq!(
+        alice.agree(&generator()).unwrap().as_bytes(),
+        &alice.public_key().x
+    );
+
+    let (public_key, secret) = agree_ephemeral(&mut rng, &bob.public_key()).unwrap();
+    assert_eq!(
+        secret.as_bytes(),
+        bob.agree(&public_key).unwrap().as_bytes()
+    );
+}
+
+#[test]
+fn invalid_peer() {
+    let mut rng = CounterRng(0);
+    let key = EphemeralKey::generate(&mut rng);
+    let peer = EcdhEsHkdf256PublicKey {
+        x: [0x01; 32],
+        y: [0x02; 32],
+    };
+    assert_eq!(key.agree(&peer).err(), Some(Error::InvalidKey));
+    assert_eq!(
+        agree_ephemeral(&mut rng, &peer).map(|_| ()),
+        Err(Error::InvalidKey)
+    );
+}
@@ -0,0 +1,48 @@
+#![cfg(all(feature = "embedded-io", feature = "p256"))]
+
+use cosey::{P256PublicKey, PublicKey, RawCoseKey};
+
+const P256_KEY: &str = "a5010203262001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+
+// Accepts at most three bytes per write, like a transport with small packets
+struct Packets(Vec<Vec<u8>>);
+
+impl embedded_io::ErrorType for Packets {
+    type Error = core::convert::Infallible;
+}
+
+impl embedded_io::Write for Packets {
+    fn write(&mut self, buf: &[u8]) -> Result<usize, Self::Error> {
+        let len = buf.len().min(3);
+        self.0.push(buf[..len].to_vec());
+        Ok(len)
+    }
+
+    fn flush(&mut self) -> Result<(), Self::Error> {
+        Ok(())
+    }
+}
+
+#[test]
+fn write_to() {
+    let key = PublicKey::from(P256PublicKey::from_coordinates([0xff; 32], [0xff; 32]));
+    let expected = hex::decode(P256_KEY).unwrap();
+
+    let mut packets = Packets(Vec::new());
+    key.write_to(&mut packets).unwrap();
+    assert!(packets.0.iter().all(|packet| packet.len() <= 3));
+    assert_eq!(packets.0.concat(), expected);
+
+    let mut buffer = [0; 128];
+    let mut writer = &mut buffer[..];
+    RawCoseKey::from(key.clone()).write_to(&mut writer).unwrap();
+    let written = 128 - writer.len();
+    assert_eq!(&buffer[..written], expected);
+
+    let mut buffer = [0; 32];
+    let mut writer = &mut buffer[..];
+    assert_eq!(
+        key.write_to(&mut writer),
+        Err(embedded_io::SliceWriteError::Full)
+    );
+}
@@ -0,0 +1,61 @@
+#![cfg(feature = "fuzzing")]
+
+use cosey::fuzzing::{check, MALFORMED};
+use cosey::{Ec2Variant, ParseOptions, TagPolicy};
+
+fn options() -> [ParseOptions; 3] {
+    [
+        ParseOptions::new(),
+        ParseOptions::new()
+            .any_order(true)
+            .allow_non_minimal(true)
+            .tags(TagPolicy::Accept)
+            .pad_short_coordinates(true)
+            .ec2_without_alg(Ec2Variant::EcdhEsHkdf256),
+        ParseOptions::new()
+            .deny_unknown_fields(true)
+            .require_alg(true)
+            .max_string_len(Some(8)),
+    ]
+}
+
+#[test]
+fn malformed() {
+    for malformed in MALFORMED {
+        assert_eq!(
+            check(malformed.data, ParseOptions::new()),
+            Err(malformed.error),
+            "{}",
+            malformed.description
+        );
+        for options in options() {
+            let _ = check(malformed.data, options);
+        }
+    }
+}
+
+#[test]
+fn mutated() {
+    for malformed in MALFORMED {
+        let mut data = malformed.data.to_vec();
+        for i in 0..data.len() {
+            let original = data[i];
+            for byte in [0x00, 0x17, 0x18, 0x1f, 0x20, 0x40, 0x7f, 0xa0, 0xbf, 0xff] {
+                data[i] = byte;
+                for options in options() {
+                    let _ = check(&data, options);
+                }
+            }
+            data[i] = original;
+        }
+    }
+}
+
+quickcheck::quickcheck! {
+    fn arbitrary_input(data: Vec<u8>) -> bool {
+        for options in options() {
+            let _ = check(&data, options);
+        }
+        true
+    }
+}
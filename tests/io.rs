@@ -0,0 +1,47 @@
+#![cfg(all(feature = "std", feature = "p256"))]
+
+use std::io::{BufReader, ErrorKind, Read as _};
+
+use cosey::{Error, P256PublicKey, ParseOptions, PublicKey, RawCoseKey};
+
+const P256_KEY: &str = "a5010203262001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff";
+
+#[test]
+fn to_vec() {
+    let key = PublicKey::from(P256PublicKey::from_coordinates([0xff; 32], [0xff; 32]));
+    let expected = hex::decode(P256_KEY).unwrap();
+    assert_eq!(key.to_vec().unwrap(), expected);
+    assert_eq!(RawCoseKey::from(key.clone()).to_vec().unwrap(), expected);
+
+    let mut written = Vec::new();
+    key.to_writer(&mut written).unwrap();
+    RawCoseKey::from(key).to_writer(&mut written).unwrap();
+    assert_eq!(written, [expected.clone(), expected].concat());
+}
+
+#[test]
+fn from_reader() {
+    let mut data = hex::decode(P256_KEY).unwrap();
+    let (expected, _) = PublicKey::decode(&data, ParseOptions::default()).unwrap();
+    data.extend_from_slice(&[0x01, 0x02]);
+    for capacity in [1, 7, 256] {
+        let mut reader = BufReader::with_capacity(capacity, data.as_slice());
+        let key = PublicKey::from_reader(&mut reader, ParseOptions::default()).unwrap();
+        assert_eq!(key, expected);
+        let mut rest = Vec::new();
+        reader.read_to_end(&mut rest).unwrap();
+        assert_eq!(rest, [0x01, 0x02]);
+    }
+}
+
+#[test]
+fn from_reader_errors() {
+    let data = hex::decode(P256_KEY).unwrap();
+    let error = PublicKey::from_reader(&data[..40], ParseOptions::default()).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::UnexpectedEof);
+
+    let error = PublicKey::from_reader(&[0x80][..], ParseOptions::default()).unwrap_err();
+    assert_eq!(error.kind(), ErrorKind::InvalidData);
+    let inner = error.into_inner().unwrap().downcast::<Error>().unwrap();
+    assert_eq!(*inner, Error::InvalidKey);
+}
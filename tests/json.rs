@@ -0,0 +1,177 @@
+#![cfg(all(feature = "ed25519", feature = "p256", feature = "serde"))]
+
+use cosey::metadata::KeyWithMetadata;
+use cosey::{
+    define_cose_key, Alg, Bytes, Crv, Ed25519PublicKey, Kty, P256PublicKey, ParseOptions,
+    PrivateKey, PublicKey, RawCoseKey,
+};
+use serde::Serialize;
+use serde_json_core::de::Deserializer;
+
+define_cose_key! {
+    struct OddKey {
+        kty: Kty::Other(7),
+        alg: Alg::Other(-48),
+        crv: None,
+        pub_key: [u8; 100] = -1,
+    }
+}
+
+const P256_JSON: &str = concat!(
+    r#"{"1":2,"3":-7,"-1":1,"#,
+    r#""-2":"AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE","#,
+    r#""-3":"AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgI"}"#,
+);
+
+fn to_json<T: Serialize>(value: &T) -> String {
+    let mut buffer = [0; 1024];
+    let len = serde_json_core::to_slice(value, &mut buffer).unwrap();
+    String::from_utf8(buffer[..len].to_vec()).unwrap()
+}
+
+fn deserializer(json: &str) -> Deserializer<'_, 'static> {
+    Deserializer::new(json.as_bytes(), None)
+}
+
+fn human_readable() -> ParseOptions {
+    ParseOptions::new().human_readable(true)
+}
+
+fn p256_error(json: &str) -> String {
+    P256PublicKey::deserialize_with(&mut deserializer(json), human_readable())
+        .unwrap_err()
+        .to_string()
+}
+
+#[test]
+fn ser_json() {
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    assert_eq!(to_json(&key), P256_JSON);
+    assert_eq!(to_json(&PublicKey::from(key)), P256_JSON);
+    assert_eq!(to_json(&RawCoseKey::from(key)), P256_JSON);
+
+    let key = Ed25519PublicKey { x: [0xfb; 32] };
+    assert_eq!(
+        to_json(&key),
+        r#"{"1":1,"3":-8,"-1":6,"-2":"-_v7-_v7-_v7-_v7-_v7-_v7-_v7-_v7-_v7-_v7-_s"}"#
+    );
+}
+
+#[test]
+fn de_json_round_trip() {
+    let p256 = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let de = P256PublicKey::deserialize_with(&mut deserializer(P256_JSON), human_readable());
+    assert_eq!(de.unwrap(), p256);
+
+    let key = PublicKey::from(Ed25519PublicKey { x: [0xfb; 32] });
+    let json = to_json(&key);
+    let de = PublicKey::deserialize_with(&mut deserializer(&json), human_readable());
+    assert_eq!(de.unwrap(), key);
+
+    let raw = RawCoseKey::from(p256);
+    let de = RawCoseKey::deserialize_with(&mut deserializer(P256_JSON), human_readable());
+    assert_eq!(de.unwrap(), raw);
+
+    // more than one chunk of the encoder, and a length that is not a multiple of three
+    let mut pub_key = [0; 100];
+    for (i, byte) in pub_key.iter_mut().enumerate() {
+        *byte = i as u8;
+    }
+    let key = OddKey { pub_key };
+    let json = to_json(&key);
+    let de = OddKey::deserialize_with(&mut deserializer(&json), human_readable());
+    assert_eq!(de.unwrap(), key);
+
+    let mut entry = KeyWithMetadata::new(p256.into());
+    entry.kid = Some(Bytes::from_slice(b"kid-1").unwrap());
+    entry.not_after = Some(1_700_000_000);
+    entry.use_count = 3;
+    let json = to_json(&entry);
+    let de = KeyWithMetadata::deserialize_with(&mut deserializer(&json), human_readable());
+    assert_eq!(de.unwrap(), entry);
+
+    let json = r#"{"1":2,"-1":1,"-4":"BAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQ"}"#;
+    let private = PrivateKey::deserialize_with(&mut deserializer(json), human_readable());
+    let private = private.unwrap();
+    assert_eq!(
+        (private.kty, private.crv, private.d),
+        (Kty::Ec2, Crv::P256, [0x04; 32])
+    );
+    assert_eq!(to_json(&private), json);
+}
+
+#[test]
+fn de_json_jose_names() {
+    let json = concat!(
+        r#"{"kty":2,"alg":-7,"crv":1,"#,
+        r#""x":"AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE","#,
+        r#""y":"AgICAgICAgICAgICAgICAgICAgICAgICAgICAgICAgI"}"#,
+    );
+    let de = P256PublicKey::deserialize_with(&mut deserializer(json), human_readable());
+    assert_eq!(
+        de.unwrap(),
+        P256PublicKey::from_coordinates([0x01; 32], [0x02; 32])
+    );
+}
+
+#[test]
+fn de_json_errors() {
+    let replace = |from: &str, to: &str| P256_JSON.replacen(from, to, 1);
+    let x = "AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE";
+
+    // padding, invalid characters, impossible lengths and non-zero unused bits
+    for invalid in [
+        format!("{x}="),
+        x.replacen('Q', "+", 1),
+        x.replacen('Q', "/", 1),
+        format!("{x}AA"),
+        format!("{}F", &x[..42]),
+    ] {
+        let error = p256_error(&replace(x, &invalid));
+        assert_eq!(error, "label -2: invalid base64url", "{invalid}");
+    }
+
+    assert_eq!(
+        p256_error(&replace(x, &x[..40])),
+        "label -2: expected 32 bytes for x, got 30"
+    );
+    assert_eq!(
+        p256_error(&replace(x, &format!("{x}AAA"))),
+        "label -2: expected 32 bytes for x, got 34"
+    );
+
+    // the metadata only allows key IDs of up to 32 bytes
+    let entry = KeyWithMetadata::new(Ed25519PublicKey { x: [0x01; 32] }.into());
+    let json = to_json(&entry).replacen(r#","5":0"#, &format!(r#","2":"{}AAA","5":0"#, x), 1);
+    let error = KeyWithMetadata::deserialize_with(&mut deserializer(&json), human_readable())
+        .unwrap_err()
+        .to_string();
+    assert_eq!(error, "label 2: expected 32 bytes for value, got 34");
+}
+
+#[test]
+fn de_binary_ignores_human_readable() {
+    let key = P256PublicKey::from_coordinates([0x01; 32], [0x02; 32]);
+    let cbor = serde_cbor::to_vec(&key).unwrap();
+    let mut de = serde_cbor::Deserializer::from_slice(&cbor);
+    let de = P256PublicKey::deserialize_with(&mut de, human_readable());
+    assert_eq!(de.unwrap(), key);
+
+    #[cfg(feature = "cbor")]
+    {
+        let de = RawCoseKey::from_slice_exact(&cbor, human_readable());
+        assert_eq!(de.unwrap(), RawCoseKey::from(key));
+    }
+
+    // binary formats do not accept base64url text strings
+    let mut cbor = vec![0xa5, 0x01, 0x02, 0x03, 0x26, 0x20, 0x01, 0x21, 0x78, 43];
+    cbor.extend_from_slice(b"AQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQEBAQE");
+    cbor.extend_from_slice(&[0x22, 0x58, 0x20]);
+    cbor.extend_from_slice(&[0x02; 32]);
+    let mut de = serde_cbor::Deserializer::from_slice(&cbor);
+    let error = P256PublicKey::deserialize_with(&mut de, human_readable()).unwrap_err();
+    assert!(
+        error.to_string().contains("invalid type: string"),
+        "{error}"
+    );
+}
@@ -0,0 +1,166 @@
+#![cfg(all(
+    feature = "ecdh",
+    feature = "ed25519",
+    feature = "p256",
+    feature = "x25519",
+    feature = "curve25519-dalek",
+    feature = "p256-curve",
+    feature = "rand_core",
+    feature = "sha2"
+))]
+
+use cosey::{
+    Crv, EcdhEsHkdf256KeyPair, Ed25519KeyPair, Kty, P256KeyPair, PrivateKey, PublicKey,
+    X25519KeyPair,
+};
+use rand_core::{CryptoRng, RngCore};
+
+// Not random, but sufficient to get distinct keys
+struct CounterRng(u8);
+
+impl RngCore for CounterRng {
+    fn next_u32(&mut self) -> u32 {
+        rand_core::impls::next_u32_via_fill(self)
+    }
+
+    fn next_u64(&mut self) -> u64 {
+        rand_core::impls::next_u64_via_fill(self)
+    }
+
+    fn fill_bytes(&mut self, dest: &mut [u8]) {
+        for byte in dest {
+            self.0 = self.0.wrapping_add(1);
+            *byte = self.0;
+        }
+    }
+
+    fn try_fill_bytes(&mut self, dest: &mut [u8]) -> Result<(), rand_core::Error> {
+        self.fill_bytes(dest);
+        Ok(())
+    }
+}
+
+impl CryptoRng for CounterRng {}
+
+fn array(s: &str) -> [u8; 32] {
+    hex::decode(s).unwrap().try_into().unwrap()
+}
+
+#[test]
+fn from_secret() {
+    // RFC 9052, appendix C.7.2, key ID 11
+    let key_pair = P256KeyPair::from_secret(array(
+        "57c92077664146e876760c9520d054aa93c3afb04e306705db6090308507b4d3",
+    ))
+    .unwrap();
+    let public_key = key_pair.public_key();
+    assert_eq!(
+        hex::encode(public_key.x),
+        "bac5b11cad8f99f9c72b05cf4b9e26d244dc189f745228255a219a86d6a09eff"
+    );
+    assert_eq!(
+        hex::encode(public_key.y),
+        "20138bf82dc1b6d562be0fa54ab7804a3a64b6d72ccfed6b6fb6ed28bbfc117e"
+    );
+    let key_pair = EcdhEsHkdf256KeyPair::from_secret(*key_pair.secret()).unwrap();
+    assert_eq!(key_pair.public_key().x, public_key.x);
+    assert_eq!(key_pair.public_key().y, public_key.y);
+
+    // RFC 8032, section 7.1, test 1
+    let key_pair = Ed25519KeyPair::from_secret(array(
+        "9d61b19deffd5a60ba844af492ec2cc44449c5697b326919703bac031cae7f60",
+    ))
+    .unwrap();
+    assert_eq!(
+        hex::encode(key_pair.public_key().x),
+        "d75a980182b10ab7d54bfed3c964073a0ee172f3daa62325af021a68f707511a"
+    );
+
+    // RFC 7748, section 6.1
+    let key_pair = X25519KeyPair::from_secret(array(
+        "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a",
+    ))
+    .unwrap();
+    assert_eq!(
+        hex::encode(key_pair.public_key().pub_key),
+        "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a"
+    );
+
+    // P-256 private keys must be in [1, n - 1]
+    assert!(P256KeyPair::from_secret([0; 32]).is_none());
+    assert!(P256KeyPair::from_secret([0xff; 32]).is_none());
+}
+
+#[test]
+fn generate() {
+    let mut rng = CounterRng(0);
+    let first = P256KeyPair::generate(&mut rng);
+    let second = P256KeyPair::generate(&mut rng);
+    assert_ne!(first.public_key(), second.public_key());
+    assert!(!format!("{first:?}").contains("secret"));
+
+    let key_pair = Ed25519KeyPair::generate(&mut rng);
+    let private_key = key_pair.to_private_key();
+    assert_eq!((private_key.kty, private_key.crv), (Kty::Okp, Crv::Ed25519));
+    assert_eq!(
+        private_key.public_key(),
+        Some(PublicKey::Ed25519Key(key_pair.public_key()))
+    );
+
+    // the private key can be encoded as a COSE_Key
+    #[cfg(feature = "serde")]
+    {
+        let private_key = EcdhEsHkdf256KeyPair::generate(&mut rng).to_private_key();
+        let serialized = serde_cbor::to_vec(&private_key).unwrap();
+        let deserialized = serde_cbor::from_slice::<PrivateKey>(&serialized).unwrap();
+        assert_eq!(deserialized.d, private_key.d);
+        assert_eq!(deserialized.public_key(), private_key.public_key());
+    }
+
+    let key_pair = X25519KeyPair::generate(&mut rng);
+    let private_key = PrivateKey {
+        x: None,
+        ..key_pair.to_private_key()
+    };
+    assert_eq!(
+        private_key.public_key(),
+        Some(PublicKey::X25519Key(key_pair.public_key()))
+    );
+}
+
+#[cfg(feature = "test-vectors")]
+#[test]
+fn insecure_from_seed() {
+    let key_pair = P256KeyPair::insecure_from_seed(b"alice");
+    assert_eq!(
+        key_pair.secret(),
+        P256KeyPair::insecure_from_seed(b"alice").secret()
+    );
+    assert_ne!(
+        key_pair.secret(),
+        P256KeyPair::insecure_from_seed(b"bob").secret()
+    );
+    // key pairs of different types do not share private keys
+    assert_ne!(
+        key_pair.secret(),
+        EcdhEsHkdf256KeyPair::insecure_from_seed(b"alice").secret()
+    );
+
+    // the derivation must not change between releases
+    assert_eq!(
+        hex::encode(key_pair.public_key().x),
+        "d695f5cd3cdfdbc96b5a6a69f3110a98175d05bb2ed21ac93b38ad0324d7d672"
+    );
+    assert_eq!(
+        hex::encode(Ed25519KeyPair::insecure_from_seed(b"alice").public_key().x),
+        "9a3301cceb1b58ee5979d4e7e8872bb6bb16383df5961546b0e5ec8bc6e5cc2e"
+    );
+    assert_eq!(
+        hex::encode(
+            X25519KeyPair::insecure_from_seed(b"alice")
+                .public_key()
+                .pub_key
+        ),
+        "6f6bf135ed18c34adea3fee4a66db987bc5ec3811446ddf19b237a5ad260fc14"
+    );
+}
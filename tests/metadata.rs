@@ -0,0 +1,98 @@
+#![cfg(all(feature = "ed25519", feature = "serde"))]
+
+use cosey::metadata::KeyWithMetadata;
+use cosey::{Bytes, Ed25519PublicKey, Error, ParseOptions, PublicKey};
+
+fn ed25519_key() -> PublicKey {
+    Ed25519PublicKey { x: [0x02; 32] }.into()
+}
+
+fn encoded_key() -> String {
+    format!("a4010103272006215820{}", "02".repeat(32))
+}
+
+fn deserialize(data: &str, options: ParseOptions) -> Result<KeyWithMetadata, String> {
+    let data = hex::decode(data).unwrap();
+    let mut deserializer = serde_cbor::Deserializer::from_slice(&data);
+    KeyWithMetadata::deserialize_with(&mut deserializer, options).map_err(|e| e.to_string())
+}
+
+#[test]
+fn roundtrip() {
+    let entry = KeyWithMetadata::new(ed25519_key());
+    let serialized = serde_cbor::to_vec(&entry).unwrap();
+    assert_eq!(
+        hex::encode(&serialized),
+        format!("a201{}0500", encoded_key())
+    );
+    assert_eq!(
+        serde_cbor::from_slice::<KeyWithMetadata>(&serialized).unwrap(),
+        entry
+    );
+
+    let entry = KeyWithMetadata {
+        kid: Some(Bytes::from_slice(b"k1").unwrap()),
+        not_before: Some(1_700_000_000),
+        not_after: Some(1_800_000_000),
+        use_count: 3,
+        ..entry
+    };
+    let serialized = serde_cbor::to_vec(&entry).unwrap();
+    assert_eq!(
+        hex::encode(&serialized),
+        format!("a501{}02426b31031a6553f100041a6b49d2000503", encoded_key())
+    );
+    assert_eq!(
+        serde_cbor::from_slice::<KeyWithMetadata>(&serialized).unwrap(),
+        entry
+    );
+}
+
+#[test]
+fn validity() {
+    let mut entry = KeyWithMetadata::new(ed25519_key());
+    assert!(entry.is_valid_at(0));
+    assert!(entry.is_valid_at(u64::MAX));
+
+    entry.not_before = Some(10);
+    entry.not_after = Some(20);
+    assert!(!entry.is_valid_at(9));
+    assert!(entry.is_valid_at(10));
+    assert!(entry.is_valid_at(20));
+    assert!(!entry.is_valid_at(21));
+
+    entry.record_use();
+    assert_eq!(entry.use_count, 1);
+    entry.use_count = u64::MAX;
+    entry.record_use();
+    assert_eq!(entry.use_count, u64::MAX);
+}
+
+#[test]
+fn de_errors() {
+    let options = ParseOptions::new();
+    let key = encoded_key();
+    // only the key is required
+    assert_eq!(
+        deserialize(&format!("a101{key}"), options),
+        Ok(KeyWithMetadata::new(ed25519_key()))
+    );
+    assert_eq!(
+        deserialize("a10500", options),
+        Err(Error::MissingField("key").to_string())
+    );
+    assert_eq!(
+        deserialize(&format!("a201{key}01{key}"), options),
+        Err(Error::DuplicateField("key").to_string())
+    );
+    assert_eq!(
+        deserialize(&format!("a2050001{key}"), options),
+        Err(Error::NonCanonicalOrder.to_string())
+    );
+    assert!(deserialize(&format!("a2050001{key}"), options.any_order(true)).is_ok());
+    assert!(deserialize(&format!("a201{key}0600"), options).is_ok());
+    assert_eq!(
+        deserialize(&format!("a201{key}0600"), options.deny_unknown_fields(true)),
+        Err(Error::UnknownField(6).to_string())
+    );
+}
@@ -0,0 +1,109 @@
+use cosey::{
+    oid::{self, AlgorithmIdentifier, Parameters},
+    Alg, Crv, Kty,
+};
+
+#[test]
+fn signature_algorithms() {
+    for alg in [
+        Alg::Es256,
+        Alg::Es384,
+        Alg::Es512,
+        Alg::EdDsa,
+        Alg::Rs256,
+        Alg::Rs1,
+        Alg::Ps256,
+    ] {
+        let identifier = oid::signature_algorithm(alg).unwrap();
+        assert_eq!(oid::signature_alg(identifier), Some(alg));
+    }
+    assert_eq!(oid::signature_algorithm(Alg::EcdhEsHkdf256), None);
+    assert_eq!(oid::signature_algorithm(Alg::Ed448), None);
+
+    // the fully specified algorithms of RFC 9864 map to the identifiers of the polymorphic ones,
+    // which are returned for these identifiers
+    for (fully_specified, polymorphic) in [
+        (Alg::Esp256, Alg::Es256),
+        (Alg::Esp384, Alg::Es384),
+        (Alg::Esp512, Alg::Es512),
+        (Alg::Ed25519, Alg::EdDsa),
+    ] {
+        let identifier = oid::signature_algorithm(fully_specified).unwrap();
+        assert_eq!(Some(identifier), oid::signature_algorithm(polymorphic));
+        assert_eq!(oid::signature_alg(identifier), Some(polymorphic));
+    }
+    assert_eq!(
+        oid::signature_algorithm(Alg::Ed25519).unwrap().algorithm,
+        oid::ID_ED25519
+    );
+    assert_eq!(
+        oid::signature_algorithm(Alg::Esp256).unwrap().algorithm,
+        oid::ECDSA_WITH_SHA256
+    );
+}
+
+#[test]
+fn signature_parameters() {
+    let rs256 = oid::signature_algorithm(Alg::Rs256).unwrap();
+    assert_eq!(rs256.algorithm, oid::SHA256_WITH_RSA_ENCRYPTION);
+    assert_eq!(rs256.parameters, Parameters::Null);
+    // RFC 4055 allows absent parameters for RSA
+    let absent = AlgorithmIdentifier {
+        parameters: Parameters::Absent,
+        ..rs256
+    };
+    assert_eq!(oid::signature_alg(absent), Some(Alg::Rs256));
+
+    // ECDSA requires absent parameters
+    let es256 = AlgorithmIdentifier {
+        algorithm: oid::ECDSA_WITH_SHA256,
+        parameters: Parameters::Null,
+    };
+    assert_eq!(oid::signature_alg(es256), None);
+
+    // PSS with other parameters than those of PS256
+    let pss = AlgorithmIdentifier {
+        algorithm: oid::ID_RSASSA_PSS,
+        parameters: Parameters::Der(&[0x30, 0x00]),
+    };
+    assert_eq!(oid::signature_alg(pss), None);
+}
+
+#[test]
+fn public_key_algorithms() {
+    for (kty, crv) in [
+        (Kty::Ec2, Crv::P256),
+        (Kty::Ec2, Crv::P384),
+        (Kty::Ec2, Crv::P521),
+        (Kty::Okp, Crv::X25519),
+        (Kty::Okp, Crv::Ed25519),
+    ] {
+        let identifier = oid::public_key_algorithm(kty, crv).unwrap();
+        assert_eq!(oid::public_key_kty_crv(identifier), Some((kty, crv)));
+        assert_eq!(oid::public_key_kty(identifier), Some(kty));
+    }
+    assert_eq!(oid::public_key_algorithm(Kty::Okp, Crv::P256), None);
+    assert_eq!(oid::public_key_algorithm(Kty::Ec2, Crv::Ed25519), None);
+}
+
+#[test]
+fn rsa_public_key() {
+    assert_eq!(oid::RSA_PUBLIC_KEY.parameters, Parameters::Null);
+    assert_eq!(oid::public_key_kty(oid::RSA_PUBLIC_KEY), Some(Kty::Rsa));
+    assert_eq!(oid::public_key_kty_crv(oid::RSA_PUBLIC_KEY), None);
+    let absent = AlgorithmIdentifier {
+        algorithm: oid::RSA_ENCRYPTION,
+        parameters: Parameters::Absent,
+    };
+    assert_eq!(oid::public_key_kty(absent), Some(Kty::Rsa));
+}
+
+#[test]
+fn ec_public_key_requires_curve() {
+    let identifier = AlgorithmIdentifier {
+        algorithm: oid::ID_EC_PUBLIC_KEY,
+        parameters: Parameters::Absent,
+    };
+    assert_eq!(oid::public_key_kty_crv(identifier), None);
+    assert_eq!(oid::public_key_kty(identifier), None);
+}
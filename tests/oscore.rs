@@ -0,0 +1,88 @@
+#![cfg(feature = "serde")]
+
+use cosey::oscore::{OscoreInputMaterial, DEFAULT_AEAD_ALG, DEFAULT_HKDF_ALG};
+use cosey::{Alg, Bytes, Error, ParseOptions};
+
+fn deserialize(data: &str, options: ParseOptions) -> Result<OscoreInputMaterial, String> {
+    let data = hex::decode(data).unwrap();
+    let mut deserializer = serde_cbor::Deserializer::from_slice(&data);
+    OscoreInputMaterial::deserialize_with(&mut deserializer, options).map_err(|e| e.to_string())
+}
+
+#[test]
+fn roundtrip() {
+    let material = OscoreInputMaterial {
+        master_secret: Bytes::from_slice(&[0x01; 16]).unwrap(),
+        ..Default::default()
+    };
+    let serialized = serde_cbor::to_vec(&material).unwrap();
+    assert_eq!(
+        hex::encode(&serialized),
+        "a1025001010101010101010101010101010101"
+    );
+    assert_eq!(
+        serde_cbor::from_slice::<OscoreInputMaterial>(&serialized).unwrap(),
+        material
+    );
+    assert_eq!(material.version(), 1);
+    assert_eq!(material.hkdf_alg(), DEFAULT_HKDF_ALG);
+    assert_eq!(material.aead_alg(), DEFAULT_AEAD_ALG);
+    assert_eq!(material.master_salt(), &[]);
+
+    let material = OscoreInputMaterial {
+        id: Some(Bytes::from_slice(&[0x02]).unwrap()),
+        version: Some(1),
+        hkdf: Some(Alg::DirectHkdfSha512),
+        alg: Some(Alg::A128Gcm),
+        master_salt: Some(Bytes::from_slice(&[0x03; 8]).unwrap()),
+        id_context: Some(Bytes::from_slice(&[0x04; 4]).unwrap()),
+        ..material
+    };
+    let serialized = serde_cbor::to_vec(&material).unwrap();
+    assert_eq!(
+        hex::encode(&serialized),
+        "a70041020101025001010101010101010101010101010101032a040105480303030303030303064404040404"
+    );
+    assert_eq!(
+        serde_cbor::from_slice::<OscoreInputMaterial>(&serialized).unwrap(),
+        material
+    );
+    assert_eq!(material.hkdf_alg(), Alg::DirectHkdfSha512);
+    assert_eq!(material.aead_alg(), Alg::A128Gcm);
+    assert_eq!(material.master_salt(), &[0x03; 8]);
+}
+
+#[test]
+fn de_errors() {
+    let options = ParseOptions::new();
+    // ms is required
+    assert_eq!(
+        deserialize("a10101", options),
+        Err(Error::MissingField("ms").to_string())
+    );
+    // salt before ms
+    assert_eq!(
+        deserialize("a2054102024101", options),
+        Err(Error::NonCanonicalOrder.to_string())
+    );
+    assert!(deserialize("a2054102024101", options.any_order(true)).is_ok());
+    assert_eq!(
+        deserialize("a202410102410100", options.any_order(true)),
+        Err(Error::DuplicateField("ms").to_string())
+    );
+    // unknown fields are skipped unless denied
+    assert!(deserialize("a202410118636178", options).is_ok());
+    assert_eq!(
+        deserialize("a202410118636178", options.deny_unknown_fields(true)),
+        Err(Error::UnknownField(99).to_string())
+    );
+    assert_eq!(
+        deserialize(&format!("a1025821{}", "01".repeat(33)), options),
+        Err(Error::InvalidLength {
+            label: 2,
+            expected: 32,
+            actual: 33
+        }
+        .to_string())
+    );
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "cbor")]
+
+//! Malformed inputs must be rejected with an error, never with a panic.
+
+use cosey::{Ec2Variant, KeySetDecoder, ParseOptions, PublicKey, StreamDecoder, TagPolicy};
+
+const KEYS: &[&str] = &[
+    // P-256
+    "a5010203262001215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+    // Ed25519
+    "a4010103272006215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+    // TOTP
+    "a201040328",
+    // unknown fields with nested and indefinite-length values, a tag and a text label
+    "bf010203262001627a7ad818410018639f01a10000ff215820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff225820ffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffffff",
+];
+
+fn options() -> [ParseOptions; 4] {
+    [
+        ParseOptions::new(),
+        ParseOptions::default(),
+        ParseOptions::new()
+            .any_order(true)
+            .allow_non_minimal(true)
+            .tags(TagPolicy::Accept)
+            .text_labels(true)
+            .pad_short_coordinates(true)
+            .ec2_without_alg(Ec2Variant::EcdhEsHkdf256),
+        ParseOptions::new()
+            .deny_unknown_fields(true)
+            .require_alg(true)
+            .max_string_len(Some(8)),
+    ]
+}
+
+// Feeds `data` to all parsers.  The results are not checked, only that the parsers return.
+fn parse(data: &[u8]) {
+    for options in options() {
+        #[cfg(feature = "serde")]
+        let _ = cosey::RawCoseKey::take_from_slice(data, options);
+        #[cfg(feature = "serde")]
+        let _ = PublicKey::from_slice_exact(data, options);
+        let _ = PublicKey::decode(data, options);
+
+        let mut decoder = StreamDecoder::new(options);
+        for byte in data {
+            if !matches!(decoder.feed(core::slice::from_ref(byte)), Ok(None)) {
+                break;
+            }
+        }
+
+        let mut decoder = KeySetDecoder::new(options);
+        for byte in data {
+            if decoder.feed(core::slice::from_ref(byte)).is_err() {
+                break;
+            }
+        }
+    }
+    #[cfg(feature = "serde")]
+    parse_serde(data);
+}
+
+// Feeds `data` to the parsers that are built on serde.
+#[cfg(feature = "serde")]
+fn parse_serde(data: &[u8]) {
+    let _ = cosey::peek_kty(data);
+    let _ = cosey::peek_alg(data);
+    let _ = cosey::RawCoseKey::from_stored(data);
+    let _ = serde_cbor::from_slice::<PublicKey>(data);
+    #[cfg(feature = "p256")]
+    let _ = serde_cbor::from_slice::<cosey::P256PublicKeyRef<'_>>(data);
+
+    let mut auth_data = vec![0x00; 32];
+    auth_data.extend_from_slice(&[0x40, 0x00, 0x00, 0x00, 0x00]);
+    auth_data.extend_from_slice(&[0x00; 16]);
+    auth_data.extend_from_slice(&[0x00, 0x00]);
+    auth_data.extend_from_slice(data);
+    let _ = cosey::ctap::credential_public_key(&auth_data);
+    let _ = cosey::ctap::credential_public_key(&auth_data[..auth_data.len().min(60)]);
+}
+
+#[test]
+fn mutated_keys() {
+    for key in KEYS {
+        let key = hex::decode(key).unwrap();
+        for i in 0..key.len() {
+            parse(&key[..i]);
+            let mut mutated = key.clone();
+            for byte in 0..=u8::MAX {
+                mutated[i] = byte;
+                parse(&mutated);
+            }
+        }
+    }
+}
+
+#[test]
+fn short_inputs() {
+    for a in 0..=u8::MAX {
+        parse(&[a]);
+        for b in 0..=u8::MAX {
+            parse(&[a, b]);
+        }
+    }
+}
+
+quickcheck::quickcheck! {
+    fn arbitrary_input(data: Vec<u8>) -> bool {
+        parse(&data);
+        true
+    }
+}
@@ -0,0 +1,114 @@
+#![cfg(feature = "serde")]
+
+use cosey::{Crv, Error, Kty, ParseOptions, PrivateKey};
+#[cfg(feature = "ed25519")]
+use cosey::{Ed25519PublicKey, PublicKey};
+
+// RFC 7748, section 6.1
+const X25519_D: &str = "77076d0a7318a57d3c16c17251b26645df4c2f87ebc0992ab177fba51db92c2a";
+#[cfg(all(feature = "x25519", feature = "curve25519-dalek"))]
+const X25519_X: &str = "8520f0098930a754748b7ddcb43ef75a0dbf3a0d26381af4eba4a98eaa9b4e6a";
+
+fn deserialize(data: &str, options: ParseOptions) -> Result<PrivateKey, String> {
+    let data = hex::decode(data).unwrap();
+    let mut deserializer = serde_cbor::Deserializer::from_slice(&data);
+    PrivateKey::deserialize_with(&mut deserializer, options).map_err(|e| e.to_string())
+}
+
+#[test]
+fn d_only() {
+    let data = format!("a30101200423 5820{X25519_D}").replace(' ', "");
+    let key = deserialize(&data, ParseOptions::new()).unwrap();
+    assert_eq!(key.kty, Kty::Okp);
+    assert_eq!(key.crv, Crv::X25519);
+    assert_eq!(key.x, None);
+    assert_eq!(hex::encode(key.d), X25519_D);
+    assert_eq!(hex::encode(serde_cbor::to_vec(&key).unwrap()), data);
+
+    #[cfg(all(feature = "x25519", feature = "curve25519-dalek"))]
+    assert_eq!(
+        key.public_key(),
+        Some(cosey::PublicKey::X25519Key(cosey::X25519PublicKey {
+            pub_key: hex::decode(X25519_X).unwrap().try_into().unwrap(),
+        }))
+    );
+    #[cfg(not(all(feature = "x25519", feature = "curve25519-dalek")))]
+    assert_eq!(key.public_key(), None);
+
+    let data = format!("a30102200123 5820{}", "01".repeat(32)).replace(' ', "");
+    let key = deserialize(&data, ParseOptions::new()).unwrap();
+    assert_eq!(key.y, None);
+    #[cfg(all(feature = "p256", feature = "p256-curve"))]
+    assert_eq!(
+        key.public_key(),
+        cosey::P256KeyPair::from_secret(key.d).map(|pair| pair.public_key().into())
+    );
+    #[cfg(not(all(feature = "p256", feature = "p256-curve")))]
+    assert_eq!(key.public_key(), None);
+}
+
+#[test]
+fn with_public_key() {
+    let key = PrivateKey {
+        kty: Kty::Okp,
+        alg: None,
+        crv: Crv::Ed25519,
+        x: Some([0x02; 32]),
+        y: None,
+        d: [0x03; 32],
+    };
+    let serialized = serde_cbor::to_vec(&key).unwrap();
+    let deserialized = serde_cbor::from_slice::<PrivateKey>(&serialized).unwrap();
+    assert_eq!(serde_cbor::to_vec(&deserialized).unwrap(), serialized);
+    #[cfg(feature = "ed25519")]
+    assert_eq!(
+        key.public_key(),
+        Some(PublicKey::Ed25519Key(Ed25519PublicKey { x: [0x02; 32] }))
+    );
+    #[cfg(not(feature = "ed25519"))]
+    assert_eq!(key.public_key(), None);
+    assert!(!format!("{key:?}").contains("3, 3"));
+}
+
+#[test]
+fn de_errors() {
+    let options = ParseOptions::new();
+    let error = |data: &str, options| deserialize(data, options).err();
+    let d = format!("235820{}", "01".repeat(32));
+    assert_eq!(
+        error("a201012004", options),
+        Some(Error::MissingField("d").to_string())
+    );
+    // OKP keys do not have a y-coordinate
+    assert_eq!(
+        error(&format!("a4010120042258{}{d}", "20".repeat(33)), options),
+        Some(Error::UnknownField(-3).to_string())
+    );
+    assert_eq!(
+        error(&format!("a3010120042350{}", "01".repeat(16)), options),
+        Some(
+            Error::InvalidLength {
+                label: -4,
+                expected: 32,
+                actual: 16
+            }
+            .to_string()
+        )
+    );
+    // OKP key on P-256
+    assert_eq!(
+        error(&format!("a301012001{d}"), options),
+        Some(
+            Error::UnsupportedValue {
+                label: -1,
+                value: 1
+            }
+            .to_string()
+        )
+    );
+    assert_eq!(
+        error(&format!("a320040101{d}"), options),
+        Some(Error::NonCanonicalOrder.to_string())
+    );
+    assert!(deserialize(&format!("a320040101{d}"), options.any_order(true)).is_ok());
+}
@@ -0,0 +1,39 @@
+#![cfg(all(
+    feature = "test-vectors",
+    feature = "cbor",
+    feature = "serde",
+    any(feature = "p256", feature = "ed25519")
+))]
+
+use cosey::test_vectors::*;
+use cosey::{ParseOptions, PublicKey};
+
+fn check(key: impl Into<PublicKey>, encoded: &[u8]) {
+    let key = key.into();
+    assert_eq!(key.to_bytes::<128>().unwrap(), encoded);
+    assert_eq!(
+        PublicKey::from_slice_exact(encoded, ParseOptions::new()),
+        Ok(key)
+    );
+}
+
+#[cfg(feature = "p256")]
+#[test]
+fn p256_keys() {
+    check(RFC9052_MERIADOC, &RFC9052_MERIADOC_ENCODED);
+    check(RFC9052_KID_11, &RFC9052_KID_11_ENCODED);
+    check(RFC9052_PEREGRIN, &RFC9052_PEREGRIN_ENCODED);
+    check(WEBAUTHN_ES256, &WEBAUTHN_ES256_ENCODED);
+    for key in [RFC9052_MERIADOC, RFC9052_KID_11, RFC9052_PEREGRIN] {
+        assert!(key.coordinates_in_range());
+    }
+}
+
+#[cfg(feature = "ed25519")]
+#[test]
+fn ed25519_keys() {
+    check(RFC8032_TEST_1, &RFC8032_TEST_1_ENCODED);
+    assert!(!RFC8032_TEST_1.is_small_order());
+    #[cfg(feature = "curve25519-dalek")]
+    assert!(RFC8032_TEST_1.is_valid_point());
+}